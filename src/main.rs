@@ -1,16 +1,48 @@
+use std::f32::consts::FRAC_PI_2;
+
 use bevy::{camera::visibility::RenderLayers, prelude::*};
 
+use ui::{
+    command_registry::{CommandRegistryAppExt, UiCommand},
+    shortcuts::{Modifiers, Shortcut, ShortcutScope},
+};
+
 mod ui;
 
+/// Start position of the scene camera.
+const CAMERA_POSITION: Vec3 = Vec3::new(-10.0, 2.0, 10.0);
+
+#[derive(Resource, Default)]
+struct ShowGrid(bool);
+
 fn main() {
     App::new()
         .add_plugins(DefaultPlugins)
         .add_plugins(ui::UiPlugin {
             editor_render_layer: 1,
+            ..default()
         })
         // Setup window
         .insert_resource(ClearColor(Color::BLACK))
+        .init_resource::<ShowGrid>()
+        .add_ui_command(
+            UiCommand::new("example.toggle_grid", "Show Grid")
+                .with_shortcut(Shortcut::new(Modifiers::NONE, KeyCode::KeyG))
+                .with_scope(ShortcutScope::Viewport)
+                .in_menu("camera")
+                .checked_if(|show_grid: Res<ShowGrid>| show_grid.0),
+            |mut show_grid: ResMut<ShowGrid>| show_grid.0 = !show_grid.0,
+        )
+        .add_ui_command(
+            UiCommand::new("example.reset_camera", "Reset Camera")
+                .in_menu("camera")
+                .enabled_if(|cameras: Query<&Transform, With<Camera3d>>| {
+                    cameras.iter().any(|transform| transform.translation != CAMERA_POSITION)
+                }),
+            reset_camera,
+        )
         .add_systems(Startup, setup_scene)
+        .add_systems(Update, draw_grid)
         .run();
 }
 
@@ -38,7 +70,7 @@ fn setup_scene(
     // Camera
     commands.spawn((
         Camera3d::default(),
-        Transform::from_translation(Vec3::new(-10.0, 2.0, 10.0)).looking_at(Vec3::ZERO, Dir3::Y),
+        Transform::from_translation(CAMERA_POSITION).looking_at(Vec3::ZERO, Dir3::Y),
         RenderLayers::layer(0),
         Camera {
             order: 0,
@@ -46,3 +78,20 @@ fn setup_scene(
         }
     ));
 }
+
+fn reset_camera(mut cameras: Query<&mut Transform, With<Camera3d>>) {
+    for mut transform in cameras.iter_mut() {
+        *transform = Transform::from_translation(CAMERA_POSITION).looking_at(Vec3::ZERO, Dir3::Y);
+    }
+}
+
+fn draw_grid(show_grid: Res<ShowGrid>, mut gizmos: Gizmos) {
+    if show_grid.0 {
+        gizmos.grid(
+            Isometry3d::from_rotation(Quat::from_rotation_x(FRAC_PI_2)),
+            UVec2::splat(20),
+            Vec2::splat(1.0),
+            Color::srgba(1.0, 1.0, 1.0, 0.2),
+        );
+    }
+}
@@ -1,16 +1,35 @@
-use bevy::{platform::collections::HashMap, prelude::*};
+use std::marker::PhantomData;
+
+use bevy::{
+    ecs::component::Mutable,
+    platform::collections::HashMap,
+    prelude::*,
+    reflect::{DynamicEnum, DynamicVariant, TypeInfo, Typed},
+};
+
+use crate::ui::transition::TransitionSettings;
 
 pub struct ColorsPlugin;
 
 impl Plugin for ColorsPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(UiColors::default())
+            .register_type::<EditorTextColor>()
+            .register_type::<EditorBackgroundColor>()
             .add_systems(PostUpdate, update_colors)
+            .add_systems(
+                PostUpdate,
+                (
+                    animate_color_transitions::<TextColor>,
+                    animate_color_transitions::<BackgroundColor>,
+                )
+                    .after(update_colors),
+            )
             .add_systems(PostUpdate, add_colors);
     }
 }
 
-#[derive(Hash, Eq, PartialEq, Clone, Copy, Debug)]
+#[derive(Reflect, Hash, Eq, PartialEq, Clone, Copy, Debug)]
 pub enum EditorColor {
     MenuBar,
     MenuBarButtonText,
@@ -27,66 +46,336 @@ pub enum EditorColor {
     Button,
     InputField,
     InputFieldText,
+    /// Background of selected text in input fields.
+    TextSelection,
+    /// Error messages and invalid values.
+    Error,
+    /// Dims the editor behind modal dialogs.
+    Overlay,
+}
+
+impl EditorColor {
+    /// Returns all colors, in the order they are declared.
+    pub fn all() -> Vec<EditorColor> {
+        let TypeInfo::Enum(enum_info) = EditorColor::type_info() else {
+            return Vec::new();
+        };
+        enum_info
+            .variant_names()
+            .iter()
+            .filter_map(|variant| EditorColor::from_reflect(&DynamicEnum::new(*variant, DynamicVariant::Unit)))
+            .collect()
+    }
+
+    /// The color that is used when a theme doesn't set this one. Text colors fall back to text colors
+    /// and backgrounds to backgrounds, so the fallbacks stay readable. At the end of the chain is [`Self::default_color`].
+    pub fn fallback(self) -> Option<EditorColor> {
+        match self {
+            EditorColor::MenuBarButtonText
+            | EditorColor::FadedText
+            | EditorColor::InputFieldText
+            | EditorColor::HeadingText
+            | EditorColor::Error => Some(EditorColor::Text),
+            EditorColor::MenuBarButtonHoverText => Some(EditorColor::MenuBarButtonText),
+            EditorColor::MenuBarButtonHover | EditorColor::TabBar => Some(EditorColor::MenuBar),
+            EditorColor::TabHover => Some(EditorColor::MenuBarButtonHover),
+            EditorColor::TextSelection => Some(EditorColor::TabHover),
+            EditorColor::MenuBar
+            | EditorColor::TabActive
+            | EditorColor::Heading
+            | EditorColor::Button
+            | EditorColor::InputField => Some(EditorColor::Background),
+            EditorColor::Background | EditorColor::Text | EditorColor::Overlay => None,
+        }
+    }
+
+    /// The built-in dark color.
+    pub fn default_color(self) -> Color {
+        match self {
+            EditorColor::MenuBar => Srgba::hex("#3B3B3B").unwrap().into(),
+            EditorColor::MenuBarButtonText => Color::WHITE,
+            EditorColor::MenuBarButtonHover => Srgba::hex("#2C2C2C").unwrap().into(),
+            EditorColor::MenuBarButtonHoverText => Color::WHITE,
+            EditorColor::TabBar => Srgba::hex("#2B2B2B").unwrap().into(),
+            EditorColor::Background => Srgba::hex("#1B1B1B").unwrap().into(),
+            EditorColor::Text => Color::WHITE,
+            EditorColor::FadedText => Srgba::hex("#959595").unwrap().into(),
+            EditorColor::TabHover => Srgba::hex("#353535").unwrap().into(),
+            EditorColor::TabActive => Srgba::hex("#1B1B1B").unwrap().into(),
+            EditorColor::Heading => Color::WHITE,
+            EditorColor::HeadingText => Color::BLACK,
+            EditorColor::Button => Srgba::hex("#0C0C0C").unwrap().into(),
+            EditorColor::InputField => Color::WHITE,
+            EditorColor::InputFieldText => Color::BLACK,
+            EditorColor::TextSelection => Srgba::hex("#9EC5F8").unwrap().into(),
+            EditorColor::Error => Srgba::hex("#FF6666").unwrap().into(),
+            EditorColor::Overlay => Color::srgba(0.0, 0.0, 0.0, 0.5),
+        }
+    }
+}
+
+/// Text colors and the backgrounds they are shown on, which should have a contrast of at least
+/// [`MIN_CONTRAST_RATIO`].
+pub const CONTRAST_PAIRS: [(EditorColor, EditorColor); 16] = [
+    (EditorColor::Text, EditorColor::Background),
+    (EditorColor::Text, EditorColor::MenuBar),
+    (EditorColor::Text, EditorColor::MenuBarButtonHover),
+    (EditorColor::Text, EditorColor::TabBar),
+    (EditorColor::Text, EditorColor::TabHover),
+    (EditorColor::Text, EditorColor::TabActive),
+    (EditorColor::Text, EditorColor::Button),
+    (EditorColor::FadedText, EditorColor::Background),
+    (EditorColor::FadedText, EditorColor::MenuBarButtonHover),
+    (EditorColor::FadedText, EditorColor::Button),
+    (EditorColor::MenuBarButtonText, EditorColor::MenuBar),
+    (EditorColor::MenuBarButtonHoverText, EditorColor::MenuBarButtonHover),
+    (EditorColor::HeadingText, EditorColor::Heading),
+    (EditorColor::InputFieldText, EditorColor::InputField),
+    (EditorColor::InputFieldText, EditorColor::TextSelection),
+    (EditorColor::Error, EditorColor::Background),
+];
+
+/// Minimum contrast ratio for normal text in WCAG 2 level AA.
+pub const MIN_CONTRAST_RATIO: f32 = 4.5;
+
+/// The WCAG 2 contrast ratio of two colors, from 1:1 for equal colors to 21:1 for black and white.
+pub fn contrast_ratio(a: Color, b: Color) -> f32 {
+    let (a, b) = (a.luminance(), b.luminance());
+    (a.max(b) + 0.05) / (a.min(b) + 0.05)
 }
 
 #[derive(Resource)]
-#[allow(dead_code)]
 pub struct UiColors {
     pub editor_colors: HashMap<EditorColor, Color>,
 }
 
 impl Default for UiColors {
     fn default() -> Self {
-        let mut editor_colors = HashMap::new();
-        editor_colors.insert(EditorColor::MenuBar, Srgba::hex("#3B3B3B").unwrap().into());
-        editor_colors.insert(EditorColor::MenuBarButtonText, Color::WHITE);
-        editor_colors.insert(EditorColor::MenuBarButtonHover, Srgba::hex("#2C2C2C").unwrap().into());
-        editor_colors.insert(EditorColor::MenuBarButtonHoverText, Color::WHITE);
-        editor_colors.insert(EditorColor::TabBar, Srgba::hex("#2B2B2B").unwrap().into());
-        editor_colors.insert(EditorColor::Background, Srgba::hex("#1B1B1B").unwrap().into());
-        editor_colors.insert(EditorColor::Text, Color::WHITE);
-        editor_colors.insert(EditorColor::FadedText, Srgba::hex("#808080").unwrap().into());
-        editor_colors.insert(EditorColor::TabHover, Srgba::hex("#353535").unwrap().into());
-        editor_colors.insert(EditorColor::TabActive, Srgba::hex("#1B1B1B").unwrap().into());
-        editor_colors.insert(EditorColor::Heading, Color::WHITE);
-        editor_colors.insert(EditorColor::HeadingText, Color::BLACK);
-        editor_colors.insert(EditorColor::Button, Srgba::hex("#0C0C0C").unwrap().into());
-        editor_colors.insert(EditorColor::InputField, Color::WHITE);
-        editor_colors.insert(EditorColor::InputFieldText, Color::BLACK);
-        Self { editor_colors }
-    }
-}
-
-#[derive(Component, Clone, Copy)]
+        Self {
+            editor_colors: EditorColor::all()
+                .into_iter()
+                .map(|editor_color| (editor_color, editor_color.default_color()))
+                .collect(),
+        }
+    }
+}
+
+impl UiColors {
+    /// Returns the color, or the first color in its fallback chain that is set, see [`EditorColor::fallback`].
+    pub fn get(&self, editor_color: EditorColor) -> Color {
+        let mut next = Some(editor_color);
+        while let Some(color) = next {
+            if let Some(color) = self.editor_colors.get(&color) {
+                return *color;
+            }
+            next = color.fallback();
+        }
+        editor_color.default_color()
+    }
+
+    /// Builds the colors of a theme, where the colors that the theme doesn't set are filled in from their fallbacks.
+    /// Returns the missing colors as well.
+    pub fn from_theme(colors: &HashMap<EditorColor, Color>) -> (Self, Vec<EditorColor>) {
+        let theme_colors = Self {
+            editor_colors: colors.clone(),
+        };
+        let missing: Vec<EditorColor> = EditorColor::all()
+            .into_iter()
+            .filter(|editor_color| !colors.contains_key(editor_color))
+            .collect();
+        let editor_colors = EditorColor::all()
+            .into_iter()
+            .map(|editor_color| (editor_color, theme_colors.get(editor_color)))
+            .collect();
+        (Self { editor_colors }, missing)
+    }
+
+    /// Returns the text and background pairs of [`CONTRAST_PAIRS`] whose contrast is below [`MIN_CONTRAST_RATIO`],
+    /// with their contrast ratio.
+    pub fn low_contrast_pairs(&self) -> Vec<(EditorColor, EditorColor, f32)> {
+        CONTRAST_PAIRS
+            .into_iter()
+            .map(|(text, background)| (text, background, contrast_ratio(self.get(text), self.get(background))))
+            .filter(|(_, _, ratio)| *ratio < MIN_CONTRAST_RATIO)
+            .collect()
+    }
+}
+
+#[derive(Component, Reflect, Clone, Copy)]
+#[reflect(Component)]
 pub struct EditorTextColor(pub EditorColor, pub Option<EditorColor>, pub Option<EditorColor>);
 
-#[derive(Component, Clone, Copy)]
+#[derive(Component, Reflect, Clone, Copy)]
+#[reflect(Component)]
 pub struct EditorBackgroundColor(pub EditorColor, pub Option<EditorColor>, pub Option<EditorColor>);
 
+impl EditorBackgroundColor {
+    /// Background that keeps its color when the element is hovered or pressed.
+    pub fn new(color: EditorColor) -> Self {
+        Self(color, None, None)
+    }
+}
+
+/// Opacity of the EditorTextColor and EditorBackgroundColor of an element, e.g. while its panel fades in or out.
+#[derive(Component, Clone, Copy, PartialEq, Debug)]
+pub struct EditorOpacity(pub f32);
+
+/// Multiplies the alpha of a color by the opacity of its element.
+fn with_opacity(color: Color, opacity: Option<&EditorOpacity>) -> Color {
+    match opacity {
+        Some(opacity) => color.with_alpha(color.alpha() * opacity.0),
+        None => color,
+    }
+}
+
 /// Update the colors of the text and background of the elements that have the EditorTextColor and EditorBackgroundColor components.
-/// This system is run when the UiColors resource is changed.
+/// Elements are updated when their colors, interaction or opacity changed, and all of them are repainted when the UiColors
+/// resource is changed, e.g. by another theme. Only changes of the interaction fade to the new color, the others are applied
+/// right away.
+#[allow(clippy::type_complexity)]
 pub fn update_colors(
-    mut text_colors: Query<(&mut TextColor, &EditorTextColor, Option<&Interaction>), Or<(Changed<EditorTextColor>, Changed<Interaction>)>>,
-    mut background_colors: Query<(&mut BackgroundColor, &EditorBackgroundColor, Option<&Interaction>), Or<(Changed<EditorBackgroundColor>, Changed<Interaction>)>>,
-    ui_colors: Res<UiColors>
+    mut commands: Commands,
+    mut text_colors: Query<(
+        Entity,
+        &mut TextColor,
+        Ref<EditorTextColor>,
+        Option<Ref<Interaction>>,
+        Option<Ref<EditorOpacity>>,
+        Has<ColorTransition<TextColor>>,
+    )>,
+    mut background_colors: Query<(
+        Entity,
+        &mut BackgroundColor,
+        Ref<EditorBackgroundColor>,
+        Option<Ref<Interaction>>,
+        Option<Ref<EditorOpacity>>,
+        Has<ColorTransition<BackgroundColor>>,
+    )>,
+    ui_colors: Res<UiColors>,
+    transition_settings: Res<TransitionSettings>,
 ) {
-    for (mut text_color, editor_text_color, interaction) in text_colors.iter_mut() {
-        let color = match (interaction, editor_text_color.1, editor_text_color.2) {
+    let repaint_all = ui_colors.is_changed();
+    for (entity, text_color, editor_text_color, interaction, opacity, has_transition) in text_colors.iter_mut() {
+        let is_interaction_changed = interaction.as_ref().is_some_and(Ref::is_changed);
+        let is_opacity_changed = opacity.as_ref().is_some_and(Ref::is_changed);
+        if !repaint_all && !editor_text_color.is_changed() && !is_interaction_changed && !is_opacity_changed {
+            continue;
+        }
+        let color = match (interaction.as_deref(), editor_text_color.1, editor_text_color.2) {
             (Some(Interaction::Pressed), _, Some(clicked_color)) => clicked_color,
             (Some(Interaction::Pressed), Some(hover_color), None) => hover_color,
             (Some(Interaction::Hovered), Some(hover_color), _) => hover_color,
             (_, _, _) => editor_text_color.0,
         };
-        text_color.0 = ui_colors.editor_colors[&color];
+        let fade = !repaint_all
+            && !editor_text_color.is_changed()
+            && !is_opacity_changed
+            && transition_settings.color_duration > 0.0;
+        let color = with_opacity(ui_colors.get(color), opacity.as_deref());
+        paint(&mut commands, entity, text_color, color, fade, has_transition);
     }
-    for (mut background_color, editor_background_color, interaction) in background_colors.iter_mut() {
-        let color = match (interaction, editor_background_color.1, editor_background_color.2) {
+    for (entity, background_color, editor_background_color, interaction, opacity, has_transition) in
+        background_colors.iter_mut()
+    {
+        let is_interaction_changed = interaction.as_ref().is_some_and(Ref::is_changed);
+        let is_opacity_changed = opacity.as_ref().is_some_and(Ref::is_changed);
+        if !repaint_all && !editor_background_color.is_changed() && !is_interaction_changed && !is_opacity_changed {
+            continue;
+        }
+        let color = match (interaction.as_deref(), editor_background_color.1, editor_background_color.2) {
             (Some(Interaction::Pressed), _, Some(clicked_color)) => clicked_color,
             (Some(Interaction::Pressed), Some(hover_color), None) => hover_color,
             (Some(Interaction::Hovered), Some(hover_color), _) => hover_color,
             (_, _, _) => editor_background_color.0,
         };
-        background_color.0 = ui_colors.editor_colors[&color];
+        let fade = !repaint_all
+            && !editor_background_color.is_changed()
+            && !is_opacity_changed
+            && transition_settings.color_duration > 0.0;
+        let color = with_opacity(ui_colors.get(color), opacity.as_deref());
+        paint(&mut commands, entity, background_color, color, fade, has_transition);
+    }
+}
+
+/// Sets the color of an element, or starts fading from its current color to the new one.
+fn paint<C: UiColorComponent>(
+    commands: &mut Commands,
+    entity: Entity,
+    mut component: Mut<C>,
+    color: Color,
+    fade: bool,
+    has_transition: bool,
+) {
+    if fade {
+        commands.entity(entity).try_insert(ColorTransition::<C> {
+            from: component.color(),
+            to: color,
+            elapsed: 0.0,
+            marker: PhantomData,
+        });
+        return;
+    }
+    if has_transition {
+        commands.entity(entity).try_remove::<ColorTransition<C>>();
+    }
+    *component.color_mut() = color;
+}
+
+/// Components that hold the color of an element.
+pub trait UiColorComponent: Component<Mutability = Mutable> {
+    fn color(&self) -> Color;
+    fn color_mut(&mut self) -> &mut Color;
+}
+
+impl UiColorComponent for TextColor {
+    fn color(&self) -> Color {
+        self.0
+    }
+
+    fn color_mut(&mut self) -> &mut Color {
+        &mut self.0
+    }
+}
+
+impl UiColorComponent for BackgroundColor {
+    fn color(&self) -> Color {
+        self.0
+    }
+
+    fn color_mut(&mut self) -> &mut Color {
+        &mut self.0
+    }
+}
+
+/// A fade of the TextColor or BackgroundColor of an element, started by [`update_colors`] when its interaction changed.
+#[derive(Component)]
+pub struct ColorTransition<C: UiColorComponent> {
+    from: Color,
+    to: Color,
+    /// Seconds since the transition started.
+    elapsed: f32,
+    marker: PhantomData<C>,
+}
+
+/// Mixes the colors of the running transitions, and removes the transitions once they reached their color.
+fn animate_color_transitions<C: UiColorComponent>(
+    mut commands: Commands,
+    mut transitions: Query<(Entity, &mut ColorTransition<C>, &mut C)>,
+    transition_settings: Res<TransitionSettings>,
+    time: Res<Time>,
+) {
+    for (entity, mut transition, mut component) in transitions.iter_mut() {
+        transition.elapsed += time.delta_secs();
+        let progress = transition.elapsed / transition_settings.color_duration;
+        if progress >= 1.0 || !progress.is_finite() {
+            *component.color_mut() = transition.to;
+            commands.entity(entity).try_remove::<ColorTransition<C>>();
+            continue;
+        }
+        // Mixing in Oklab keeps the brightness of the intermediate colors even
+        let from = Oklaba::from(transition.from);
+        let to = Oklaba::from(transition.to);
+        *component.color_mut() = from.mix(&to, transition_settings.ease(progress)).into();
     }
 }
 
@@ -95,13 +384,62 @@ pub fn update_colors(
 pub fn add_colors(
     mut commands: Commands,
     ui_colors: Res<UiColors>,
-    text_colors: Query<(Entity, &EditorTextColor), Changed<EditorTextColor>>,
-    background_colors: Query<(Entity, &EditorBackgroundColor), Changed<EditorBackgroundColor>>,
+    text_colors: Query<(Entity, &EditorTextColor, Option<&EditorOpacity>), Changed<EditorTextColor>>,
+    background_colors: Query<(Entity, &EditorBackgroundColor, Option<&EditorOpacity>), Changed<EditorBackgroundColor>>,
 ) {
-    for (entity, editor_text_color) in text_colors.iter() {
-        commands.entity(entity).insert(TextColor(ui_colors.editor_colors[&editor_text_color.0]));
+    for (entity, editor_text_color, opacity) in text_colors.iter() {
+        commands.entity(entity).insert(TextColor(with_opacity(ui_colors.get(editor_text_color.0), opacity)));
+    }
+    for (entity, editor_background_color, opacity) in background_colors.iter() {
+        let color = with_opacity(ui_colors.get(editor_background_color.0), opacity);
+        commands.entity(entity).insert(BackgroundColor(color));
     }
-    for (entity, editor_background_color) in background_colors.iter() {
-        commands.entity(entity).insert(BackgroundColor(ui_colors.editor_colors[&editor_background_color.0]));
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::asset::AssetPlugin;
+
+    use super::*;
+    use crate::ui::theme::{Theme, ThemePlugin};
+
+    fn parse_shipped_theme(bytes: &[u8]) -> Theme {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default(), ThemePlugin));
+        let registry = app.world().resource::<AppTypeRegistry>().read();
+        Theme::parse(bytes, &registry).unwrap()
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn missing_colors_resolve_through_their_fallbacks() {
+        let text = Color::srgb(0.9, 0.8, 0.7);
+        let colors = HashMap::from_iter([(EditorColor::Text, text)]);
+        let (ui_colors, missing) = UiColors::from_theme(&colors);
+
+        assert_eq!(ui_colors.get(EditorColor::FadedText), text);
+        assert_eq!(ui_colors.get(EditorColor::MenuBarButtonHoverText), text);
+        assert_eq!(ui_colors.get(EditorColor::Background), EditorColor::Background.default_color());
+        assert!(missing.contains(&EditorColor::FadedText));
+        assert!(!missing.contains(&EditorColor::Text));
+    }
+
+    #[test]
+    fn black_and_white_have_the_highest_contrast() {
+        assert!((contrast_ratio(Color::WHITE, Color::BLACK) - 21.0).abs() < 1e-4);
+        assert!((contrast_ratio(Color::BLACK, Color::WHITE) - 21.0).abs() < 1e-4);
+        assert_eq!(contrast_ratio(Color::WHITE, Color::WHITE), 1.0);
+    }
+
+    #[test]
+    fn shipped_themes_have_enough_contrast() {
+        for bytes in [
+            include_bytes!("../../assets/themes/dark.theme.ron").as_slice(),
+            include_bytes!("../../assets/themes/light.theme.ron"),
+            include_bytes!("../../assets/themes/high_contrast.theme.ron"),
+        ] {
+            let theme = parse_shipped_theme(bytes);
+            let (ui_colors, _) = UiColors::from_theme(&theme.colors);
+            assert_eq!(ui_colors.low_contrast_pairs(), Vec::new(), "{}", theme.name);
+        }
+    }
+}
@@ -0,0 +1,501 @@
+use bevy::{
+    input::{ButtonState, keyboard::KeyboardInput},
+    prelude::*,
+    ui::FocusPolicy,
+};
+
+use crate::ui::{
+    ClickAction, ClickActionDisabled, EditorUiElement, UiEvent,
+    command_registry::CommandRegistry,
+    colors::{EditorBackgroundColor, EditorColor, EditorTextColor},
+    elements::{EditorUi, MenuBarButton, MenuBarDropdownRoot, Modal, ToolButton, reactive_element},
+    reactive::Reconcile,
+    shortcuts::{ShortcutScope, Shortcuts, event_label, split_words},
+    style::{
+        EditorBorderRadius, EditorFontSize, EditorGap, EditorPadding, EditorTop, EditorWidth, FontSize, Height, Radius,
+        Spacing, Width,
+    },
+    tabs::OpenDocuments,
+    text_input::{
+        AutoFocus, TextInput, TextInputCancelled, TextInputChanged, TextInputFocus, TextInputSubmitted,
+        update_text_input_focus,
+    },
+};
+
+/// Number of commands that are shown at once.
+const MAX_VISIBLE_ROWS: usize = 12;
+
+pub struct CommandPalettePlugin;
+
+impl Plugin for CommandPalettePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, open_command_palette)
+            .add_systems(Update, handle_command_palette_input.after(update_text_input_focus))
+            .add_systems(Update, navigate_command_palette)
+            .add_systems(
+                Update,
+                handle_command_palette_events
+                    .after(open_command_palette)
+                    .after(handle_command_palette_input),
+            )
+            .add_systems(PostUpdate, reactive_command_palette);
+    }
+}
+
+/// Actions of the command palette. Commands are referred to by their index in the list of matches.
+#[derive(Reflect, Hash, Eq, PartialEq, Clone, Copy, Debug)]
+pub enum CommandPaletteAction {
+    Open,
+    Execute(usize),
+    Close,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct PaletteEntry {
+    pub label: String,
+    pub shortcut: Option<String>,
+    pub event: UiEvent,
+}
+
+/// Overlay that lists the commands of the editor, filtered by the typed query.
+#[derive(Component, Clone, PartialEq, Default)]
+#[require(Modal, ShortcutScope::CommandPalette)]
+pub struct CommandPalette {
+    pub entries: Vec<PaletteEntry>,
+    pub query: String,
+    /// Indices into the entries of the commands that match the query, best match first.
+    pub matches: Vec<usize>,
+    /// Index into the matches of the highlighted command.
+    pub selected: usize,
+    /// Index into the matches of the first visible command.
+    pub first_visible: usize,
+}
+
+impl CommandPalette {
+    fn new(entries: Vec<PaletteEntry>) -> Self {
+        let mut command_palette = Self {
+            entries,
+            ..default()
+        };
+        command_palette.set_query(String::new());
+        command_palette
+    }
+
+    /// Filters the commands and highlights the best match.
+    fn set_query(&mut self, query: String) {
+        let mut matches: Vec<(i32, usize)> = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter_map(|(index, entry)| Some((fuzzy_score(&query, &entry.label)?, index)))
+            .collect();
+        // Stable, so commands with the same score keep their order
+        matches.sort_by_key(|(score, _)| -score);
+        self.matches = matches.into_iter().map(|(_, index)| index).collect();
+        self.query = query;
+        self.selected = 0;
+        self.first_visible = 0;
+    }
+
+    /// Moves the highlight by the given number of rows, wrapping around at the ends, and scrolls it into view.
+    fn move_selection(&mut self, offset: isize) {
+        if self.matches.is_empty() {
+            return;
+        }
+        self.selected = (self.selected as isize + offset).rem_euclid(self.matches.len() as isize) as usize;
+        if self.selected < self.first_visible {
+            self.first_visible = self.selected;
+        } else if self.selected >= self.first_visible + MAX_VISIBLE_ROWS {
+            self.first_visible = self.selected + 1 - MAX_VISIBLE_ROWS;
+        }
+    }
+
+    fn get_match(&self, index: usize) -> Option<&PaletteEntry> {
+        self.entries.get(*self.matches.get(index)?)
+    }
+}
+
+/// The text input of the command palette.
+#[derive(Component, Clone, Copy)]
+pub struct CommandPaletteInput;
+
+/// Scores how well the query matches the label, or returns None if the characters of the query are not all
+/// in the label in the same order. Matches at the start of a word and consecutive matches score higher,
+/// gaps between matches score lower. Case and spaces in the query are ignored.
+pub fn fuzzy_score(query: &str, label: &str) -> Option<i32> {
+    let label: Vec<char> = label.chars().flat_map(char::to_lowercase).collect();
+    let mut score = 0;
+    let mut next_index = 0;
+    let mut previous_match: Option<usize> = None;
+    for character in query.chars().filter(|character| !character.is_whitespace()).flat_map(char::to_lowercase) {
+        let index = (next_index..label.len()).find(|index| label[*index] == character)?;
+        score += 1;
+        if index == 0 || !label[index - 1].is_alphanumeric() {
+            score += 8;
+        }
+        match previous_match {
+            Some(previous) if previous + 1 == index => score += 5,
+            Some(previous) => score -= (index - previous - 1).min(5) as i32,
+            None => {}
+        }
+        previous_match = Some(index);
+        next_index = index + 1;
+    }
+    if previous_match.is_none() {
+        // Everything matches an empty query equally
+        return Some(0);
+    }
+    // Prefer shorter labels when the matches are equally good
+    Some(score * 100 - label.len() as i32)
+}
+
+/// Returns the names of the menus that contain the menu item, outermost first.
+fn menu_path(
+    entity: Entity,
+    parents: &Query<&ChildOf>,
+    dropdown_roots: &Query<&Children, With<MenuBarDropdownRoot>>,
+    menu_bar_buttons: &Query<(Entity, &MenuBarButton, &ClickAction), Without<ClickActionDisabled>>,
+) -> Vec<String> {
+    let mut path: Vec<String> = parents
+        .iter_ancestors(entity)
+        .filter_map(|ancestor| dropdown_roots.get(ancestor).ok())
+        .filter_map(|children| {
+            children
+                .iter()
+                .filter_map(|child| menu_bar_buttons.get(child).ok())
+                .find(|(_, menu_bar_button, _)| menu_bar_button.is_dropdown)
+                .map(|(_, menu_bar_button, _)| menu_bar_button.text.clone())
+        })
+        .collect();
+    path.reverse();
+    path
+}
+
+/// Opens the command palette with the enabled menu items, the tools, the open tabs, the registered commands
+/// and the other global shortcuts.
+#[allow(clippy::too_many_arguments)]
+fn open_command_palette(
+    mut commands: Commands,
+    mut ui_event_reader: MessageReader<UiEvent>,
+    editor_ui: Single<Entity, With<EditorUi>>,
+    command_palettes: Query<(), With<CommandPalette>>,
+    menu_bar_buttons: Query<(Entity, &MenuBarButton, &ClickAction), Without<ClickActionDisabled>>,
+    dropdown_roots: Query<&Children, With<MenuBarDropdownRoot>>,
+    parents: Query<&ChildOf>,
+    tool_buttons: Query<&ToolButton>,
+    open_documents: Res<OpenDocuments>,
+    shortcuts: Res<Shortcuts>,
+    registry: Res<CommandRegistry>,
+) {
+    for event in ui_event_reader.read() {
+        if *event != UiEvent::CommandPalette(CommandPaletteAction::Open) || !command_palettes.is_empty() {
+            continue;
+        }
+
+        let mut commands_to_list: Vec<(String, UiEvent)> = Vec::new();
+        for (entity, menu_bar_button, click_action) in menu_bar_buttons.iter() {
+            if menu_bar_button.is_dropdown {
+                continue;
+            }
+            let path = menu_path(entity, &parents, &dropdown_roots, &menu_bar_buttons);
+            let label = match path.is_empty() {
+                true => menu_bar_button.text.clone(),
+                false => format!("{}: {}", path.join(" > "), menu_bar_button.text),
+            };
+            commands_to_list.push((label, click_action.0.clone()));
+        }
+        for tool_button in tool_buttons.iter() {
+            let label = format!("Tool: {}", split_words(&format!("{:?}", tool_button.action)));
+            commands_to_list.push((label, UiEvent::SelectTool(tool_button.action)));
+        }
+        for index in 0..open_documents.len() {
+            if let Some(document) = open_documents.get(index) {
+                commands_to_list.push((format!("Tab: {}", document.name), UiEvent::SelectTab(index)));
+            }
+        }
+        for command in registry.iter() {
+            commands_to_list.push((command.label.clone(), command.event()));
+        }
+        let mut global_events: Vec<&UiEvent> = shortcuts
+            .shortcuts
+            .keys()
+            .filter(|event| shortcuts.get_scope(event) == ShortcutScope::Global)
+            .collect();
+        global_events.sort_by_key(|event| event_label(event));
+        for event in global_events {
+            commands_to_list.push((event_label(event), event.clone()));
+        }
+
+        let mut entries: Vec<PaletteEntry> = Vec::new();
+        for (label, event) in commands_to_list {
+            if matches!(event, UiEvent::CommandPalette(_) | UiEvent::OpenMenu { .. } | UiEvent::CloseMenus)
+                || entries.iter().any(|entry| entry.event == event)
+                || matches!(&event, UiEvent::Command(id) if !registry.get(id).is_some_and(|command| command.is_enabled))
+            {
+                continue;
+            }
+            entries.push(PaletteEntry {
+                label,
+                shortcut: shortcuts.get_shortcut(&event).map(|shortcut| shortcut.to_string()),
+                event,
+            });
+        }
+        commands.entity(*editor_ui).with_child(CommandPalette::new(entries));
+    }
+}
+
+fn handle_command_palette_events(
+    mut commands: Commands,
+    mut ui_event_reader: MessageReader<UiEvent>,
+    command_palettes: Query<(Entity, &CommandPalette)>,
+) {
+    for event in ui_event_reader.read() {
+        let UiEvent::CommandPalette(action) = event else {
+            continue;
+        };
+        let Ok((entity, command_palette)) = command_palettes.single() else {
+            continue;
+        };
+        match *action {
+            CommandPaletteAction::Open => {}
+            CommandPaletteAction::Execute(index) => {
+                if let Some(entry) = command_palette.get_match(index) {
+                    info!("Command palette --> {:?}", entry.event);
+                    commands.write_message(entry.event.clone());
+                }
+                commands.entity(entity).despawn();
+            }
+            CommandPaletteAction::Close => {
+                commands.entity(entity).despawn();
+            }
+        }
+    }
+}
+
+/// Filters the commands as the query is typed. Enter executes the highlighted command and Escape closes the palette.
+fn handle_command_palette_input(
+    mut text_input_changed_reader: MessageReader<TextInputChanged>,
+    mut text_input_submitted_reader: MessageReader<TextInputSubmitted>,
+    mut text_input_cancelled_reader: MessageReader<TextInputCancelled>,
+    mut ui_event_writer: MessageWriter<UiEvent>,
+    mut text_input_focus: ResMut<TextInputFocus>,
+    mut command_palettes: Query<&mut CommandPalette>,
+    command_palette_inputs: Query<(Entity, &TextInput), With<CommandPaletteInput>>,
+) {
+    let Ok(mut command_palette) = command_palettes.single_mut() else {
+        text_input_changed_reader.clear();
+        text_input_submitted_reader.clear();
+        text_input_cancelled_reader.clear();
+        return;
+    };
+    for changed in text_input_changed_reader.read() {
+        if command_palette_inputs.contains(changed.entity) {
+            command_palette.set_query(changed.value.clone());
+        }
+    }
+    for submitted in text_input_submitted_reader.read() {
+        if command_palette_inputs.contains(submitted.entity) {
+            let selected = command_palette.selected;
+            ui_event_writer.write(UiEvent::CommandPalette(CommandPaletteAction::Execute(selected)));
+        }
+    }
+    for cancelled in text_input_cancelled_reader.read() {
+        if command_palette_inputs.contains(cancelled.entity) {
+            ui_event_writer.write(UiEvent::CommandPalette(CommandPaletteAction::Close));
+        }
+    }
+
+    if !text_input_focus.is_focused()
+        && let Ok((command_palette_input, text_input)) = command_palette_inputs.single()
+    {
+        text_input_focus.focus(command_palette_input, text_input);
+    }
+}
+
+/// Moves the highlight with the arrow keys, including key repeats.
+fn navigate_command_palette(
+    mut keyboard_input_reader: MessageReader<KeyboardInput>,
+    mut command_palettes: Query<&mut CommandPalette>,
+) {
+    let Ok(mut command_palette) = command_palettes.single_mut() else {
+        keyboard_input_reader.clear();
+        return;
+    };
+    for event in keyboard_input_reader.read() {
+        if event.state != ButtonState::Pressed {
+            continue;
+        }
+        match event.key_code {
+            KeyCode::ArrowUp => command_palette.move_selection(-1),
+            KeyCode::ArrowDown => command_palette.move_selection(1),
+            KeyCode::PageUp => command_palette.move_selection(-(MAX_VISIBLE_ROWS as isize)),
+            KeyCode::PageDown => command_palette.move_selection(MAX_VISIBLE_ROWS as isize),
+            _ => {}
+        }
+    }
+}
+
+reactive_element!(CommandPalette, reactive_command_palette, |command_palette: &CommandPalette| {
+    let selected = command_palette.selected;
+    let rows: Vec<(usize, PaletteEntry)> = command_palette
+        .matches
+        .iter()
+        .enumerate()
+        .skip(command_palette.first_visible)
+        .take(MAX_VISIBLE_ROWS)
+        .filter_map(|(index, entry_index)| Some((index, command_palette.entries.get(*entry_index)?.clone())))
+        .collect();
+    let rows = rows.into_iter().map(move |(index, entry)| {
+        (
+            EditorUiElement,
+            Button,
+            ClickAction(UiEvent::CommandPalette(CommandPaletteAction::Execute(index))),
+            Node {
+                display: Display::Flex,
+                flex_shrink: 0.0,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::SpaceBetween,
+                ..default()
+            },
+            EditorGap(Spacing::Md),
+            EditorPadding::axes(Spacing::Md, Spacing::Sm),
+            EditorBorderRadius::all(Radius::Sm),
+            EditorBackgroundColor(
+                if index == selected { EditorColor::MenuBarButtonHover } else { EditorColor::Button },
+                Some(EditorColor::MenuBarButtonHover),
+                None,
+            ),
+            children![
+                (
+                    EditorUiElement,
+                    Text::new(entry.label),
+                    EditorTextColor(EditorColor::Text, None, None),
+                    EditorFontSize(FontSize::Body)
+                ),
+                (
+                    EditorUiElement,
+                    Node {
+                        display: if entry.shortcut.is_some() { Display::Flex } else { Display::None },
+                        ..default()
+                    },
+                    Text::new(entry.shortcut.unwrap_or_default()),
+                    EditorTextColor(EditorColor::FadedText, None, None),
+                    EditorFontSize(FontSize::Body)
+                ),
+            ],
+        )
+    });
+
+    (
+        EditorUiElement,
+        // Covers the editor, clicking next to the palette closes it
+        Button,
+        ClickAction(UiEvent::CommandPalette(CommandPaletteAction::Close)),
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::FlexStart,
+            ..default()
+        },
+        GlobalZIndex(10000),
+        EditorBackgroundColor(EditorColor::Overlay, None, None),
+        children![(
+            EditorUiElement,
+            Node {
+                display: Display::Flex,
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            EditorWidth(Width::CommandPalette),
+            EditorTop(Height::CommandPaletteTop),
+            EditorGap(Spacing::Md),
+            EditorPadding::all(Spacing::Md),
+            FocusPolicy::Block,
+            EditorBorderRadius::all(Radius::Lg),
+            EditorBackgroundColor(EditorColor::Background, None, None),
+            children![
+                (
+                    CommandPaletteInput,
+                    AutoFocus,
+                    TextInput::new(command_palette.query.clone()).with_placeholder("Type a command"),
+                    Node {
+                        flex_grow: 0.0,
+                        ..default()
+                    },
+                    EditorPadding::axes(Spacing::Md, Spacing::Sm),
+                    EditorBorderRadius::all(Radius::Md),
+                ),
+                (
+                    EditorUiElement,
+                    Node {
+                        display: Display::Flex,
+                        flex_direction: FlexDirection::Column,
+                        ..default()
+                    },
+                    EditorGap(Spacing::Xs),
+                    Children::spawn(SpawnIter(rows)),
+                ),
+                (
+                    EditorUiElement,
+                    Node {
+                        display: if command_palette.matches.is_empty() { Display::Flex } else { Display::None },
+                        ..default()
+                    },
+                    EditorPadding::axes(Spacing::Md, Spacing::Sm),
+                    Text::new("No matching commands"),
+                    EditorTextColor(EditorColor::FadedText, None, None),
+                    EditorFontSize(FontSize::Body)
+                ),
+            ],
+        )],
+    )
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn word_starts_score_higher_than_other_matches() {
+        assert!(fuzzy_score("sa", "Save").unwrap() > fuzzy_score("sa", "Unsaved").unwrap());
+        assert!(fuzzy_score("fs", "Frame Selection").unwrap() > fuzzy_score("fs", "Offset").unwrap());
+        // A word start after punctuation counts as well
+        assert!(fuzzy_score("n", "File > New").unwrap() > fuzzy_score("n", "Open").unwrap());
+    }
+
+    #[test]
+    fn consecutive_matches_score_higher_than_gaps() {
+        assert!(fuzzy_score("ope", "Open").unwrap() > fuzzy_score("ope", "Open Prefab Editor").unwrap());
+        assert!(fuzzy_score("ab", "abc").unwrap() > fuzzy_score("ac", "abc").unwrap());
+    }
+
+    #[test]
+    fn shorter_labels_win_ties() {
+        assert!(fuzzy_score("save", "Save").unwrap() > fuzzy_score("save", "Save All").unwrap());
+    }
+
+    #[test]
+    fn non_matches_return_none() {
+        assert_eq!(fuzzy_score("x", "Save"), None);
+        // The characters have to be in the same order
+        assert_eq!(fuzzy_score("evas", "Save"), None);
+        assert_eq!(fuzzy_score("saves", "Save"), None);
+        assert_eq!(fuzzy_score("save", ""), None);
+    }
+
+    #[test]
+    fn empty_queries_match_everything_equally() {
+        assert_eq!(fuzzy_score("", "Save"), Some(0));
+        assert_eq!(fuzzy_score("  ", "Open Scene"), Some(0));
+    }
+
+    #[test]
+    fn case_and_spaces_are_ignored() {
+        assert_eq!(fuzzy_score("SAVE", "save as"), fuzzy_score("save", "Save As"));
+        assert_eq!(fuzzy_score("save as", "Save As"), fuzzy_score("saveas", "Save As"));
+        assert_eq!(fuzzy_score(" s a ", "Save As"), fuzzy_score("sa", "Save As"));
+        assert!(fuzzy_score("openscene", "Open Scene").is_some());
+    }
+}
@@ -0,0 +1,302 @@
+use std::fmt::{Display, Formatter};
+
+use bevy::{ecs::system::SystemId, prelude::*};
+
+use crate::ui::{
+    ClickAction, ClickActionDisabled, UiEvent,
+    elements::{MenuBar, MenuBarButton},
+    menu::{MenuCommandsExt, MenuItem},
+    shortcuts::{Shortcut, ShortcutScope, Shortcuts, event_label},
+};
+
+pub struct CommandRegistryPlugin;
+
+impl Plugin for CommandRegistryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CommandRegistry>()
+            .register_type::<CommandId>()
+            .add_systems(Update, run_ui_commands)
+            .add_systems(Update, update_command_states)
+            .add_systems(
+                Update,
+                (load_command_icons, add_command_menu_items, update_command_menu_items)
+                    .chain()
+                    .after(update_command_states)
+                    .run_if(resource_changed::<CommandRegistry>),
+            );
+    }
+}
+
+/// Identifies a registered command, e.g. "my_game.spawn_enemy".
+#[derive(Reflect, Hash, Eq, PartialEq, Clone, Debug)]
+pub struct CommandId(pub String);
+
+impl Display for CommandId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), core::fmt::Error> {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl From<&str> for CommandId {
+    fn from(id: &str) -> Self {
+        Self(id.to_string())
+    }
+}
+
+impl From<String> for CommandId {
+    fn from(id: String) -> Self {
+        Self(id)
+    }
+}
+
+/// Description of a command that is registered with [`CommandRegistryAppExt::add_ui_command`].
+/// Its UiEvent is `UiEvent::Command(id)`, which can be used for ClickActions and shortcuts like the built-in events.
+pub struct UiCommand {
+    pub id: CommandId,
+    pub label: String,
+    /// Asset path of the icon that is shown in menus.
+    pub icon: Option<String>,
+    pub shortcut: Option<Shortcut>,
+    pub scope: ShortcutScope,
+    /// Id of the menu the command is added to, e.g. "edit". A new menu is added to the menu bar if there is none.
+    pub menu: Option<String>,
+    enabled: Option<Box<dyn System<In = (), Out = bool>>>,
+    checked: Option<Box<dyn System<In = (), Out = bool>>>,
+}
+
+#[allow(dead_code)]
+impl UiCommand {
+    pub fn new(id: impl Into<CommandId>, label: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            label: label.into(),
+            icon: None,
+            shortcut: None,
+            scope: ShortcutScope::Global,
+            menu: None,
+            enabled: None,
+            checked: None,
+        }
+    }
+
+    pub fn with_icon(mut self, path: impl Into<String>) -> Self {
+        self.icon = Some(path.into());
+        self
+    }
+
+    /// Sets the default shortcut, which can be changed by the user.
+    pub fn with_shortcut(mut self, shortcut: Shortcut) -> Self {
+        self.shortcut = Some(shortcut);
+        self
+    }
+
+    /// Makes the shortcut only apply in the given scope.
+    pub fn with_scope(mut self, scope: ShortcutScope) -> Self {
+        self.scope = scope;
+        self
+    }
+
+    pub fn in_menu(mut self, menu: impl Into<String>) -> Self {
+        self.menu = Some(menu.into());
+        self
+    }
+
+    /// The command can only be executed while the system returns true. Its menu item is greyed out otherwise.
+    pub fn enabled_if<M>(mut self, system: impl IntoSystem<(), bool, M>) -> Self {
+        self.enabled = Some(Box::new(IntoSystem::into_system(system)));
+        self
+    }
+
+    /// Shows a check mark in front of the menu item while the system returns true.
+    pub fn checked_if<M>(mut self, system: impl IntoSystem<(), bool, M>) -> Self {
+        self.checked = Some(Box::new(IntoSystem::into_system(system)));
+        self
+    }
+}
+
+/// A command in the [`CommandRegistry`].
+#[derive(Debug)]
+pub struct RegisteredCommand {
+    pub id: CommandId,
+    pub label: String,
+    pub icon_path: Option<String>,
+    pub icon: Option<Handle<Image>>,
+    pub menu: Option<String>,
+    pub is_enabled: bool,
+    /// None if the command can't be checked.
+    pub is_checked: Option<bool>,
+    handler: SystemId,
+    enabled: Option<SystemId<(), bool>>,
+    checked: Option<SystemId<(), bool>>,
+    has_menu_item: bool,
+}
+
+impl RegisteredCommand {
+    pub fn event(&self) -> UiEvent {
+        UiEvent::Command(self.id.clone())
+    }
+}
+
+/// The commands that were registered by plugins.
+#[derive(Resource, Default, Debug)]
+pub struct CommandRegistry {
+    commands: Vec<RegisteredCommand>,
+}
+
+impl CommandRegistry {
+    pub fn get(&self, id: &CommandId) -> Option<&RegisteredCommand> {
+        self.commands.iter().find(|command| command.id == *id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &RegisteredCommand> {
+        self.commands.iter()
+    }
+
+    /// Returns the label of a registered command, or the label of a built-in event, see [`event_label`].
+    pub fn event_label(&self, event: &UiEvent) -> String {
+        match event {
+            UiEvent::Command(id) => self.get(id).map_or_else(|| event_label(event), |command| command.label.clone()),
+            event => event_label(event),
+        }
+    }
+}
+
+pub trait CommandRegistryAppExt {
+    /// Registers a command, whose handler system runs when its `UiEvent::Command` is sent while it is enabled.
+    fn add_ui_command<M>(&mut self, command: UiCommand, handler: impl IntoSystem<(), (), M> + 'static) -> &mut Self;
+}
+
+impl CommandRegistryAppExt for App {
+    fn add_ui_command<M>(&mut self, command: UiCommand, handler: impl IntoSystem<(), (), M> + 'static) -> &mut Self {
+        let world = self.world_mut();
+        let handler = world.register_system(handler);
+        let enabled = command.enabled.map(|system| world.register_boxed_system(system));
+        let checked = command.checked.map(|system| world.register_boxed_system(system));
+
+        let event = UiEvent::Command(command.id.clone());
+        let mut shortcuts = world.get_resource_or_init::<Shortcuts>();
+        if let Some(shortcut) = command.shortcut {
+            shortcuts.add_default(event.clone(), shortcut);
+        }
+        if command.scope != ShortcutScope::Global {
+            shortcuts.set_scope(event, command.scope);
+        }
+
+        let mut registry = world.get_resource_or_init::<CommandRegistry>();
+        if registry.get(&command.id).is_some() {
+            warn!("Command {} is registered twice", command.id);
+        }
+        registry.commands.push(RegisteredCommand {
+            id: command.id,
+            label: command.label,
+            icon_path: command.icon,
+            icon: None,
+            menu: command.menu,
+            is_enabled: true,
+            is_checked: checked.map(|_| false),
+            handler,
+            enabled,
+            checked,
+            has_menu_item: false,
+        });
+        self
+    }
+}
+
+/// Runs the handlers of the commands whose UiEvent was sent.
+fn run_ui_commands(mut commands: Commands, mut ui_event_reader: MessageReader<UiEvent>, registry: Res<CommandRegistry>) {
+    for event in ui_event_reader.read() {
+        let UiEvent::Command(id) = event else {
+            continue;
+        };
+        match registry.get(id) {
+            Some(command) if command.is_enabled => commands.run_system(command.handler),
+            Some(_) => info!("Command {id} is disabled"),
+            None => warn!("Command {id} is not registered"),
+        }
+    }
+}
+
+/// Runs the enabled and checked systems of the commands. The registry is only marked as changed when
+/// one of them returned something else than before.
+fn update_command_states(world: &mut World) {
+    world.resource_scope(|world, mut registry: Mut<CommandRegistry>| {
+        let mut is_changed = false;
+        for command in registry.bypass_change_detection().commands.iter_mut() {
+            if let Some(enabled) = command.enabled {
+                let is_enabled = world.run_system(enabled).unwrap_or(false);
+                is_changed |= command.is_enabled != is_enabled;
+                command.is_enabled = is_enabled;
+            }
+            if let Some(checked) = command.checked {
+                let is_checked = Some(world.run_system(checked).unwrap_or(false));
+                is_changed |= command.is_checked != is_checked;
+                command.is_checked = is_checked;
+            }
+        }
+        if is_changed {
+            registry.set_changed();
+        }
+    });
+}
+
+fn load_command_icons(asset_server: Res<AssetServer>, mut registry: ResMut<CommandRegistry>) {
+    for command in registry.bypass_change_detection().commands.iter_mut() {
+        if command.icon.is_none()
+            && let Some(icon_path) = &command.icon_path
+        {
+            command.icon = Some(asset_server.load(icon_path.clone()));
+        }
+    }
+}
+
+/// Adds the menu items of the commands that have a menu. Menus that don't exist yet are added to the menu bar.
+fn add_command_menu_items(
+    mut commands: Commands,
+    mut registry: ResMut<CommandRegistry>,
+    menu_bars: Query<(), With<MenuBar>>,
+) {
+    if menu_bars.is_empty() {
+        return;
+    }
+    for command in registry.bypass_change_detection().commands.iter_mut() {
+        let Some(menu) = &command.menu else {
+            continue;
+        };
+        if command.has_menu_item {
+            continue;
+        }
+        let item = MenuItem::new(command.label.clone(), command.event());
+        commands.extend_menu(menu.clone(), move |menu| menu.add(item));
+        command.has_menu_item = true;
+    }
+}
+
+/// Greys out the menu items of disabled commands and shows their icon and whether they are checked.
+fn update_command_menu_items(
+    mut commands: Commands,
+    registry: Res<CommandRegistry>,
+    mut menu_bar_buttons: Query<(Entity, &mut MenuBarButton, &ClickAction)>,
+) {
+    for (entity, mut menu_bar_button, click_action) in menu_bar_buttons.iter_mut() {
+        let UiEvent::Command(id) = &click_action.0 else {
+            continue;
+        };
+        let Some(command) = registry.get(id) else {
+            continue;
+        };
+        if menu_bar_button.is_enabled != command.is_enabled
+            || menu_bar_button.is_checked != command.is_checked
+            || menu_bar_button.icon != command.icon
+        {
+            menu_bar_button.is_enabled = command.is_enabled;
+            menu_bar_button.is_checked = command.is_checked;
+            menu_bar_button.icon = command.icon.clone();
+        }
+        if command.is_enabled {
+            commands.entity(entity).remove::<ClickActionDisabled>();
+        } else {
+            commands.entity(entity).insert(ClickActionDisabled);
+        }
+    }
+}
@@ -1,7 +1,14 @@
 use crate::ui::{
-    ClickAction, EditorUiElement, Tool, UiEvent, colors::{EditorBackgroundColor, EditorColor, EditorTextColor}, fullscreen::NodeFullscreenDisplay, shortcuts::Shortcuts
+    ClickAction, EditorUiElement, Tool, UiEvent, colors::{EditorBackgroundColor, EditorColor, EditorTextColor}, fullscreen::{NodeFullscreenDisplay, SlideEdge}, menu::{ContextMenu, MenuItem}, reactive::Reconcile, shortcuts::{ShortcutScope, Shortcuts}, style::{EditorBorderRadius, EditorFontSize, EditorGap, EditorHeight, EditorIconSize, EditorMargin, EditorPadding, EditorWidth, FontSize, Height, IconSize, Radius, Spacing, Width}
 };
-use bevy::prelude::*;
+use bevy::{
+    input::mouse::{MouseScrollUnit, MouseWheel},
+    prelude::*,
+    ui::{FocusPolicy, RelativeCursorPosition},
+};
+
+/// Pixels scrolled per line of mouse wheel movement.
+const SCROLL_LINE_HEIGHT: f32 = 24.0;
 
 pub struct ElementsPlugin;
 
@@ -14,28 +21,33 @@ impl Plugin for ElementsPlugin {
             .add_systems(PostUpdate, reactive_tool_button)
             .add_systems(PostUpdate, reactive_status_bar)
             .add_systems(PostUpdate, reactive_fps_counter)
-            .add_systems(PostUpdate, reactive_camera_preview);
+            .add_systems(PostUpdate, reactive_camera_preview)
+            .add_systems(Update, scroll_views)
+            .add_systems(Update, update_menu_shortcut_texts);
     }
 }
 /// This macro is used to create a reactive element.
-/// It is currently not very performant because it despawns and re-inserts the entire entity every time the data changes.
+/// Whenever the data changes, the new bundle is reconciled with the previously rendered one,
+/// so only the components that actually changed are updated and child entities are kept.
 macro_rules! reactive_element {
     ($name:ident, $system_name:ident, $bundle:expr) => {
         pub fn $system_name(
             mut commands: Commands,
             query: Query<(Entity, &$name), Changed<$name>>,
         ) {
+            let render = $bundle;
             for (entity, entity_data) in query.iter() {
-                let mut entity_commands = commands.entity(entity);
-                entity_commands.despawn_children();
-
-                let bundle = $bundle(entity_data);
-                entity_commands.insert(bundle);
+                commands.queue(Reconcile {
+                    entity,
+                    bundle: render(entity_data),
+                });
             }
         }
     };
 }
 
+pub(crate) use reactive_element;
+
 #[derive(Component)]
 #[require(EditorUiElement)]
 #[require(Node {
@@ -52,78 +64,83 @@ pub struct EditorUi;
 
 #[derive(Component)]
 #[require(EditorUiElement)]
-#[require(EditorBackgroundColor(EditorColor::MenuBar, None, None))]
-#[require(NodeFullscreenDisplay::new(Display::Flex, Display::None))]
+#[require(EditorBackgroundColor::new(EditorColor::MenuBar))]
+#[require(NodeFullscreenDisplay = NodeFullscreenDisplay::new(Display::Flex, Display::None).with_slide(SlideEdge::Top).with_fade())]
 #[require(Node {
     display: Display::Flex,
-    height: Val::Px(39.0),
     width: Val::Percent(100.0),
-    column_gap: Val::Px(2.0),
-    padding: UiRect::new(Val::Px(15.0), Val::Px(15.0), Val::ZERO, Val::ZERO),
     ..default()
 })]
+#[require(EditorHeight(Height::MenuBar))]
+#[require(EditorGap(Spacing::Xs))]
+#[require(EditorPadding::axes(Spacing::Lg, Spacing::None))]
 pub struct MenuBar;
 
-#[derive(Component)]
+#[derive(Component, Clone)]
 pub struct MenuBarButton {
     pub text: String,
     pub shortcut_text: Option<String>,
-    pub is_in_submenu: bool,
+    /// 0 for the buttons in the menu bar, 1 for the items of their dropdowns, 2 for the items of submenus, etc.
+    pub depth: usize,
+    /// Opens a dropdown or submenu instead of emitting an event.
     pub is_dropdown: bool,
+    /// Disabled buttons are greyed out.
+    pub is_enabled: bool,
+    /// Shows a check mark in front of the text when true. None if the button can't be checked.
+    pub is_checked: Option<bool>,
+    /// Shows a dot instead of a check mark, for items of which only one can be checked.
+    pub is_radio: bool,
+    pub icon: Option<Handle<Image>>,
+    /// Index of the character in the text that is underlined and activates the button with the keyboard.
+    pub mnemonic: Option<usize>,
+    pub show_mnemonic: bool,
+    /// Selected with the keyboard.
+    pub is_highlighted: bool,
+}
+
+impl MenuBarButton {
+    /// Splits the text into the part before the mnemonic, the mnemonic and the part after it.
+    /// Spaces are replaced by non-breaking spaces, so they aren't trimmed at the ends of the parts.
+    fn split_mnemonic(&self) -> (String, String, String) {
+        let text = self.text.replace(' ', "\u{a0}");
+        let Some((index, character)) = self.mnemonic.and_then(|index| text.char_indices().nth(index)) else {
+            return (text, String::new(), String::new());
+        };
+        let end = index + character.len_utf8();
+        (text[..index].to_string(), text[index..end].to_string(), text[end..].to_string())
+    }
+}
+
+fn menu_bar_button_text(text: String, color: EditorColor) -> impl Bundle {
+    (
+        EditorUiElement,
+        Text::new(text),
+        EditorTextColor(color, None, None),
+        EditorFontSize(FontSize::Body),
+    )
 }
 
 #[derive(Component)]
 #[require(Node {
     position_type: PositionType::Relative,
-    padding: UiRect::new(Val::ZERO, Val::ZERO, Val::Px(5.0), Val::Px(5.0)),
     ..default()
 })]
+#[require(EditorPadding::axes(Spacing::None, Spacing::Sm))]
 pub struct MenuBarDropdownRoot;
 
-#[macro_export]
-macro_rules! menu_bar_dropdown {
-    ($text:expr, $id:literal, $children:tt) => {
-        (
-            MenuBarDropdownRoot,
-            children![
-                (
-                    MenuBarButton {
-                        text: $text,
-                        shortcut_text: None,
-                        is_dropdown: true,
-                        is_in_submenu: false,
-                    },
-                    ClickAction(UiEvent::OpenMenu {
-                        id: $id.to_string()
-                    })
-                ),
-                (
-                    MenuBarDropdown {
-                        id: $id.to_string()
-                    },
-                    children!$children
-                )
-            ]
-        )
-    };
-}
-
-impl MenuBarButton {
-    pub fn new(text: String, event: UiEvent, shortcuts: &Shortcuts) -> impl Bundle {
-        (
-            MenuBarButton {
-                text: text,
-                shortcut_text: shortcuts.get_shortcut(&event).map(|shortcut| shortcut.to_string()),
-                is_in_submenu: true,
-                is_dropdown: false,
-            },
-            ClickAction(event)
-        )
+/// Updates the shortcut texts of the menu buttons when they are added or a shortcut is changed.
+fn update_menu_shortcut_texts(shortcuts: Res<Shortcuts>, mut menu_bar_buttons: Query<(&mut MenuBarButton, &ClickAction)>) {
+    for (mut menu_bar_button, click_action) in menu_bar_buttons.iter_mut() {
+        if !shortcuts.is_changed() && !menu_bar_button.is_added() {
+            continue;
+        }
+        let shortcut_text = shortcuts.get_shortcut(&click_action.0).map(|shortcut| shortcut.to_string());
+        if menu_bar_button.shortcut_text != shortcut_text {
+            menu_bar_button.shortcut_text = shortcut_text;
+        }
     }
 }
 
-pub use menu_bar_dropdown;
-
 #[derive(Component)]
 #[require(Node {
     position_type: PositionType::Absolute,
@@ -131,78 +148,171 @@ pub use menu_bar_dropdown;
     top: Val::Percent(100.0),
     display: Display::Flex,
     flex_direction: FlexDirection::Column,
-    row_gap: Val::Px(5.0),
-    padding: UiRect::new(Val::Px(10.0), Val::Px(10.0), Val::Px(5.0), Val::Px(5.0)),
     ..default()
 })]
-#[require(EditorBackgroundColor(EditorColor::Background, None, None))]
+#[require(EditorGap(Spacing::Sm))]
+#[require(EditorPadding::axes(Spacing::Md, Spacing::Sm))]
+#[require(EditorBackgroundColor::new(EditorColor::Background))]
 #[require(GlobalZIndex(9000))]
 #[require(Visibility::Hidden)]
+// Clicks between the items don't reach the elements below the menu
+#[require(FocusPolicy::Block)]
 pub struct MenuBarDropdown {
     pub id: String,
 }
 
+/// Line between groups of items in a menu.
+#[derive(Component)]
+#[require(EditorUiElement)]
+#[require(Node {
+    height: Val::Px(1.0),
+    ..default()
+})]
+#[require(EditorMargin::axes(Spacing::None, Spacing::Xs))]
+#[require(EditorBackgroundColor::new(EditorColor::FadedText))]
+pub struct MenuSeparator;
+
 reactive_element!(
     MenuBarButton,
     reactive_menu_bar_button,
     |menu_bar_button: &MenuBarButton| {
+        let is_submenu = menu_bar_button.is_dropdown && menu_bar_button.depth > 0;
+        let (before, mnemonic, after) = menu_bar_button.split_mnemonic();
+        let text_color = if menu_bar_button.is_enabled {
+            EditorColor::Text
+        } else {
+            EditorColor::FadedText
+        };
         (
             EditorUiElement,
             Button,
             Node {
                 display: Display::Flex,
-                height: if menu_bar_button.is_in_submenu { Val::Px(26.0) } else { Val::Px(29.0) },
                 align_items: AlignItems::Center,
                 justify_content: JustifyContent::SpaceBetween,
-                column_gap: Val::Px(10.0),
-                padding: if menu_bar_button.is_in_submenu {
-                    UiRect::all(Val::Px(5.0))
-                } else {
-                    UiRect::new(Val::Px(15.0), Val::Px(15.0), Val::Px(6.0), Val::Px(6.0))
-                },
                 ..default()
             },
-            BorderRadius::all(Val::Px(4.0)),
+            EditorHeight(if menu_bar_button.depth > 0 { Height::MenuItem } else { Height::MenuButton }),
+            EditorGap(Spacing::Md),
+            if menu_bar_button.depth > 0 {
+                EditorPadding::all(Spacing::Sm)
+            } else {
+                EditorPadding::axes(Spacing::Lg, Spacing::Sm)
+            },
+            EditorBorderRadius::all(Radius::Md),
             EditorBackgroundColor(
-                if menu_bar_button.is_in_submenu {
+                if menu_bar_button.is_highlighted {
+                    EditorColor::MenuBarButtonHover
+                } else if menu_bar_button.depth > 0 {
                     EditorColor::Background
                 } else {
                     EditorColor::MenuBar
                 },
-                Some(EditorColor::MenuBarButtonHover),
+                menu_bar_button.is_enabled.then_some(EditorColor::MenuBarButtonHover),
                 None,
             ),
             children![
                 (
                     EditorUiElement,
-                    Text::new(&menu_bar_button.text),
-                    EditorTextColor(EditorColor::Text, None, None),
-                    TextFont {
-                        font_size: 13.0,
+                    Node {
+                        display: Display::Flex,
+                        align_items: AlignItems::Center,
                         ..default()
-                    }
+                    },
+                    EditorGap(Spacing::Sm),
+                    children![
+                        (
+                            EditorUiElement,
+                            Node {
+                                display: if menu_bar_button.is_checked.is_some() {
+                                    Display::Flex
+                                } else {
+                                    Display::None
+                                },
+                                ..default()
+                            },
+                            EditorWidth(Width::MenuCheck),
+                            Text::new(match menu_bar_button.is_checked {
+                                Some(true) if menu_bar_button.is_radio => "●",
+                                Some(true) => "✓",
+                                _ => "",
+                            }),
+                            EditorTextColor(EditorColor::Text, None, None),
+                            EditorFontSize(FontSize::Body)
+                        ),
+                        (
+                            EditorUiElement,
+                            Node {
+                                display: if menu_bar_button.icon.is_some() {
+                                    Display::Flex
+                                } else {
+                                    Display::None
+                                },
+                                ..default()
+                            },
+                            EditorIconSize(IconSize::Sm),
+                            ImageNode {
+                                image: menu_bar_button.icon.clone().unwrap_or_default(),
+                                ..default()
+                            }
+                        ),
+                        (
+                            EditorUiElement,
+                            Node {
+                                display: Display::Flex,
+                                ..default()
+                            },
+                            children![
+                                menu_bar_button_text(before, text_color),
+                                (
+                                    EditorUiElement,
+                                    Node {
+                                        position_type: PositionType::Relative,
+                                        ..default()
+                                    },
+                                    children![
+                                        menu_bar_button_text(mnemonic, text_color),
+                                        (
+                                            EditorUiElement,
+                                            Node {
+                                                display: if menu_bar_button.show_mnemonic {
+                                                    Display::Flex
+                                                } else {
+                                                    Display::None
+                                                },
+                                                position_type: PositionType::Absolute,
+                                                left: Val::Px(0.0),
+                                                right: Val::Px(0.0),
+                                                bottom: Val::Px(1.0),
+                                                height: Val::Px(1.0),
+                                                ..default()
+                                            },
+                                            EditorBackgroundColor(text_color, None, None)
+                                        )
+                                    ]
+                                ),
+                                menu_bar_button_text(after, text_color)
+                            ]
+                        )
+                    ]
                 ),
                 (
                     EditorUiElement,
                     Node {
-                        display: if menu_bar_button.shortcut_text.is_some() {
+                        display: if menu_bar_button.shortcut_text.is_some() || is_submenu {
                             Display::Flex
                         } else {
                             Display::None
                         },
                         ..default()
                     },
-                    Text::new(
-                        menu_bar_button
-                            .shortcut_text
-                            .as_ref()
-                            .unwrap_or(&String::new())
-                    ),
+                    Text::new(if is_submenu {
+                        "›"
+                    } else {
+                        menu_bar_button.shortcut_text.as_deref().unwrap_or_default()
+                    }),
                     EditorTextColor(EditorColor::FadedText, None, None),
-                    TextFont {
-                        font_size: 13.0,
-                        ..default()
-                    }
+                    EditorFontSize(FontSize::Body)
                 )
             ],
         )
@@ -211,27 +321,31 @@ reactive_element!(
 
 #[derive(Component)]
 #[require(EditorUiElement)]
-#[require(EditorBackgroundColor(EditorColor::TabBar, None, None))]
-#[require(NodeFullscreenDisplay::new(Display::Flex, Display::None))]
+#[require(EditorBackgroundColor::new(EditorColor::TabBar))]
+#[require(NodeFullscreenDisplay = NodeFullscreenDisplay::new(Display::Flex, Display::None).with_slide(SlideEdge::Top).with_fade())]
 #[require(Node {
-    height: Val::Px(50.0),
     width: Val::Percent(100.0),
-    column_gap: Val::Px(10.0),
-    padding: UiRect::new(Val::Px(15.0), Val::Px(15.0), Val::Px(12.0), Val::Px(0.0)),
+    // The tabs sit on the bottom of the bar
+    align_items: AlignItems::FlexEnd,
     ..default()
 })]
+#[require(EditorHeight(Height::TabBar))]
+#[require(EditorGap(Spacing::Md))]
+#[require(EditorPadding::axes(Spacing::Lg, Spacing::None))]
 pub struct TabBar;
 
-#[derive(Component, Clone)]
+#[derive(Component, Clone, PartialEq)]
 pub struct Tab {
     pub index: usize,
     pub name: String,
     pub is_active: bool,
+    /// The document was opened from or saved to a file.
+    pub has_path: bool,
 }
 
 impl Tab {
-    pub fn new(index: usize, name: String, is_active: bool) -> Self {
-        Self { index, name, is_active }
+    pub fn new(index: usize, name: String, is_active: bool, has_path: bool) -> Self {
+        Self { index, name, is_active, has_path }
     }
 }
 
@@ -240,14 +354,22 @@ reactive_element!(Tab, reactive_tab, |tab: &Tab| {
         EditorUiElement,
         Button,
         ClickAction(UiEvent::SelectTab(tab.index)),
+        ContextMenu::new(|menu| {
+            let copy_path = MenuItem::new("Copy &Path", UiEvent::CopyTabPath(tab.index));
+            menu.item("&Close", UiEvent::CloseTab(tab.index))
+                .item("Close &Others", UiEvent::CloseOtherTabs(tab.index))
+                .item("Close to the &Right", UiEvent::CloseTabsToRight(tab.index))
+                .separator()
+                .add(if tab.has_path { copy_path } else { copy_path.disabled() })
+        }),
         Node {
             display: Display::Flex,
-            height: Val::Px(38.0),
             align_items: AlignItems::Center,
-            column_gap: Val::Px(10.0),
-            padding: UiRect::new(Val::Px(15.0), Val::Px(15.0), Val::Px(10.0), Val::Px(10.0)),
             ..default()
         },
+        EditorHeight(Height::Control),
+        EditorGap(Spacing::Md),
+        EditorPadding::axes(Spacing::Lg, Spacing::Md),
         EditorBackgroundColor(
             if tab.is_active {
                 EditorColor::TabActive
@@ -261,30 +383,26 @@ reactive_element!(Tab, reactive_tab, |tab: &Tab| {
             }),
             None,
         ),
-        BorderRadius::new(Val::Px(4.0), Val::Px(4.0), Val::Px(0.0), Val::Px(0.0)),
+        EditorBorderRadius::top(Radius::Md),
         children![
             (
                 EditorUiElement,
                 Node {
                     display: Display::Flex,
-                    height: Val::Px(18.0),
-                    width: Val::Px(18.0),
                     align_items: AlignItems::Center,
                     justify_content: JustifyContent::Center,
                     ..default()
                 },
+                EditorIconSize(IconSize::Md),
                 Text::new("o"),
                 BackgroundColor(Srgba::hex("#378D09").unwrap().into()),
-                BorderRadius::all(Val::Px(2.0)),
+                EditorBorderRadius::all(Radius::Xs),
             ),
             (
                 EditorUiElement,
                 Text::new(&tab.name),
                 EditorTextColor(EditorColor::Text, None, None),
-                TextFont {
-                    font_size: 13.0,
-                    ..default()
-                }
+                EditorFontSize(FontSize::Body)
             ),
             (
                 EditorUiElement,
@@ -296,20 +414,16 @@ reactive_element!(Tab, reactive_tab, |tab: &Tab| {
                     } else {
                         Display::None
                     },
-                    height: Val::Px(18.0),
-                    width: Val::Px(18.0),
                     align_items: AlignItems::Center,
                     justify_content: JustifyContent::Center,
                     ..default()
                 },
+                EditorIconSize(IconSize::Md),
                 children![(
                     EditorUiElement,
                     Text::new("x"),
                     EditorTextColor(EditorColor::Text, None, None),
-                    TextFont {
-                        font_size: 13.0,
-                        ..default()
-                    }
+                    EditorFontSize(FontSize::Body)
                 )]
             )
         ],
@@ -318,14 +432,14 @@ reactive_element!(Tab, reactive_tab, |tab: &Tab| {
 
 #[derive(Component)]
 #[require(EditorUiElement)]
-#[require(NodeFullscreenDisplay::new(Display::Flex, Display::None))]
+#[require(NodeFullscreenDisplay = NodeFullscreenDisplay::new(Display::Flex, Display::None).with_slide(SlideEdge::Top).with_fade())]
 #[require(Node {
-    padding: UiRect::new(Val::Px(15.0), Val::Px(15.0), Val::Px(6.0), Val::Px(6.0)),
     align_items: AlignItems::Center,
     justify_content: JustifyContent::SpaceBetween,
     ..default()
 })]
-#[require(EditorBackgroundColor(EditorColor::Background, None, None))]
+#[require(EditorPadding::axes(Spacing::Lg, Spacing::Sm))]
+#[require(EditorBackgroundColor::new(EditorColor::Background))]
 pub struct ToolBar;
 
 #[derive(Component)]
@@ -333,37 +447,37 @@ pub struct ToolBar;
 #[require(Node {
     display: Display::Flex,
     align_items: AlignItems::Center,
-    column_gap: Val::Px(10.0),
     ..default()
 })]
+#[require(EditorGap(Spacing::Md))]
 pub struct ToolButtons;
 
 #[derive(Component)]
 #[require(EditorUiElement)]
 #[require(Node {
     display: Display::Flex,
-    height: Val::Px(38.0),
     align_items: AlignItems::Center,
-    column_gap: Val::Px(10.0),
-    padding: UiRect::new(Val::Px(15.0), Val::Px(15.0), Val::Px(10.0), Val::Px(10.0)),
     ..default()
 })]
-#[require(BorderRadius::all(Val::Px(4.0)))]
-#[require(EditorBackgroundColor(EditorColor::Button, None, None))]
+#[require(EditorHeight(Height::Control))]
+#[require(EditorGap(Spacing::Md))]
+#[require(EditorPadding::axes(Spacing::Lg, Spacing::Md))]
+#[require(EditorBorderRadius::all(Radius::Md))]
+#[require(EditorBackgroundColor::new(EditorColor::Button))]
 pub struct ToolButtonGroup;
 
 #[derive(Component)]
 #[require(EditorUiElement)]
 #[require(Node {
     display: Display::Block,
-    height: Val::Px(14.0),
     width: Val::Px(1.0),
     ..default()
 })]
-#[require(EditorBackgroundColor(EditorColor::Background, None, None))]
+#[require(EditorHeight(Height::Separator))]
+#[require(EditorBackgroundColor::new(EditorColor::Background))]
 pub struct ToolButtonSeparator;
 
-#[derive(Component)]
+#[derive(Component, Clone)]
 pub struct ToolButton {
     pub action: Tool,
     pub is_active: bool,
@@ -386,10 +500,9 @@ reactive_element!(
                 display: Display::Flex,
                 align_items: AlignItems::Center,
                 justify_content: JustifyContent::Center,
-                width: Val::Px(25.0),
-                height: Val::Px(25.0),
                 ..default()
             },
+            EditorIconSize(IconSize::Xl),
             EditorBackgroundColor(
                 if tool_button.is_active {
                     EditorColor::Background
@@ -401,14 +514,10 @@ reactive_element!(
             ),
             Button,
             ClickAction(UiEvent::SelectTool(tool_button.action)),
-            BorderRadius::all(Val::Px(3.0)),
+            EditorBorderRadius::all(Radius::Sm),
             children![(
                 EditorUiElement,
-                Node {
-                    width: Val::Px(20.0),
-                    height: Val::Px(20.0),
-                    ..default()
-                },
+                EditorIconSize(IconSize::Lg),
                 ImageNode {
                     image: tool_button.icon.clone(),
                     ..default()
@@ -418,35 +527,51 @@ reactive_element!(
     }
 );
 
-#[derive(Component)]
+#[derive(Component, Clone)]
 pub struct StatusBar {
     pub text: String,
+    /// The chords of a multi-chord shortcut that were pressed so far, shown at the end of the status bar.
+    pub pending_chords: Option<String>,
 }
 
 reactive_element!(StatusBar, reactive_status_bar, |status_bar: &StatusBar| {
     (
         EditorUiElement,
-        NodeFullscreenDisplay::new(Display::Flex, Display::None),
+        NodeFullscreenDisplay::new(Display::Flex, Display::None).with_slide(SlideEdge::Bottom).with_fade(),
         Node {
-            height: Val::Px(20.0),
             align_items: AlignItems::Center,
-            padding: UiRect::all(Val::Px(10.0)),
+            justify_content: JustifyContent::SpaceBetween,
             ..default()
         },
+        EditorHeight(Height::StatusBar),
+        EditorPadding::all(Spacing::Md),
         EditorBackgroundColor(EditorColor::Background, None, None),
-        children![(
-            EditorUiElement,
-            Text::new(format!("Status: {}", &status_bar.text)),
-            EditorTextColor(EditorColor::Text, None, None),
-            TextFont {
-                font_size: 13.0,
-                ..default()
-            }
-        )],
+        children![
+            (
+                EditorUiElement,
+                Text::new(format!("Status: {}", &status_bar.text)),
+                EditorTextColor(EditorColor::Text, None, None),
+                EditorFontSize(FontSize::Body)
+            ),
+            (
+                EditorUiElement,
+                Node {
+                    display: if status_bar.pending_chords.is_some() { Display::Flex } else { Display::None },
+                    ..default()
+                },
+                Text::new(status_bar.pending_chords.clone().unwrap_or_default()),
+                EditorTextColor(EditorColor::FadedText, None, None),
+                EditorFontSize(FontSize::Body)
+            )
+        ],
     )
 });
 
-#[derive(Component)]
+/// The area in which the scene cameras are rendered.
+/// Interaction is used to tell whether the cursor is over the viewport and not over other UI elements.
+#[derive(Component, Clone)]
+#[require(Interaction)]
+#[require(ShortcutScope::Viewport)]
 pub struct CameraPreview;
 
 reactive_element!(
@@ -467,9 +592,10 @@ reactive_element!(
     }
 );
 
-#[derive(Component)]
+#[derive(Component, Clone)]
 pub struct FpsCounter {
-    pub fps: Option<f32>,
+    /// Whole frames per second, so the counter is only rendered again when the shown number changes.
+    pub fps: Option<u32>,
 }
 
 reactive_element!(
@@ -480,22 +606,47 @@ reactive_element!(
             EditorUiElement,
             Node {
                 display: Display::Flex,
-                height: Val::Px(20.0),
                 justify_self: JustifySelf::End,
                 align_items: AlignItems::Center,
                 ..default()
             },
+            EditorHeight(Height::StatusBar),
             Text::new(format!(
                 "FPS: {}",
                 fps_counter
                     .fps
-                    .map(|fps| (fps as u32).to_string())
+                    .map(|fps| fps.to_string())
                     .unwrap_or("--".to_string())
             )),
-            TextFont {
-                font_size: 13.0,
-                ..default()
-            },
+            EditorFontSize(FontSize::Body),
         )
     }
 );
+
+/// Dialog that covers the editor, like the file dialog. While one is open, only the shortcut scopes inside it
+/// are active.
+#[derive(Component, Default, Clone, Copy)]
+pub struct Modal;
+
+/// Node that scrolls vertically with the mouse wheel while the cursor is over it.
+/// The Node needs `overflow: Overflow::scroll_y()`.
+#[derive(Component, Default, Clone, Copy)]
+#[require(RelativeCursorPosition)]
+pub struct ScrollView;
+
+fn scroll_views(
+    mut mouse_wheel_reader: MessageReader<MouseWheel>,
+    mut scroll_views: Query<(&mut ScrollPosition, &RelativeCursorPosition), With<ScrollView>>,
+) {
+    for event in mouse_wheel_reader.read() {
+        let delta = match event.unit {
+            MouseScrollUnit::Line => event.y * SCROLL_LINE_HEIGHT,
+            MouseScrollUnit::Pixel => event.y,
+        };
+        for (mut scroll_position, relative_cursor_position) in scroll_views.iter_mut() {
+            if relative_cursor_position.cursor_over {
+                scroll_position.y = (scroll_position.y - delta).max(0.0);
+            }
+        }
+    }
+}
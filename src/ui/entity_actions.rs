@@ -0,0 +1,244 @@
+use std::any::TypeId;
+
+use bevy::{
+    camera::primitives::Aabb,
+    ecs::{entity::EntityHashMap, entity_disabling::Disabled},
+    prelude::*,
+};
+
+use crate::ui::{
+    ClickAction, ClickActionDisabled, EditorUiCamera, UiEvent,
+    elements::MenuBarButton,
+    history::{DespawnCommand, EditorCommand, EditorHistory, SpawnCommand},
+    scene_file::build_scene,
+    selection::Selected,
+};
+
+/// Distance in front of the camera at which new entities are added.
+const ADD_ENTITY_DISTANCE: f32 = 10.0;
+
+/// Distance between the camera and the framed entities, relative to the radius of their bounds.
+const FRAME_DISTANCE: f32 = 2.5;
+
+pub struct EntityActionsPlugin;
+
+impl Plugin for EntityActionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EntityClipboard>()
+            .add_systems(Update, handle_entity_actions)
+            .add_systems(Update, update_paste_menu_items);
+    }
+}
+
+/// Actions on the scene entities, used by the context menus of the viewport and the hierarchy.
+/// All actions except Add and Paste apply to the selected entities.
+#[derive(Reflect, Hash, Eq, PartialEq, Clone, Copy, Debug)]
+pub enum EntityAction {
+    /// Spawns an empty entity in front of the camera.
+    Add,
+    Copy,
+    /// Spawns a copy of the entities on the clipboard and selects it.
+    Paste,
+    Delete,
+    /// Moves the camera so it looks at the selected entities from close by.
+    FrameSelection,
+}
+
+/// The entities that were copied with [`EntityAction::Copy`], including their descendants.
+#[derive(Resource, Default)]
+pub struct EntityClipboard {
+    scene: Option<DynamicScene>,
+    /// The copied entities without their descendants.
+    roots: Vec<Entity>,
+}
+
+impl EntityClipboard {
+    pub fn is_empty(&self) -> bool {
+        self.scene.is_none()
+    }
+}
+
+fn handle_entity_actions(mut commands: Commands, mut ui_event_reader: MessageReader<UiEvent>) {
+    for event in ui_event_reader.read() {
+        let UiEvent::Entity(action) = event else {
+            continue;
+        };
+        match action {
+            EntityAction::Add => commands.queue(add_entity),
+            EntityAction::Copy => commands.queue(copy_entities),
+            EntityAction::Paste => commands.queue(paste_entities),
+            EntityAction::Delete => commands.queue(delete_entities),
+            EntityAction::FrameSelection => commands.queue(frame_selection),
+        }
+    }
+}
+
+/// Returns the selected entities that are not descendants of other selected entities.
+fn selected_roots(world: &mut World) -> Vec<Entity> {
+    let mut selected: Vec<Entity> = world.query_filtered::<Entity, With<Selected>>().iter(world).collect();
+    selected.sort();
+    let mut parents = world.query::<&ChildOf>();
+    let parents = parents.query(world);
+    selected
+        .iter()
+        .copied()
+        .filter(|entity| !parents.iter_ancestors(*entity).any(|ancestor| selected.contains(&ancestor)))
+        .collect()
+}
+
+/// Replaces the selection with the given entities.
+fn select(world: &mut World, entities: &[Entity]) {
+    let selected: Vec<Entity> = world.query_filtered::<Entity, With<Selected>>().iter(world).collect();
+    for entity in selected {
+        world.entity_mut(entity).remove::<Selected>();
+    }
+    for entity in entities {
+        world.entity_mut(*entity).insert(Selected);
+    }
+}
+
+fn record(world: &mut World, command: impl EditorCommand) {
+    world.resource_scope(|world, mut history: Mut<EditorHistory>| history.push(world, Box::new(command)));
+}
+
+fn add_entity(world: &mut World) {
+    let translation = world
+        .query_filtered::<(&Camera, &GlobalTransform), (With<Camera3d>, Without<EditorUiCamera>)>()
+        .iter(world)
+        .find(|(camera, _)| camera.is_active)
+        .map_or(Vec3::ZERO, |(_, camera_transform)| {
+            camera_transform.translation() + camera_transform.forward() * ADD_ENTITY_DISTANCE
+        });
+    let entity = world
+        .spawn((Name::new("Entity"), Transform::from_translation(translation)))
+        .id();
+    select(world, &[entity]);
+    record(world, SpawnCommand { entities: vec![entity] });
+}
+
+fn copy_entities(world: &mut World) {
+    let roots = selected_roots(world);
+    if roots.is_empty() {
+        return;
+    }
+    let mut children = world.query::<&Children>();
+    let children = children.query(world);
+    let entities: Vec<Entity> = roots
+        .iter()
+        .flat_map(|root| std::iter::once(*root).chain(children.iter_descendants(*root)))
+        // Deleted entities are disabled until they are dropped from the history
+        .filter(|entity| world.get::<Disabled>(*entity).is_none())
+        .collect();
+
+    let mut scene = build_scene(world, &entities);
+    // The hierarchy is rebuilt from ChildOf when pasting, and the copies of the roots become root entities
+    for scene_entity in scene.entities.iter_mut() {
+        let is_root = roots.contains(&scene_entity.entity);
+        scene_entity.components.retain(|component| {
+            let type_id = component.get_represented_type_info().map(|info| info.type_id());
+            type_id != Some(TypeId::of::<Children>()) && !(is_root && type_id == Some(TypeId::of::<ChildOf>()))
+        });
+    }
+    let mut clipboard = world.resource_mut::<EntityClipboard>();
+    clipboard.scene = Some(scene);
+    clipboard.roots = roots;
+}
+
+fn paste_entities(world: &mut World) {
+    let result = world.resource_scope(|world, clipboard: Mut<EntityClipboard>| {
+        let scene = clipboard.scene.as_ref()?;
+        let mut entity_map = EntityHashMap::default();
+        let result = scene.write_to_world(world, &mut entity_map).map(|()| {
+            let entities: Vec<Entity> = scene
+                .entities
+                .iter()
+                .filter_map(|scene_entity| entity_map.get(&scene_entity.entity).copied())
+                .collect();
+            let roots: Vec<Entity> = clipboard
+                .roots
+                .iter()
+                .filter_map(|root| entity_map.get(root).copied())
+                .collect();
+            (entities, roots)
+        });
+        Some(result)
+    });
+    let (entities, roots) = match result {
+        Some(Ok(pasted)) => pasted,
+        Some(Err(error)) => {
+            error!("Failed to paste the entities: {error}");
+            return;
+        }
+        None => return,
+    };
+
+    // Writing a scene skips the hierarchy hooks, so inserting ChildOf again adds the children to their parents
+    for entity in entities {
+        if let Some(child_of) = world.get::<ChildOf>(entity).cloned() {
+            world.entity_mut(entity).insert(child_of);
+        }
+    }
+    select(world, &roots);
+    record(world, SpawnCommand { entities: roots });
+}
+
+fn delete_entities(world: &mut World) {
+    let entities = selected_roots(world);
+    if entities.is_empty() {
+        return;
+    }
+    let mut command = DespawnCommand { entities };
+    command.apply(world);
+    record(world, command);
+}
+
+fn frame_selection(world: &mut World) {
+    // Bounding spheres of the selected entities, entities without a mesh count as small spheres
+    let bounds: Vec<(Vec3, f32)> = world
+        .query_filtered::<(&GlobalTransform, Option<&Aabb>), With<Selected>>()
+        .iter(world)
+        .map(|(global_transform, aabb)| match aabb {
+            Some(aabb) => (
+                global_transform.transform_point(aabb.center.into()),
+                (Vec3::from(aabb.half_extents) * global_transform.scale()).length(),
+            ),
+            None => (global_transform.translation(), 0.5),
+        })
+        .collect();
+    let Some(min) = bounds.iter().map(|(center, radius)| center - radius).reduce(Vec3::min) else {
+        return;
+    };
+    let max = bounds
+        .iter()
+        .map(|(center, radius)| center + radius)
+        .fold(min, Vec3::max);
+    let center = (min + max) / 2.0;
+    let radius = (max - min).length() / 2.0;
+
+    for mut transform in world
+        .query_filtered::<&mut Transform, (With<Camera3d>, Without<EditorUiCamera>)>()
+        .iter_mut(world)
+    {
+        transform.translation = center - transform.forward() * radius * FRAME_DISTANCE;
+    }
+}
+
+/// Greys out the Paste menu items while there is nothing to paste.
+fn update_paste_menu_items(
+    mut commands: Commands,
+    clipboard: Res<EntityClipboard>,
+    mut menu_bar_buttons: Query<(Entity, &mut MenuBarButton, &ClickAction, Has<ClickActionDisabled>)>,
+) {
+    let is_enabled = !clipboard.is_empty();
+    for (entity, mut menu_bar_button, click_action, is_disabled) in menu_bar_buttons.iter_mut() {
+        if click_action.0 != UiEvent::Entity(EntityAction::Paste) || is_disabled != is_enabled {
+            continue;
+        }
+        menu_bar_button.is_enabled = is_enabled;
+        if is_enabled {
+            commands.entity(entity).remove::<ClickActionDisabled>();
+        } else {
+            commands.entity(entity).insert(ClickActionDisabled);
+        }
+    }
+}
@@ -0,0 +1,558 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use bevy::{
+    prelude::*,
+    ui::FocusPolicy,
+};
+
+use crate::ui::{
+    ClickAction, CurrentTab, DOUBLE_CLICK_TIME, EditorUiElement, UiEvent,
+    colors::{EditorBackgroundColor, EditorColor, EditorTextColor},
+    elements::{EditorUi, Modal, ScrollView, reactive_element},
+    reactive::Reconcile,
+    scene_file::{OpenScene, SCENE_FILE_EXTENSION, SaveScene, default_scene_folder},
+    shortcuts::ShortcutScope,
+    style::{
+        EditorBorderRadius, EditorFontSize, EditorGap, EditorHeight, EditorPadding, EditorWidth, FontSize, Height, Radius,
+        Spacing, Width,
+    },
+    tabs::OpenDocuments,
+    text_input::{
+        AutoFocus, TextInput, TextInputCancelled, TextInputChanged, TextInputFocus, TextInputSubmitted,
+        update_text_input_focus,
+    },
+};
+
+/// Maximum length of a file name on common file systems.
+const MAX_FILE_NAME_LENGTH: usize = 255;
+
+pub struct FileDialogPlugin;
+
+impl Plugin for FileDialogPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FileDialogRoot>()
+            .add_systems(Update, open_file_dialog)
+            .add_systems(Update, handle_file_name_input.after(update_text_input_focus))
+            .add_systems(
+                Update,
+                handle_file_dialog_events
+                    .after(open_file_dialog)
+                    .after(handle_file_name_input),
+            )
+            .add_systems(PostUpdate, reactive_file_dialog);
+    }
+}
+
+/// The folder the file dialog starts in. The dialog can't navigate outside of it.
+/// Defaults to the Bevy asset folder, insert the resource to use another folder.
+#[derive(Resource, Clone, Debug)]
+pub struct FileDialogRoot(pub PathBuf);
+
+impl Default for FileDialogRoot {
+    fn default() -> Self {
+        Self(default_scene_folder())
+    }
+}
+
+#[derive(Reflect, Hash, Eq, PartialEq, Clone, Copy, Debug)]
+pub enum FileDialogMode {
+    Open,
+    SaveAs,
+}
+
+/// Actions of the buttons and keys in the file dialog.
+#[derive(Reflect, Hash, Eq, PartialEq, Clone, Debug)]
+pub enum FileDialogAction {
+    /// Shows the contents of the given folder.
+    Navigate(PathBuf),
+    /// Clicks an entry of the current folder.
+    Select(String),
+    ToggleFilter,
+    Confirm,
+    Cancel,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct FileDialogEntry {
+    pub name: String,
+    pub is_directory: bool,
+}
+
+/// Modal dialog for choosing a scene file to open or to save to.
+#[derive(Component, Clone, PartialEq)]
+#[require(Modal, ShortcutScope::FileDialog)]
+pub struct FileDialog {
+    pub mode: FileDialogMode,
+    pub root: PathBuf,
+    pub directory: PathBuf,
+    /// The folders and files in the directory, folders first.
+    pub entries: Vec<FileDialogEntry>,
+    pub file_name: String,
+    /// Only shows files with the scene file extension when true.
+    pub filter_extension: bool,
+    pub error: Option<String>,
+}
+
+impl FileDialog {
+    pub fn new(mode: FileDialogMode, root: PathBuf, directory: PathBuf, file_name: String) -> Self {
+        let mut file_dialog = Self {
+            mode,
+            directory: root.clone(),
+            root,
+            entries: Vec::new(),
+            file_name,
+            filter_extension: true,
+            error: None,
+        };
+        file_dialog.navigate(directory);
+        file_dialog
+    }
+
+    /// Shows the contents of the given folder, if it is inside the root folder.
+    pub fn navigate(&mut self, directory: PathBuf) {
+        if directory.starts_with(&self.root) && directory.is_dir() {
+            self.directory = directory;
+        }
+        self.refresh();
+    }
+
+    /// Reads the entries of the current folder. Hidden files are left out.
+    pub fn refresh(&mut self) {
+        self.error = None;
+        self.entries = match fs::read_dir(&self.directory) {
+            Ok(read_dir) => read_dir
+                .filter_map(|entry| entry.ok())
+                .map(|entry| FileDialogEntry {
+                    name: entry.file_name().to_string_lossy().into_owned(),
+                    is_directory: entry.path().is_dir(),
+                })
+                .filter(|entry| !entry.name.starts_with('.'))
+                .filter(|entry| entry.is_directory || !self.filter_extension || has_scene_extension(&entry.name))
+                .collect(),
+            Err(error) => {
+                self.error = Some(format!("Can't read folder: {error}"));
+                Vec::new()
+            }
+        };
+        self.entries.sort_by_cached_key(|entry| (!entry.is_directory, entry.name.to_lowercase()));
+    }
+
+    /// The folders from the root folder to the current folder, with their names.
+    pub fn breadcrumbs(&self) -> Vec<(String, PathBuf)> {
+        let root_name = self
+            .root
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| self.root.display().to_string());
+        let mut path = self.root.clone();
+        let mut breadcrumbs = vec![(root_name, path.clone())];
+        if let Ok(relative) = self.directory.strip_prefix(&self.root) {
+            for component in relative.components() {
+                path.push(component);
+                breadcrumbs.push((component.as_os_str().to_string_lossy().into_owned(), path.clone()));
+            }
+        }
+        breadcrumbs
+    }
+
+    /// Returns the chosen file, or enters the folder if the file name is a folder.
+    /// Sets the error when the file name is not valid.
+    pub fn confirm(&mut self) -> Option<PathBuf> {
+        let file_name = self.file_name.trim().to_string();
+        if file_name.is_empty() {
+            return None;
+        }
+        if !is_valid_file_name(&file_name) {
+            self.error = Some("File names can't contain folders".to_string());
+            return None;
+        }
+
+        let path = self.directory.join(&file_name);
+        if path.is_dir() {
+            self.file_name.clear();
+            self.navigate(path);
+            return None;
+        }
+        match self.mode {
+            FileDialogMode::Open if !path.is_file() => {
+                self.error = Some(format!("{file_name} does not exist"));
+                None
+            }
+            FileDialogMode::Open => Some(path),
+            FileDialogMode::SaveAs if has_scene_extension(&file_name) => Some(path),
+            FileDialogMode::SaveAs => Some(self.directory.join(format!("{file_name}.{SCENE_FILE_EXTENSION}"))),
+        }
+    }
+}
+
+fn has_scene_extension(file_name: &str) -> bool {
+    file_name.ends_with(&format!(".{SCENE_FILE_EXTENSION}"))
+}
+
+/// Returns false for file names with path separators or "..", which would save outside the current folder.
+fn is_valid_file_name(file_name: &str) -> bool {
+    !file_name.contains(['/', '\\']) && file_name.trim() != ".."
+}
+
+/// Marks the text input for the file name.
+#[derive(Component, Default, Clone, Copy)]
+pub struct FileNameInput;
+
+/// Marks the scrollable list of folders and files.
+#[derive(Component, Default, Clone, Copy)]
+pub struct FileDialogList;
+
+/// Opens the file dialog for the Open and Save As events, replacing a dialog that is already open.
+/// Save As starts in the folder of the current document.
+fn open_file_dialog(
+    mut commands: Commands,
+    mut ui_event_reader: MessageReader<UiEvent>,
+    root: Res<FileDialogRoot>,
+    open_documents: Res<OpenDocuments>,
+    current_tab: Res<CurrentTab>,
+    editor_ui: Single<Entity, With<EditorUi>>,
+    file_dialogs: Query<Entity, With<FileDialog>>,
+) {
+    for event in ui_event_reader.read() {
+        let file_dialog = match event {
+            UiEvent::FileOpen => FileDialog::new(FileDialogMode::Open, root.0.clone(), root.0.clone(), String::new()),
+            UiEvent::FileSaveAs => {
+                let Some(document) = current_tab.0.and_then(|index| open_documents.get(index)) else {
+                    continue;
+                };
+                let directory = document
+                    .path
+                    .as_ref()
+                    .and_then(|path| path.parent())
+                    .map_or_else(|| root.0.clone(), Path::to_path_buf);
+                let file_name = format!("{}.{SCENE_FILE_EXTENSION}", document.name);
+                FileDialog::new(FileDialogMode::SaveAs, root.0.clone(), directory, file_name)
+            }
+            _ => continue,
+        };
+        for entity in file_dialogs.iter() {
+            commands.entity(entity).despawn();
+        }
+        commands.entity(*editor_ui).with_child(file_dialog);
+    }
+}
+
+/// Handles the buttons of the file dialog. Confirming opens or saves the scene and closes the dialog.
+#[allow(clippy::too_many_arguments)]
+fn handle_file_dialog_events(
+    mut commands: Commands,
+    mut ui_event_reader: MessageReader<UiEvent>,
+    mut open_scene_writer: MessageWriter<OpenScene>,
+    mut save_scene_writer: MessageWriter<SaveScene>,
+    mut file_dialogs: Query<(Entity, &mut FileDialog)>,
+    mut file_dialog_lists: Query<&mut ScrollPosition, With<FileDialogList>>,
+    mut file_name_inputs: Query<(Entity, &mut TextInput), With<FileNameInput>>,
+    mut text_input_focus: ResMut<TextInputFocus>,
+    time: Res<Time>,
+    mut last_click: Local<Option<(PathBuf, f64)>>,
+) {
+    for event in ui_event_reader.read() {
+        let UiEvent::FileDialog(action) = event else {
+            continue;
+        };
+        let Ok((entity, mut file_dialog)) = file_dialogs.single_mut() else {
+            continue;
+        };
+
+        let mut confirm = false;
+        match action {
+            FileDialogAction::Navigate(directory) => {
+                file_dialog.navigate(directory.clone());
+                for mut scroll_position in file_dialog_lists.iter_mut() {
+                    scroll_position.0 = Vec2::ZERO;
+                }
+            }
+            FileDialogAction::Select(name) => {
+                let path = file_dialog.directory.join(name);
+                if path.is_dir() {
+                    file_dialog.navigate(path);
+                    for mut scroll_position in file_dialog_lists.iter_mut() {
+                        scroll_position.0 = Vec2::ZERO;
+                    }
+                } else {
+                    // Clicking the same file twice in quick succession confirms it
+                    let now = time.elapsed_secs_f64();
+                    confirm = last_click
+                        .as_ref()
+                        .is_some_and(|(last_path, last_time)| *last_path == path && now - last_time < DOUBLE_CLICK_TIME);
+                    *last_click = Some((path, now));
+                    file_dialog.file_name = name.clone();
+                    file_dialog.error = None;
+                    // Typing replaces the selected file name
+                    if let Ok((file_name_input, mut text_input)) = file_name_inputs.single_mut() {
+                        text_input_focus.focus_with_value(file_name_input, &mut text_input, name.clone());
+                    }
+                }
+            }
+            FileDialogAction::ToggleFilter => {
+                file_dialog.filter_extension = !file_dialog.filter_extension;
+                file_dialog.refresh();
+            }
+            FileDialogAction::Confirm => confirm = true,
+            FileDialogAction::Cancel => {
+                commands.entity(entity).despawn();
+            }
+        }
+
+        if confirm && let Some(path) = file_dialog.confirm() {
+            match file_dialog.mode {
+                FileDialogMode::Open => {
+                    open_scene_writer.write(OpenScene(path));
+                }
+                FileDialogMode::SaveAs => {
+                    save_scene_writer.write(SaveScene(path));
+                }
+            }
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+/// Keeps the file name that is typed. Enter confirms and Escape cancels the dialog.
+/// The file name input keeps the focus while the dialog is open, so typing always edits the file name.
+fn handle_file_name_input(
+    mut text_input_changed_reader: MessageReader<TextInputChanged>,
+    mut text_input_submitted_reader: MessageReader<TextInputSubmitted>,
+    mut text_input_cancelled_reader: MessageReader<TextInputCancelled>,
+    mut ui_event_writer: MessageWriter<UiEvent>,
+    mut text_input_focus: ResMut<TextInputFocus>,
+    mut file_dialogs: Query<&mut FileDialog>,
+    file_name_inputs: Query<(Entity, &TextInput), With<FileNameInput>>,
+) {
+    let Ok(mut file_dialog) = file_dialogs.single_mut() else {
+        text_input_changed_reader.clear();
+        text_input_submitted_reader.clear();
+        text_input_cancelled_reader.clear();
+        return;
+    };
+    for changed in text_input_changed_reader.read() {
+        if file_name_inputs.contains(changed.entity) {
+            file_dialog.file_name = changed.value.clone();
+            file_dialog.error = None;
+        }
+    }
+    for submitted in text_input_submitted_reader.read() {
+        if file_name_inputs.contains(submitted.entity) {
+            ui_event_writer.write(UiEvent::FileDialog(FileDialogAction::Confirm));
+        }
+    }
+    for cancelled in text_input_cancelled_reader.read() {
+        if file_name_inputs.contains(cancelled.entity) {
+            ui_event_writer.write(UiEvent::FileDialog(FileDialogAction::Cancel));
+        }
+    }
+
+    if !text_input_focus.is_focused()
+        && let Ok((file_name_input, text_input)) = file_name_inputs.single()
+    {
+        text_input_focus.focus(file_name_input, text_input);
+    }
+}
+
+fn file_dialog_button(text: impl Into<String>, action: FileDialogAction) -> impl Bundle {
+    (
+        EditorUiElement,
+        Button,
+        ClickAction(UiEvent::FileDialog(action)),
+        EditorPadding::axes(Spacing::Md, Spacing::Sm),
+        EditorBorderRadius::all(Radius::Md),
+        EditorBackgroundColor(EditorColor::Button, Some(EditorColor::MenuBarButtonHover), None),
+        children![(
+            EditorUiElement,
+            Text::new(text.into()),
+            EditorTextColor(EditorColor::Text, None, None),
+            EditorFontSize(FontSize::Body)
+        )],
+    )
+}
+
+reactive_element!(FileDialog, reactive_file_dialog, |file_dialog: &FileDialog| {
+    let breadcrumbs = file_dialog.breadcrumbs().into_iter().enumerate().map(|(index, (name, path))| {
+        (
+            EditorUiElement,
+            Button,
+            ClickAction(UiEvent::FileDialog(FileDialogAction::Navigate(path))),
+            EditorPadding::axes(Spacing::Sm, Spacing::Xs),
+            EditorBorderRadius::all(Radius::Sm),
+            EditorBackgroundColor(EditorColor::Background, Some(EditorColor::MenuBarButtonHover), None),
+            children![(
+                EditorUiElement,
+                Text::new(if index == 0 { name } else { format!("/ {name}") }),
+                EditorTextColor(EditorColor::Text, None, None),
+                EditorFontSize(FontSize::Body)
+            )],
+        )
+    });
+    let file_name = file_dialog.file_name.clone();
+    let entries = file_dialog.entries.clone().into_iter().map(move |entry| {
+        let is_selected = !entry.is_directory && entry.name == file_name;
+        (
+            EditorUiElement,
+            Button,
+            ClickAction(UiEvent::FileDialog(FileDialogAction::Select(entry.name.clone()))),
+            Node {
+                flex_shrink: 0.0,
+                ..default()
+            },
+            EditorPadding::axes(Spacing::Md, Spacing::Sm),
+            EditorBorderRadius::all(Radius::Sm),
+            EditorBackgroundColor(
+                if is_selected { EditorColor::MenuBarButtonHover } else { EditorColor::Button },
+                Some(EditorColor::MenuBarButtonHover),
+                None,
+            ),
+            children![(
+                EditorUiElement,
+                Text::new(if entry.is_directory { format!("{}/", entry.name) } else { entry.name }),
+                EditorTextColor(EditorColor::Text, None, None),
+                EditorFontSize(FontSize::Body)
+            )],
+        )
+    });
+
+    (
+        EditorUiElement,
+        // Covers the editor so the rest of the UI can't be used while the dialog is open
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        FocusPolicy::Block,
+        GlobalZIndex(10000),
+        EditorBackgroundColor(EditorColor::Overlay, None, None),
+        children![(
+            EditorUiElement,
+            Node {
+                display: Display::Flex,
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            EditorWidth(Width::FileDialog),
+            EditorGap(Spacing::Md),
+            EditorPadding::all(Spacing::Lg),
+            EditorBorderRadius::all(Radius::Lg),
+            EditorBackgroundColor(EditorColor::Background, None, None),
+            children![
+                (
+                    EditorUiElement,
+                    Text::new(match file_dialog.mode {
+                        FileDialogMode::Open => "Open Scene",
+                        FileDialogMode::SaveAs => "Save Scene As",
+                    }),
+                    EditorTextColor(EditorColor::Text, None, None),
+                    EditorFontSize(FontSize::Title)
+                ),
+                (
+                    EditorUiElement,
+                    Node {
+                        display: Display::Flex,
+                        flex_wrap: FlexWrap::Wrap,
+                        ..default()
+                    },
+                    EditorGap(Spacing::Xs),
+                    Children::spawn(SpawnIter(breadcrumbs)),
+                ),
+                (
+                    EditorUiElement,
+                    FileDialogList,
+                    ScrollView,
+                    Node {
+                        display: Display::Flex,
+                        flex_direction: FlexDirection::Column,
+                        overflow: Overflow::scroll_y(),
+                        ..default()
+                    },
+                    EditorHeight(Height::FileList),
+                    EditorGap(Spacing::Xs),
+                    EditorPadding::all(Spacing::Sm),
+                    EditorBorderRadius::all(Radius::Md),
+                    EditorBackgroundColor(EditorColor::Button, None, None),
+                    Children::spawn(SpawnIter(entries)),
+                ),
+                (
+                    EditorUiElement,
+                    Node {
+                        display: Display::Flex,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    EditorGap(Spacing::Md),
+                    children![
+                        (
+                            EditorUiElement,
+                            Text::new("File name"),
+                            EditorTextColor(EditorColor::FadedText, None, None),
+                            EditorFontSize(FontSize::Body)
+                        ),
+                        (
+                            FileNameInput,
+                            AutoFocus,
+                            TextInput::new(file_dialog.file_name.clone())
+                                .with_placeholder(format!("Untitled.{SCENE_FILE_EXTENSION}"))
+                                .with_max_length(MAX_FILE_NAME_LENGTH)
+                                .with_validator(is_valid_file_name),
+                            EditorPadding::axes(Spacing::Md, Spacing::Sm),
+                            EditorBorderRadius::all(Radius::Md),
+                        ),
+                    ],
+                ),
+                (
+                    EditorUiElement,
+                    Node {
+                        display: if file_dialog.error.is_some() { Display::Flex } else { Display::None },
+                        ..default()
+                    },
+                    Text::new(file_dialog.error.clone().unwrap_or_default()),
+                    EditorTextColor(EditorColor::Error, None, None),
+                    EditorFontSize(FontSize::Body)
+                ),
+                (
+                    EditorUiElement,
+                    Node {
+                        display: Display::Flex,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    EditorGap(Spacing::Md),
+                    children![
+                        file_dialog_button(
+                            if file_dialog.filter_extension {
+                                format!("Scene files (*.{SCENE_FILE_EXTENSION})")
+                            } else {
+                                "All files (*)".to_string()
+                            },
+                            FileDialogAction::ToggleFilter,
+                        ),
+                        (
+                            EditorUiElement,
+                            Node {
+                                flex_grow: 1.0,
+                                ..default()
+                            },
+                        ),
+                        file_dialog_button("Cancel", FileDialogAction::Cancel),
+                        file_dialog_button(
+                            match file_dialog.mode {
+                                FileDialogMode::Open => "Open",
+                                FileDialogMode::SaveAs => "Save",
+                            },
+                            FileDialogAction::Confirm,
+                        ),
+                    ],
+                ),
+            ],
+        )],
+    )
+});
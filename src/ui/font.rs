@@ -22,11 +22,15 @@ pub fn load_font(
 }
 
 /// This system adds the font to the text elements that have the EditorUiElement component.
+/// It also runs when the TextFont is replaced, e.g. when a reactive element patches it.
+#[allow(clippy::type_complexity)]
 pub fn update_text_font(
-    mut text_fonts: Query<&mut TextFont, (Added<Text>, With<EditorUiElement>)>,
+    mut text_fonts: Query<&mut TextFont, (Or<(Added<Text>, Changed<TextFont>)>, With<EditorUiElement>)>,
     font_handle: Res<FontHandle>,
 ) {
     for mut text_font in text_fonts.iter_mut() {
-        text_font.font = font_handle.0.clone();
+        if text_font.font != font_handle.0 {
+            text_font.font = font_handle.0.clone();
+        }
     }
 }
\ No newline at end of file
@@ -1,12 +1,19 @@
 use bevy::prelude::*;
 
+use crate::ui::{
+    colors::{EditorBackgroundColor, EditorOpacity, EditorTextColor},
+    transition::TransitionSettings,
+};
+
 pub struct FullscreenPlugin;
 
 impl Plugin for FullscreenPlugin {
     fn build(&self, app: &mut App) {
         app
             .init_state::<FullscreenState>()
+            .register_type::<NodeFullscreenDisplay>()
             .add_systems(Update, update_node_fullscreen_display)
+            .add_systems(Update, fade_fullscreen_nodes.after(update_node_fullscreen_display))
         ;
     }
 }
@@ -18,30 +25,222 @@ pub enum FullscreenState {
     Fullscreen,
 }
 
-#[derive(Component, Default, Clone, Copy)]
+#[derive(Component, Reflect, Default, Clone, Copy)]
+#[reflect(Component)]
+#[require(FullscreenTransition)]
 pub struct NodeFullscreenDisplay {
     pub normal_display: Display,
     pub fullscreen_display: Display,
+    /// The edge of the parent the node slides out of when it is hidden.
+    pub slide_edge: Option<SlideEdge>,
+    /// Fades the colors of the node and its descendants out when it is hidden. Nodes that neither slide nor fade
+    /// are hidden right away.
+    pub fade: bool,
 }
 
 impl NodeFullscreenDisplay {
     pub fn new(normal_display: Display, fullscreen_display: Display) -> Self {
-        Self { normal_display, fullscreen_display }
+        Self { normal_display, fullscreen_display, slide_edge: None, fade: false }
+    }
+
+    /// The display of the node in the state in which it is shown.
+    fn shown_display(&self) -> Display {
+        if self.normal_display != Display::None { self.normal_display } else { self.fullscreen_display }
+    }
+
+    pub fn with_slide(mut self, edge: SlideEdge) -> Self {
+        self.slide_edge = Some(edge);
+        self
+    }
+
+    pub fn with_fade(mut self) -> Self {
+        self.fade = true;
+        self
+    }
+}
+
+#[derive(Reflect, Clone, Copy, Eq, PartialEq, Debug)]
+pub enum SlideEdge {
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+impl SlideEdge {
+    fn margin(self, node: &mut Node) -> &mut Val {
+        match self {
+            SlideEdge::Top => &mut node.margin.top,
+            SlideEdge::Bottom => &mut node.margin.bottom,
+            SlideEdge::Left => &mut node.margin.left,
+            SlideEdge::Right => &mut node.margin.right,
+        }
+    }
+
+    /// The logical size of the node in the direction it slides.
+    fn size(self, computed_node: &ComputedNode) -> f32 {
+        let size = computed_node.size() * computed_node.inverse_scale_factor();
+        match self {
+            SlideEdge::Top | SlideEdge::Bottom => size.y,
+            SlideEdge::Left | SlideEdge::Right => size.x,
+        }
+    }
+}
+
+/// State of the slide and fade animation of a node with a [`NodeFullscreenDisplay`].
+#[derive(Component, Clone, Copy)]
+pub struct FullscreenTransition {
+    /// How far the node is shown, from 0 when it is hidden to 1, before easing.
+    shown: f32,
+    /// The size of the node while it was last shown completely, which is the distance it slides.
+    size: f32,
+    /// The margin the node had before it started sliding.
+    margin: Option<Val>,
+}
+
+impl Default for FullscreenTransition {
+    fn default() -> Self {
+        Self { shown: 1.0, size: 0.0, margin: None }
     }
 }
 
+/// Shows or hides the nodes for the fullscreen state. Nodes with a slide edge move out of the parent behind that
+/// edge with a negative margin before they are hidden, and move back in when they are shown.
+/// Nodes that fade are faded by [`fade_fullscreen_nodes`] while they are shown.
 pub fn update_node_fullscreen_display(
-    mut nodes: Query<(&NodeFullscreenDisplay, &mut Node)>,
+    mut nodes: Query<(&NodeFullscreenDisplay, &mut FullscreenTransition, &mut Node, &ComputedNode)>,
     fullscreen_state: Res<State<FullscreenState>>,
+    transition_settings: Res<TransitionSettings>,
+    time: Res<Time>,
 ) {
-    for (node_fullscreen_display, mut node) in nodes.iter_mut() {
-        node.display = match fullscreen_state.get() {
-            FullscreenState::Normal => {
-                node_fullscreen_display.normal_display
+    for (node_fullscreen_display, mut transition, mut node, computed_node) in nodes.iter_mut() {
+        let display = match fullscreen_state.get() {
+            FullscreenState::Normal => node_fullscreen_display.normal_display,
+            FullscreenState::Fullscreen => node_fullscreen_display.fullscreen_display,
+        };
+        let target = if display == Display::None { 0.0 } else { 1.0 };
+        let slide_edge = node_fullscreen_display.slide_edge;
+
+        let is_animated = (slide_edge.is_some() || node_fullscreen_display.fade) && transition_settings.layout_duration > 0.0;
+        if !is_animated || transition.shown == target {
+            if transition.shown != target {
+                transition.shown = target;
+            }
+            if let Some(edge) = slide_edge
+                && target == 1.0
+                && node.display != Display::None
+            {
+                let size = edge.size(computed_node);
+                if transition.size != size {
+                    transition.size = size;
+                }
             }
-            FullscreenState::Fullscreen => {
-                node_fullscreen_display.fullscreen_display
+            if node.display != display {
+                node.display = display;
             }
+            continue;
+        }
+
+        let step = time.delta_secs() / transition_settings.layout_duration;
+        transition.shown = if target > transition.shown {
+            (transition.shown + step).min(target)
+        } else {
+            (transition.shown - step).max(target)
         };
+
+        if transition.shown == target {
+            if let Some(edge) = slide_edge
+                && let Some(margin) = transition.margin.take()
+            {
+                *edge.margin(&mut node) = margin;
+            }
+            node.display = display;
+            continue;
+        }
+        // While sliding or fading out, the node keeps the display it has when it is shown
+        node.display = node_fullscreen_display.shown_display();
+        if let Some(edge) = slide_edge {
+            let margin = *transition.margin.get_or_insert(*edge.margin(&mut node));
+            let offset = transition.size * (1.0 - transition_settings.ease(transition.shown));
+            *edge.margin(&mut node) = match margin {
+                Val::Px(margin) => Val::Px(margin - offset),
+                _ => Val::Px(-offset),
+            };
+        }
+    }
+}
+
+/// Elements whose colors are set by the editor, which can therefore be faded.
+type ColoredElements<'w, 's> =
+    Query<'w, 's, Option<&'static EditorOpacity>, Or<(With<EditorTextColor>, With<EditorBackgroundColor>)>>;
+
+/// Sets the [`EditorOpacity`] of the colored elements in the nodes that fade, from their [`FullscreenTransition`].
+pub fn fade_fullscreen_nodes(
+    mut commands: Commands,
+    nodes: Query<(Entity, &NodeFullscreenDisplay, &FullscreenTransition), Changed<FullscreenTransition>>,
+    children: Query<&Children>,
+    colored: ColoredElements,
+    transition_settings: Res<TransitionSettings>,
+) {
+    for (entity, node_fullscreen_display, transition) in nodes.iter() {
+        if !node_fullscreen_display.fade {
+            continue;
+        }
+        let opacity = EditorOpacity(transition_settings.ease(transition.shown));
+        for entity in std::iter::once(entity).chain(children.iter_descendants(entity)) {
+            match colored.get(entity) {
+                Ok(Some(current)) if *current == opacity => {}
+                Ok(None) if opacity.0 == 1.0 => {}
+                Ok(_) => {
+                    commands.entity(entity).try_insert(opacity);
+                }
+                Err(_) => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::ui::colors::{EditorColor, UiColors, update_colors};
+
+    #[test]
+    fn nodes_fade_out_before_they_are_hidden() {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .insert_resource(TransitionSettings::default())
+            .insert_resource(UiColors::default())
+            .insert_resource(State::new(FullscreenState::Normal))
+            .add_systems(Update, (update_node_fullscreen_display, fade_fullscreen_nodes).chain())
+            .add_systems(PostUpdate, update_colors);
+        let panel = app
+            .world_mut()
+            .spawn((Node::default(), NodeFullscreenDisplay::new(Display::Flex, Display::None).with_fade()))
+            .id();
+        let label = app
+            .world_mut()
+            .spawn((EditorTextColor(EditorColor::Text, None, None), TextColor::default(), ChildOf(panel)))
+            .id();
+        app.update();
+
+        let alpha = |app: &App| app.world().get::<TextColor>(label).unwrap().0.alpha();
+        let display = |app: &App| app.world().get::<Node>(panel).unwrap().display;
+        let shown_alpha = UiColors::default().get(EditorColor::Text).alpha();
+        assert_eq!(alpha(&app), shown_alpha);
+
+        let duration = app.world().resource::<TransitionSettings>().layout_duration;
+        app.insert_resource(State::new(FullscreenState::Fullscreen));
+        app.world_mut().resource_mut::<Time>().advance_by(Duration::from_secs_f32(duration / 2.0));
+        app.update();
+        assert_eq!(display(&app), Display::Flex);
+        assert!(alpha(&app) > 0.0 && alpha(&app) < shown_alpha);
+
+        app.world_mut().resource_mut::<Time>().advance_by(Duration::from_secs_f32(duration));
+        app.update();
+        assert_eq!(display(&app), Display::None);
+        assert_eq!(alpha(&app), 0.0);
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,551 @@
+use bevy::{
+    platform::collections::HashSet,
+    prelude::*,
+    ui::RelativeCursorPosition,
+};
+
+use crate::ui::{
+    ClickAction, DOUBLE_CLICK_TIME, EditorUiElement, UiEvent,
+    colors::{EditorBackgroundColor, EditorColor, EditorTextColor},
+    elements::{ScrollView, reactive_element},
+    entity_actions::EntityAction,
+    fullscreen::{NodeFullscreenDisplay, SlideEdge},
+    history::{EditorHistoryCommandsExt, RenameCommand, ReparentCommand},
+    menu::ContextMenu,
+    reactive::Reconcile,
+    scene_file::SceneRootFilter,
+    selection::{Selected, set_selection},
+    shortcuts::ShortcutScope,
+    style::{
+        EditorBorderRadius, EditorFontSize, EditorGap, EditorIconSize, EditorPadding, EditorWidth, FontSize, IconSize, Radius,
+        Spacing, Width,
+    },
+    text_input::{TextInput, TextInputCancelled, TextInputChanged, TextInputFocus, TextInputSubmitted, update_text_input_focus},
+};
+
+/// Indentation of the rows per level of the tree, in pixels.
+const ROW_INDENT: f32 = 14.0;
+
+pub struct HierarchyPlugin;
+
+impl Plugin for HierarchyPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<HierarchyRowEntity>()
+            .register_type::<RenameInput>()
+            .add_systems(Update, handle_hierarchy_events.after(update_text_input_focus))
+            .add_systems(Update, select_rows_on_right_click)
+            .add_systems(Update, handle_hierarchy_drag)
+            .add_systems(Update, handle_rename_input.after(handle_hierarchy_events))
+            .add_systems(
+                Update,
+                update_hierarchy_panel
+                    .after(handle_hierarchy_events)
+                    .after(handle_rename_input),
+            )
+            .add_systems(Update, update_workspace_dock)
+            .add_systems(PostUpdate, reactive_hierarchy_panel);
+    }
+}
+
+/// Actions of the rows and buttons in the hierarchy panel.
+#[derive(Reflect, Hash, Eq, PartialEq, Clone, Copy, Debug)]
+pub enum HierarchyAction {
+    Select(Entity),
+    ToggleExpanded(Entity),
+    /// Starts renaming the entity in its row.
+    Rename(Entity),
+    /// Starts renaming the topmost selected entity.
+    RenameSelected,
+    /// Moves the panel to the other side of the CameraPreview.
+    ToggleDock,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum DockSide {
+    #[default]
+    Left,
+    Right,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct HierarchyRow {
+    pub entity: Entity,
+    pub label: String,
+    pub depth: usize,
+    pub has_children: bool,
+    pub is_expanded: bool,
+    pub is_selected: bool,
+}
+
+/// Panel next to the CameraPreview that shows the scene entities as a tree.
+#[derive(Component, Clone, PartialEq, Default)]
+#[require(ShortcutScope::Hierarchy)]
+pub struct HierarchyPanel {
+    pub dock: DockSide,
+    /// The visible rows, in tree order.
+    pub rows: Vec<HierarchyRow>,
+    /// Entities whose children are hidden.
+    pub collapsed: HashSet<Entity>,
+    /// The entity that is being renamed and the name typed so far.
+    pub renaming: Option<(Entity, String)>,
+}
+
+/// The entity a row in the hierarchy panel stands for.
+#[derive(Component, Reflect, Clone, Copy)]
+#[reflect(Component)]
+pub struct HierarchyRowEntity(pub Entity);
+
+/// The text input for renaming the entity of a row.
+#[derive(Component, Reflect, Clone, Copy)]
+#[reflect(Component)]
+pub struct RenameInput(pub Entity);
+
+/// Contains the CameraPreview and the panels next to it.
+#[derive(Component)]
+#[require(EditorUiElement)]
+#[require(Node {
+    display: Display::Flex,
+    flex_grow: 1.0,
+    width: Val::Percent(100.0),
+    ..default()
+})]
+pub struct Workspace;
+
+/// Returns the Name of an entity, or its id if it has no Name.
+fn entity_label(entity: Entity, name: Option<&Name>) -> String {
+    match name {
+        Some(name) => name.to_string(),
+        None => format!("Entity {entity}"),
+    }
+}
+
+type HierarchyEntities<'w, 's> =
+    Query<'w, 's, (Option<&'static Name>, Option<&'static Children>, Has<Selected>), Without<EditorUiElement>>;
+
+/// Rebuilds the rows of the hierarchy panel from the scene entities.
+fn update_hierarchy_panel(
+    mut hierarchy_panel: Single<&mut HierarchyPanel>,
+    roots: Query<Entity, SceneRootFilter>,
+    entities: HierarchyEntities,
+) {
+    fn add_rows(
+        rows: &mut Vec<HierarchyRow>,
+        collapsed: &HashSet<Entity>,
+        entities: &HierarchyEntities,
+        entity: Entity,
+        depth: usize,
+    ) {
+        // Disabled entities, e.g. deleted ones that can be restored with undo, are not listed
+        let Ok((name, children, is_selected)) = entities.get(entity) else {
+            return;
+        };
+        let children: Vec<Entity> = children
+            .map(|children| children.iter().filter(|child| entities.contains(*child)).collect())
+            .unwrap_or_default();
+        let is_expanded = !collapsed.contains(&entity);
+        rows.push(HierarchyRow {
+            entity,
+            label: entity_label(entity, name),
+            depth,
+            has_children: !children.is_empty(),
+            is_expanded,
+            is_selected,
+        });
+        if is_expanded {
+            for child in children {
+                add_rows(rows, collapsed, entities, child, depth + 1);
+            }
+        }
+    }
+
+    let mut roots: Vec<Entity> = roots.iter().collect();
+    roots.sort();
+    let mut rows = Vec::new();
+    for root in roots {
+        add_rows(&mut rows, &hierarchy_panel.collapsed, &entities, root, 0);
+    }
+    if hierarchy_panel.rows != rows {
+        hierarchy_panel.rows = rows;
+    }
+}
+
+/// Selects entities when their row is clicked and starts renaming on double-click.
+#[allow(clippy::too_many_arguments)]
+fn handle_hierarchy_events(
+    mut commands: Commands,
+    mut ui_event_reader: MessageReader<UiEvent>,
+    mut hierarchy_panel: Single<&mut HierarchyPanel>,
+    mut text_input_focus: ResMut<TextInputFocus>,
+    keys: Res<ButtonInput<KeyCode>>,
+    selected: Query<Entity, With<Selected>>,
+    names: Query<Option<&Name>>,
+    mut rename_inputs: Query<(Entity, &RenameInput, &mut TextInput)>,
+    time: Res<Time>,
+    mut last_click: Local<Option<(Entity, f64)>>,
+) {
+    for event in ui_event_reader.read() {
+        let UiEvent::Hierarchy(action) = event else {
+            continue;
+        };
+        let mut rename = None;
+        match *action {
+            HierarchyAction::Select(entity) => {
+                let multi_select = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+                set_selection(&mut commands, &selected, Some(entity), multi_select);
+
+                let now = time.elapsed_secs_f64();
+                let is_double_click = last_click
+                    .is_some_and(|(last_entity, last_time)| last_entity == entity && now - last_time < DOUBLE_CLICK_TIME);
+                *last_click = Some((entity, now));
+                if is_double_click {
+                    rename = Some(entity);
+                } else if hierarchy_panel.renaming.as_ref().is_some_and(|(renaming, _)| *renaming != entity) {
+                    hierarchy_panel.renaming = None;
+                }
+            }
+            HierarchyAction::Rename(entity) => rename = Some(entity),
+            HierarchyAction::RenameSelected => {
+                rename = hierarchy_panel.rows.iter().find(|row| row.is_selected).map(|row| row.entity);
+            }
+            HierarchyAction::ToggleExpanded(entity) => {
+                if !hierarchy_panel.collapsed.remove(&entity) {
+                    hierarchy_panel.collapsed.insert(entity);
+                }
+            }
+            HierarchyAction::ToggleDock => {
+                hierarchy_panel.dock = match hierarchy_panel.dock {
+                    DockSide::Left => DockSide::Right,
+                    DockSide::Right => DockSide::Left,
+                };
+            }
+        }
+        if let Some(entity) = rename
+            && let Ok(name) = names.get(entity)
+            && let Some((rename_input, _, mut text_input)) = rename_inputs.iter_mut().find(|(_, input, _)| input.0 == entity)
+        {
+            let name = entity_label(entity, name);
+            // The input may still hold an older name, e.g. from before an undo
+            text_input_focus.focus_with_value(rename_input, &mut text_input, name.clone());
+            hierarchy_panel.renaming = Some((entity, name));
+        }
+    }
+}
+
+/// Selects the entity of a row when it is right-clicked, so the actions of its context menu apply to it.
+/// The selection is kept when the entity is already selected.
+fn select_rows_on_right_click(
+    mut commands: Commands,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    rows: Query<(&HierarchyRowEntity, &Interaction)>,
+    selected: Query<Entity, With<Selected>>,
+) {
+    if !mouse_buttons.just_pressed(MouseButton::Right) {
+        return;
+    }
+    if let Some((row, _)) = rows.iter().find(|(_, interaction)| **interaction == Interaction::Hovered)
+        && !selected.contains(row.0)
+    {
+        set_selection(&mut commands, &selected, Some(row.0), false);
+    }
+}
+
+/// Keeps the name that is typed in the rename input. Submitting renames the entity, and renaming stops
+/// when the input is cancelled or loses focus.
+fn handle_rename_input(
+    mut commands: Commands,
+    mut text_input_changed_reader: MessageReader<TextInputChanged>,
+    mut text_input_submitted_reader: MessageReader<TextInputSubmitted>,
+    mut text_input_cancelled_reader: MessageReader<TextInputCancelled>,
+    mut hierarchy_panel: Single<&mut HierarchyPanel>,
+    text_input_focus: Res<TextInputFocus>,
+    rename_inputs: Query<&RenameInput>,
+) {
+    for changed in text_input_changed_reader.read() {
+        if let Ok(rename_input) = rename_inputs.get(changed.entity)
+            && let Some((entity, name)) = hierarchy_panel.renaming.as_mut()
+            && *entity == rename_input.0
+        {
+            *name = changed.value.clone();
+        }
+    }
+    for submitted in text_input_submitted_reader.read() {
+        if let Ok(&RenameInput(entity)) = rename_inputs.get(submitted.entity) {
+            let name = submitted.value.trim().to_string();
+            if !name.is_empty() {
+                commands.execute_editor_command_with(move |world| RenameCommand::new(world, entity, name));
+            }
+            hierarchy_panel.renaming = None;
+        }
+    }
+    for cancelled in text_input_cancelled_reader.read() {
+        if rename_inputs.contains(cancelled.entity) {
+            hierarchy_panel.renaming = None;
+        }
+    }
+
+    let is_focused = text_input_focus
+        .entity()
+        .is_some_and(|entity| rename_inputs.contains(entity));
+    if hierarchy_panel.renaming.is_some() && !is_focused {
+        hierarchy_panel.renaming = None;
+    }
+}
+
+/// Reparents an entity when its row is dragged onto another row.
+/// Dropping it on the empty part of the panel makes it a root entity.
+fn handle_hierarchy_drag(
+    mut commands: Commands,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    rows: Query<(&HierarchyRowEntity, &Interaction)>,
+    hierarchy_panel: Single<&RelativeCursorPosition, With<HierarchyPanel>>,
+    parents: Query<&ChildOf>,
+    mut dragged: Local<Option<Entity>>,
+) {
+    if mouse_buttons.just_pressed(MouseButton::Left) {
+        *dragged = rows
+            .iter()
+            .find(|(_, interaction)| **interaction == Interaction::Pressed)
+            .map(|(row, _)| row.0);
+    }
+    if !mouse_buttons.just_released(MouseButton::Left) {
+        return;
+    }
+    let Some(entity) = dragged.take() else {
+        return;
+    };
+
+    // The pressed row is reset on release, so the row under the cursor is hovered
+    let target = rows
+        .iter()
+        .find(|(_, interaction)| **interaction == Interaction::Hovered)
+        .map(|(row, _)| row.0);
+    let parent = parents.get(entity).ok().map(ChildOf::parent);
+    let new_parent = match target {
+        // Released on the same row, this was a click
+        Some(target) if target == entity => return,
+        // An entity can't become a child of one of its descendants
+        Some(target) if parents.iter_ancestors(target).any(|ancestor| ancestor == entity) => return,
+        Some(target) => Some(target),
+        None if hierarchy_panel.cursor_over => None,
+        None => return,
+    };
+    if new_parent != parent {
+        commands.execute_editor_command_with(move |world| ReparentCommand::new(world, entity, new_parent));
+    }
+}
+
+/// Places the hierarchy panel on the side of the CameraPreview it is docked to.
+fn update_workspace_dock(
+    hierarchy_panel: Single<&HierarchyPanel, Changed<HierarchyPanel>>,
+    mut workspace: Single<&mut Node, With<Workspace>>,
+) {
+    let flex_direction = match hierarchy_panel.dock {
+        DockSide::Left => FlexDirection::Row,
+        DockSide::Right => FlexDirection::RowReverse,
+    };
+    if workspace.flex_direction != flex_direction {
+        workspace.flex_direction = flex_direction;
+    }
+}
+
+reactive_element!(HierarchyPanel, reactive_hierarchy_panel, |hierarchy_panel: &HierarchyPanel| {
+    let renaming = hierarchy_panel.renaming.clone();
+    let rows = hierarchy_panel.rows.clone().into_iter().map(move |row| {
+        let rename_text = renaming
+            .as_ref()
+            .filter(|(entity, _)| *entity == row.entity)
+            .map(|(_, name)| name.clone());
+        (
+            EditorUiElement,
+            Button,
+            HierarchyRowEntity(row.entity),
+            ClickAction(UiEvent::Hierarchy(HierarchyAction::Select(row.entity))),
+            ContextMenu::new(|menu| {
+                menu.item("&Rename", UiEvent::Hierarchy(HierarchyAction::Rename(row.entity)))
+                    .separator()
+                    .item("&Copy", UiEvent::Entity(EntityAction::Copy))
+                    .item("&Paste", UiEvent::Entity(EntityAction::Paste))
+                    .item("&Delete", UiEvent::Entity(EntityAction::Delete))
+                    .separator()
+                    .item("&Frame Selection", UiEvent::Entity(EntityAction::FrameSelection))
+            }),
+            Node {
+                display: Display::Flex,
+                flex_shrink: 0.0,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            EditorGap(Spacing::Sm),
+            EditorPadding::axes(Spacing::Sm, Spacing::Xs),
+            EditorBorderRadius::all(Radius::Sm),
+            EditorBackgroundColor(
+                if row.is_selected {
+                    EditorColor::MenuBarButtonHover
+                } else {
+                    EditorColor::Background
+                },
+                Some(EditorColor::TabHover),
+                None,
+            ),
+            children![
+                (
+                    EditorUiElement,
+                    Button,
+                    ClickAction(UiEvent::Hierarchy(HierarchyAction::ToggleExpanded(row.entity))),
+                    Node {
+                        // Indents the row by its depth in the tree
+                        margin: UiRect::left(Val::Px(row.depth as f32 * ROW_INDENT)),
+                        align_items: AlignItems::Center,
+                        justify_content: JustifyContent::Center,
+                        ..default()
+                    },
+                    EditorIconSize(IconSize::Sm),
+                    // Keeps the space of the button so the labels line up
+                    if row.has_children {
+                        Visibility::Inherited
+                    } else {
+                        Visibility::Hidden
+                    },
+                    children![(
+                        EditorUiElement,
+                        Text::new(if row.is_expanded { "v" } else { ">" }),
+                        EditorTextColor(EditorColor::FadedText, None, None),
+                        EditorFontSize(FontSize::Caption)
+                    )],
+                ),
+                (
+                    EditorUiElement,
+                    Node {
+                        display: if rename_text.is_none() { Display::Flex } else { Display::None },
+                        ..default()
+                    },
+                    Text::new(row.label.clone()),
+                    EditorTextColor(EditorColor::Text, None, None),
+                    EditorFontSize(FontSize::Body)
+                ),
+                (
+                    RenameInput(row.entity),
+                    TextInput::new(rename_text.clone().unwrap_or(row.label)),
+                    Node {
+                        display: if rename_text.is_some() { Display::Flex } else { Display::None },
+                        flex_grow: 1.0,
+                        ..default()
+                    },
+                    EditorPadding::axes(Spacing::Sm, Spacing::None),
+                ),
+            ],
+        )
+    });
+
+    (
+        EditorUiElement,
+        NodeFullscreenDisplay::new(Display::Flex, Display::None).with_slide(SlideEdge::Left).with_fade(),
+        RelativeCursorPosition::default(),
+        Node {
+            display: Display::Flex,
+            flex_direction: FlexDirection::Column,
+            flex_shrink: 0.0,
+            ..default()
+        },
+        EditorWidth(Width::HierarchyPanel),
+        EditorGap(Spacing::Sm),
+        EditorPadding::all(Spacing::Md),
+        EditorBackgroundColor(EditorColor::Background, None, None),
+        children![
+            (
+                EditorUiElement,
+                Node {
+                    display: Display::Flex,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::SpaceBetween,
+                    ..default()
+                },
+                children![
+                    (
+                        EditorUiElement,
+                        Text::new("Hierarchy"),
+                        EditorTextColor(EditorColor::Text, None, None),
+                        EditorFontSize(FontSize::Body)
+                    ),
+                    (
+                        EditorUiElement,
+                        Button,
+                        ClickAction(UiEvent::Hierarchy(HierarchyAction::ToggleDock)),
+                        EditorPadding::axes(Spacing::Sm, Spacing::Xs),
+                        EditorBorderRadius::all(Radius::Sm),
+                        EditorBackgroundColor(EditorColor::Background, Some(EditorColor::MenuBarButtonHover), None),
+                        children![(
+                            EditorUiElement,
+                            Text::new(match hierarchy_panel.dock {
+                                DockSide::Left => ">>",
+                                DockSide::Right => "<<",
+                            }),
+                            EditorTextColor(EditorColor::FadedText, None, None),
+                            EditorFontSize(FontSize::Caption)
+                        )],
+                    ),
+                ],
+            ),
+            (
+                EditorUiElement,
+                ScrollView,
+                Node {
+                    display: Display::Flex,
+                    flex_direction: FlexDirection::Column,
+                    flex_grow: 1.0,
+                    overflow: Overflow::scroll_y(),
+                    ..default()
+                },
+                EditorGap(Spacing::Xs),
+                Children::spawn(SpawnIter(rows)),
+            ),
+        ],
+    )
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::history::EditorHistory;
+
+    /// Starts renaming the selected entity and returns the value of the rename input.
+    fn start_rename(app: &mut App) -> (Entity, String) {
+        app.world_mut().write_message(UiEvent::Hierarchy(HierarchyAction::RenameSelected));
+        app.update();
+        let rename_input = app.world().resource::<TextInputFocus>().entity().unwrap();
+        (rename_input, app.world().get::<TextInput>(rename_input).unwrap().value.clone())
+    }
+
+    #[test]
+    fn renaming_starts_with_the_current_name() {
+        let mut app = App::new();
+        app.add_message::<UiEvent>()
+            .add_message::<TextInputChanged>()
+            .add_message::<TextInputSubmitted>()
+            .add_message::<TextInputCancelled>()
+            .init_resource::<TextInputFocus>()
+            .init_resource::<ButtonInput<KeyCode>>()
+            .init_resource::<Time>()
+            .insert_resource(EditorHistory::new(10))
+            .add_systems(Update, (handle_hierarchy_events, handle_rename_input, update_hierarchy_panel).chain())
+            .add_systems(PostUpdate, reactive_hierarchy_panel);
+        app.world_mut().spawn(HierarchyPanel::default());
+        let entity = app.world_mut().spawn((Transform::default(), Name::new("Cube"), Selected)).id();
+        app.update();
+
+        let (rename_input, name) = start_rename(&mut app);
+        assert_eq!(name, "Cube");
+        app.world_mut().write_message(TextInputSubmitted {
+            entity: rename_input,
+            value: "Box".to_string(),
+        });
+        app.update();
+        assert_eq!(app.world().get::<Name>(entity).unwrap().as_str(), "Box");
+        assert_eq!(start_rename(&mut app).1, "Box");
+
+        app.world_mut()
+            .resource_scope(|world, mut history: Mut<EditorHistory>| history.undo(world));
+        app.update();
+        assert_eq!(start_rename(&mut app).1, "Cube");
+    }
+}
@@ -0,0 +1,666 @@
+use std::{any::{Any, TypeId}, collections::VecDeque};
+
+use bevy::{ecs::entity_disabling::Disabled, prelude::*};
+
+use crate::ui::{ClickAction, ClickActionDisabled, UiEvent, elements::MenuBarButton};
+
+pub struct HistoryPlugin {
+    pub max_depth: usize,
+}
+
+impl Plugin for HistoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(EditorHistory::new(self.max_depth))
+            .add_systems(Update, handle_history_events)
+            .add_systems(Update, update_history_menu_items.run_if(resource_changed::<EditorHistory>));
+    }
+}
+
+/// A reversible change made in the editor.
+///
+/// Commands are applied to the world before they are added to the [`EditorHistory`], either by
+/// [`EditorHistoryCommandsExt::execute_editor_command`] or by the system that made the change itself,
+/// see [`EditorHistoryCommandsExt::record_editor_command`].
+pub trait EditorCommand: Any + Send + Sync {
+    /// Short description shown in the Edit menu, e.g. "Move".
+    fn label(&self) -> String;
+
+    /// Applies the change, used when redoing the command.
+    fn apply(&mut self, world: &mut World);
+
+    /// Reverts the change.
+    fn undo(&mut self, world: &mut World);
+
+    /// Tries to merge a command that was made right after this one into this one,
+    /// so a continuous edit like a drag can be undone in one step.
+    /// Returns false if the commands can't be merged.
+    fn merge(&mut self, _next: &dyn EditorCommand) -> bool {
+        false
+    }
+
+    /// Called when the command is dropped from the history.
+    /// `undone` is true if the command was undone at that moment.
+    fn discard(&mut self, _world: &mut World, _undone: bool) {}
+}
+
+/// Undo and redo stacks of the editor.
+#[derive(Resource)]
+pub struct EditorHistory {
+    undo_stack: VecDeque<Box<dyn EditorCommand>>,
+    redo_stack: Vec<Box<dyn EditorCommand>>,
+    /// Maximum number of commands that can be undone.
+    pub max_depth: usize,
+    /// When sealed, the next command is never merged into the last one.
+    sealed: bool,
+}
+
+impl EditorHistory {
+    pub fn new(max_depth: usize) -> Self {
+        Self {
+            undo_stack: VecDeque::new(),
+            redo_stack: Vec::new(),
+            max_depth,
+            sealed: true,
+        }
+    }
+
+    pub fn undo_label(&self) -> Option<String> {
+        self.undo_stack.back().map(|command| command.label())
+    }
+
+    pub fn redo_label(&self) -> Option<String> {
+        self.redo_stack.last().map(|command| command.label())
+    }
+
+    /// Adds a command that has already been applied to the history.
+    /// The command is merged into the last one if possible and the history is not sealed.
+    pub fn push(&mut self, world: &mut World, command: Box<dyn EditorCommand>) {
+        for mut command in self.redo_stack.drain(..) {
+            command.discard(world, true);
+        }
+
+        if !self.sealed
+            && let Some(last) = self.undo_stack.back_mut()
+            && last.merge(command.as_ref())
+        {
+            return;
+        }
+
+        self.undo_stack.push_back(command);
+        self.sealed = false;
+        while self.undo_stack.len() > self.max_depth {
+            if let Some(mut command) = self.undo_stack.pop_front() {
+                command.discard(world, false);
+            }
+        }
+    }
+
+    /// Ends the current continuous edit, so the next command gets its own undo step.
+    pub fn seal(&mut self) {
+        self.sealed = true;
+    }
+
+    /// Drops all commands, e.g. when the scene entities they refer to are despawned.
+    pub fn clear(&mut self, world: &mut World) {
+        for mut command in self.undo_stack.drain(..) {
+            command.discard(world, false);
+        }
+        for mut command in self.redo_stack.drain(..) {
+            command.discard(world, true);
+        }
+        self.sealed = true;
+    }
+
+    pub fn undo(&mut self, world: &mut World) {
+        if let Some(mut command) = self.undo_stack.pop_back() {
+            command.undo(world);
+            self.redo_stack.push(command);
+            self.sealed = true;
+        }
+    }
+
+    pub fn redo(&mut self, world: &mut World) {
+        if let Some(mut command) = self.redo_stack.pop() {
+            command.apply(world);
+            self.undo_stack.push_back(command);
+            self.sealed = true;
+        }
+    }
+}
+
+pub trait EditorHistoryCommandsExt {
+    /// Applies the command and adds it to the history.
+    #[allow(dead_code)]
+    fn execute_editor_command(&mut self, command: impl EditorCommand);
+
+    /// Builds a command from the current state of the world, then applies it and adds it to the history.
+    fn execute_editor_command_with<C: EditorCommand>(&mut self, build: impl FnOnce(&World) -> C + Send + 'static);
+
+    /// Adds a command that has already been applied to the history.
+    fn record_editor_command(&mut self, command: impl EditorCommand);
+
+    /// Ends the current continuous edit, see [`EditorHistory::seal`].
+    fn seal_editor_history(&mut self);
+}
+
+impl EditorHistoryCommandsExt for Commands<'_, '_> {
+    fn execute_editor_command(&mut self, mut command: impl EditorCommand) {
+        self.queue(move |world: &mut World| {
+            command.apply(world);
+            world.resource_scope(|world, mut history: Mut<EditorHistory>| {
+                history.push(world, Box::new(command));
+            });
+        });
+    }
+
+    fn execute_editor_command_with<C: EditorCommand>(&mut self, build: impl FnOnce(&World) -> C + Send + 'static) {
+        self.queue(move |world: &mut World| {
+            let mut command = build(world);
+            command.apply(world);
+            world.resource_scope(|world, mut history: Mut<EditorHistory>| {
+                history.push(world, Box::new(command));
+            });
+        });
+    }
+
+    fn record_editor_command(&mut self, command: impl EditorCommand) {
+        self.queue(move |world: &mut World| {
+            world.resource_scope(|world, mut history: Mut<EditorHistory>| {
+                history.push(world, Box::new(command));
+            });
+        });
+    }
+
+    fn seal_editor_history(&mut self) {
+        self.queue(|world: &mut World| {
+            world.resource_mut::<EditorHistory>().seal();
+        });
+    }
+}
+
+fn handle_history_events(
+    mut commands: Commands,
+    mut ui_event_reader: MessageReader<UiEvent>,
+) {
+    for event in ui_event_reader.read() {
+        match event {
+            UiEvent::Undo => commands.queue(|world: &mut World| {
+                world.resource_scope(|world, mut history: Mut<EditorHistory>| history.undo(world));
+            }),
+            UiEvent::Redo => commands.queue(|world: &mut World| {
+                world.resource_scope(|world, mut history: Mut<EditorHistory>| history.redo(world));
+            }),
+            _ => {}
+        }
+    }
+}
+
+/// Greys out the Undo and Redo menu items when there is nothing to undo or redo,
+/// and shows what will be undone or redone.
+fn update_history_menu_items(
+    mut commands: Commands,
+    history: Res<EditorHistory>,
+    mut menu_bar_buttons: Query<(Entity, &mut MenuBarButton, &ClickAction)>,
+) {
+    for (entity, mut menu_bar_button, click_action) in menu_bar_buttons.iter_mut() {
+        let (text, label) = match click_action.0 {
+            UiEvent::Undo => ("Undo", history.undo_label()),
+            UiEvent::Redo => ("Redo", history.redo_label()),
+            _ => continue,
+        };
+        let text = match &label {
+            Some(label) => format!("{} {}", text, label),
+            None => text.to_string(),
+        };
+        let is_enabled = label.is_some();
+        if menu_bar_button.text != text || menu_bar_button.is_enabled != is_enabled {
+            menu_bar_button.text = text;
+            menu_bar_button.is_enabled = is_enabled;
+        }
+        if is_enabled {
+            commands.entity(entity).remove::<ClickActionDisabled>();
+        } else {
+            commands.entity(entity).insert(ClickActionDisabled);
+        }
+    }
+}
+
+/// Change of the transforms of one or more entities, e.g. by dragging the transform gizmo.
+pub struct TransformCommand {
+    pub label: String,
+    pub changes: Vec<TransformChange>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TransformChange {
+    pub entity: Entity,
+    pub before: Transform,
+    pub after: Transform,
+}
+
+impl EditorCommand for TransformCommand {
+    fn label(&self) -> String {
+        self.label.clone()
+    }
+
+    fn apply(&mut self, world: &mut World) {
+        for change in self.changes.iter() {
+            if let Some(mut transform) = world.get_mut::<Transform>(change.entity) {
+                *transform = change.after;
+            }
+        }
+    }
+
+    fn undo(&mut self, world: &mut World) {
+        for change in self.changes.iter() {
+            if let Some(mut transform) = world.get_mut::<Transform>(change.entity) {
+                *transform = change.before;
+            }
+        }
+    }
+
+    fn merge(&mut self, next: &dyn EditorCommand) -> bool {
+        let Some(next) = (next as &dyn Any).downcast_ref::<TransformCommand>() else {
+            return false;
+        };
+        let same_entities = self.changes.len() == next.changes.len()
+            && self.changes.iter().zip(next.changes.iter()).all(|(a, b)| a.entity == b.entity);
+        if !same_entities || self.label != next.label {
+            return false;
+        }
+        for (change, next_change) in self.changes.iter_mut().zip(next.changes.iter()) {
+            change.after = next_change.after;
+        }
+        true
+    }
+}
+
+/// Inserts, replaces or removes a reflected component.
+pub struct ComponentCommand {
+    pub entity: Entity,
+    type_id: TypeId,
+    type_name: String,
+    before: Option<Box<dyn Reflect>>,
+    after: Option<Box<dyn Reflect>>,
+}
+
+impl ComponentCommand {
+    /// Changes a component from `before` to `after`, where None means the component is not present.
+    /// The component type has to be registered with `#[reflect(Component)]`.
+    pub fn new(
+        entity: Entity,
+        type_id: TypeId,
+        type_name: String,
+        before: Option<Box<dyn Reflect>>,
+        after: Option<Box<dyn Reflect>>,
+    ) -> Self {
+        Self { entity, type_id, type_name, before, after }
+    }
+
+    /// Removes the component with the given type path from the entity.
+    /// Returns None if the entity doesn't have the component or it can't be cloned with reflection.
+    pub fn remove(world: &World, entity: Entity, type_path: &str) -> Option<Self> {
+        let type_registry = world.resource::<AppTypeRegistry>().read();
+        let registration = type_registry.get_with_type_path(type_path)?;
+        let entity_ref = world.get_entity(entity).ok()?;
+        let before = registration.data::<ReflectComponent>()?.reflect(entity_ref)?.reflect_clone().ok()?;
+        let type_name = registration.type_info().type_path_table().short_path().to_string();
+        Some(Self::new(entity, registration.type_id(), type_name, Some(before), None))
+    }
+
+    fn set(&self, world: &mut World, value: Option<&dyn Reflect>) {
+        let type_registry = world.resource::<AppTypeRegistry>().clone();
+        let type_registry = type_registry.read();
+        let Some(reflect_component) = type_registry.get_type_data::<ReflectComponent>(self.type_id) else {
+            warn!("Can't change component {}, because it is not registered as a reflected component", self.type_name);
+            return;
+        };
+        let Ok(mut entity) = world.get_entity_mut(self.entity) else {
+            return;
+        };
+        match value {
+            Some(value) => reflect_component.insert(&mut entity, value.as_partial_reflect(), &type_registry),
+            None => reflect_component.remove(&mut entity),
+        }
+    }
+}
+
+impl EditorCommand for ComponentCommand {
+    fn label(&self) -> String {
+        match (&self.before, &self.after) {
+            (None, _) => format!("Add {}", self.type_name),
+            (_, None) => format!("Remove {}", self.type_name),
+            _ => format!("Edit {}", self.type_name),
+        }
+    }
+
+    fn apply(&mut self, world: &mut World) {
+        self.set(world, self.after.as_deref());
+    }
+
+    fn undo(&mut self, world: &mut World) {
+        self.set(world, self.before.as_deref());
+    }
+
+    fn merge(&mut self, next: &dyn EditorCommand) -> bool {
+        let Some(next) = (next as &dyn Any).downcast_ref::<ComponentCommand>() else {
+            return false;
+        };
+        if next.entity != self.entity || next.type_id != self.type_id || self.after.is_none() {
+            return false;
+        }
+        match next.after.as_ref().map(|after| after.reflect_clone()) {
+            Some(Ok(after)) => {
+                self.after = Some(after);
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Changes the Name of an entity.
+pub struct RenameCommand {
+    pub entity: Entity,
+    before: Option<Name>,
+    after: Name,
+}
+
+impl RenameCommand {
+    pub fn new(world: &World, entity: Entity, name: impl Into<String>) -> Self {
+        Self {
+            entity,
+            before: world.get::<Name>(entity).cloned(),
+            after: Name::new(name.into()),
+        }
+    }
+}
+
+impl EditorCommand for RenameCommand {
+    fn label(&self) -> String {
+        "Rename".to_string()
+    }
+
+    fn apply(&mut self, world: &mut World) {
+        if let Ok(mut entity) = world.get_entity_mut(self.entity) {
+            entity.insert(self.after.clone());
+        }
+    }
+
+    fn undo(&mut self, world: &mut World) {
+        if let Ok(mut entity) = world.get_entity_mut(self.entity) {
+            match &self.before {
+                Some(before) => entity.insert(before.clone()),
+                None => entity.remove::<Name>(),
+            };
+        }
+    }
+}
+
+/// Moves an entity to another parent, or makes it a root entity when the parent is None.
+/// The local transform is changed so the entity stays in the same place in the world.
+pub struct ReparentCommand {
+    pub entity: Entity,
+    before: (Option<Entity>, Transform),
+    after: (Option<Entity>, Transform),
+}
+
+impl ReparentCommand {
+    pub fn new(world: &World, entity: Entity, parent: Option<Entity>) -> Self {
+        let transform = world.get::<Transform>(entity).copied().unwrap_or_default();
+        let global_transform = world.get::<GlobalTransform>(entity).copied().unwrap_or_default();
+        let parent_global_transform = parent
+            .and_then(|parent| world.get::<GlobalTransform>(parent))
+            .copied()
+            .unwrap_or_default();
+        Self {
+            entity,
+            before: (world.get::<ChildOf>(entity).map(ChildOf::parent), transform),
+            after: (parent, global_transform.reparented_to(&parent_global_transform)),
+        }
+    }
+
+    fn set(&self, world: &mut World, (parent, transform): (Option<Entity>, Transform)) {
+        let Ok(mut entity) = world.get_entity_mut(self.entity) else {
+            return;
+        };
+        match parent {
+            Some(parent) => entity.insert((ChildOf(parent), transform)),
+            None => entity.remove::<ChildOf>().insert(transform),
+        };
+    }
+}
+
+impl EditorCommand for ReparentCommand {
+    fn label(&self) -> String {
+        "Reparent".to_string()
+    }
+
+    fn apply(&mut self, world: &mut World) {
+        self.set(world, self.after);
+    }
+
+    fn undo(&mut self, world: &mut World) {
+        self.set(world, self.before);
+    }
+}
+
+/// Spawning of entities. Undoing it disables the entities and their descendants instead of despawning them,
+/// so the entities keep their ids and later commands that refer to them still work after redoing.
+pub struct SpawnCommand {
+    pub entities: Vec<Entity>,
+}
+
+impl EditorCommand for SpawnCommand {
+    fn label(&self) -> String {
+        "Spawn".to_string()
+    }
+
+    fn apply(&mut self, world: &mut World) {
+        for entity in self.entities.iter() {
+            set_disabled(world, *entity, false);
+        }
+    }
+
+    fn undo(&mut self, world: &mut World) {
+        for entity in self.entities.iter() {
+            set_disabled(world, *entity, true);
+        }
+    }
+
+    fn discard(&mut self, world: &mut World, undone: bool) {
+        if undone {
+            for entity in self.entities.iter() {
+                world.try_despawn(*entity).ok();
+            }
+        }
+    }
+}
+
+/// Despawning of entities. The entities and their descendants are disabled instead of despawned
+/// until the command is dropped from the history, so they can be restored with the same ids.
+pub struct DespawnCommand {
+    pub entities: Vec<Entity>,
+}
+
+impl EditorCommand for DespawnCommand {
+    fn label(&self) -> String {
+        "Delete".to_string()
+    }
+
+    fn apply(&mut self, world: &mut World) {
+        for entity in self.entities.iter() {
+            set_disabled(world, *entity, true);
+        }
+    }
+
+    fn undo(&mut self, world: &mut World) {
+        for entity in self.entities.iter() {
+            set_disabled(world, *entity, false);
+        }
+    }
+
+    fn discard(&mut self, world: &mut World, undone: bool) {
+        if !undone {
+            for entity in self.entities.iter() {
+                world.try_despawn(*entity).ok();
+            }
+        }
+    }
+}
+
+/// Disables or enables an entity and all of its descendants.
+fn set_disabled(world: &mut World, entity: Entity, disabled: bool) {
+    let children = world
+        .get::<Children>(entity)
+        .map(|children| children.to_vec())
+        .unwrap_or_default();
+    for child in children {
+        set_disabled(world, child, disabled);
+    }
+    if let Ok(mut entity) = world.get_entity_mut(entity) {
+        if disabled {
+            entity.insert(Disabled);
+        } else {
+            entity.remove::<Disabled>();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Adds a number to the [`Log`] when applied, and removes it again when undone.
+    struct LogCommand(u32);
+
+    #[derive(Resource, Default)]
+    struct Log {
+        numbers: Vec<u32>,
+        discarded: Vec<(u32, bool)>,
+    }
+
+    impl EditorCommand for LogCommand {
+        fn label(&self) -> String {
+            self.0.to_string()
+        }
+
+        fn apply(&mut self, world: &mut World) {
+            world.resource_mut::<Log>().numbers.push(self.0);
+        }
+
+        fn undo(&mut self, world: &mut World) {
+            let mut log = world.resource_mut::<Log>();
+            assert_eq!(log.numbers.pop(), Some(self.0), "commands are undone in reverse order");
+        }
+
+        fn discard(&mut self, world: &mut World, undone: bool) {
+            world.resource_mut::<Log>().discarded.push((self.0, undone));
+        }
+    }
+
+    fn execute(world: &mut World, history: &mut EditorHistory, mut command: impl EditorCommand) {
+        command.apply(world);
+        history.push(world, Box::new(command));
+    }
+
+    fn move_command(entity: Entity, from: f32, to: f32) -> TransformCommand {
+        TransformCommand {
+            label: "Move".to_string(),
+            changes: vec![TransformChange {
+                entity,
+                before: Transform::from_xyz(from, 0.0, 0.0),
+                after: Transform::from_xyz(to, 0.0, 0.0),
+            }],
+        }
+    }
+
+    #[test]
+    fn undo_and_redo_in_order() {
+        let mut world = World::new();
+        world.init_resource::<Log>();
+        let mut history = EditorHistory::new(10);
+        for number in 1..=3 {
+            execute(&mut world, &mut history, LogCommand(number));
+            history.seal();
+        }
+
+        history.undo(&mut world);
+        history.undo(&mut world);
+        assert_eq!(world.resource::<Log>().numbers, [1]);
+        assert_eq!(history.undo_label().as_deref(), Some("1"));
+        assert_eq!(history.redo_label().as_deref(), Some("2"));
+
+        history.redo(&mut world);
+        assert_eq!(world.resource::<Log>().numbers, [1, 2]);
+
+        // A new command drops the commands that can be redone
+        execute(&mut world, &mut history, LogCommand(4));
+        assert_eq!(world.resource::<Log>().discarded, [(3, true)]);
+        assert_eq!(history.redo_label(), None);
+        history.redo(&mut world);
+        assert_eq!(world.resource::<Log>().numbers, [1, 2, 4]);
+    }
+
+    #[test]
+    fn depth_limit_drops_the_oldest_commands() {
+        let mut world = World::new();
+        world.init_resource::<Log>();
+        let mut history = EditorHistory::new(2);
+        for number in 1..=3 {
+            execute(&mut world, &mut history, LogCommand(number));
+        }
+
+        assert_eq!(world.resource::<Log>().discarded, [(1, false)]);
+        history.undo(&mut world);
+        history.undo(&mut world);
+        history.undo(&mut world);
+        assert_eq!(world.resource::<Log>().numbers, [1]);
+    }
+
+    #[test]
+    fn continuous_edits_merge_until_sealed() {
+        let mut world = World::new();
+        let entity = world.spawn(Transform::default()).id();
+        let mut history = EditorHistory::new(10);
+        execute(&mut world, &mut history, move_command(entity, 0.0, 1.0));
+        execute(&mut world, &mut history, move_command(entity, 1.0, 2.0));
+        history.seal();
+        execute(&mut world, &mut history, move_command(entity, 2.0, 3.0));
+        assert_eq!(history.undo_stack.len(), 2);
+
+        history.undo(&mut world);
+        assert_eq!(world.get::<Transform>(entity).unwrap().translation.x, 2.0);
+        history.undo(&mut world);
+        assert_eq!(world.get::<Transform>(entity).unwrap().translation.x, 0.0);
+        history.redo(&mut world);
+        assert_eq!(world.get::<Transform>(entity).unwrap().translation.x, 2.0);
+    }
+
+    #[test]
+    fn commands_of_other_entities_do_not_merge() {
+        let mut world = World::new();
+        let first = world.spawn(Transform::default()).id();
+        let second = world.spawn(Transform::default()).id();
+        let mut history = EditorHistory::new(10);
+        execute(&mut world, &mut history, move_command(first, 0.0, 1.0));
+        execute(&mut world, &mut history, move_command(second, 0.0, 1.0));
+        assert_eq!(history.undo_stack.len(), 2);
+    }
+
+    #[test]
+    fn removed_components_are_restored_on_undo() {
+        let mut world = World::new();
+        world.init_resource::<AppTypeRegistry>();
+        world.resource::<AppTypeRegistry>().write().register::<Name>();
+        let entity = world.spawn(Name::new("Cube")).id();
+        let mut history = EditorHistory::new(10);
+        let command = ComponentCommand::remove(&world, entity, Name::type_path()).unwrap();
+        assert_eq!(command.label(), "Remove Name");
+        execute(&mut world, &mut history, command);
+        assert!(world.get::<Name>(entity).is_none());
+
+        history.undo(&mut world);
+        assert_eq!(world.get::<Name>(entity).map(Name::as_str), Some("Cube"));
+    }
+}
@@ -0,0 +1,1181 @@
+use std::any::TypeId;
+
+use bevy::{
+    asset::{LoadedUntypedAsset, ReflectHandle},
+    ecs::{archetype::ArchetypeId, system::SystemChangeTick},
+    platform::collections::HashSet,
+    prelude::*,
+    reflect::{DynamicEnum, DynamicVariant, GetPath, ReflectRef, TypeInfo, TypeRegistry, VariantInfo},
+};
+
+use crate::ui::{
+    ClickAction, EditorUiElement, UiEvent,
+    colors::{EditorBackgroundColor, EditorColor, EditorTextColor},
+    elements::{ScrollView, reactive_element},
+    fullscreen::{NodeFullscreenDisplay, SlideEdge},
+    history::{ComponentCommand, EditorCommand, EditorHistory},
+    menu::ContextMenu,
+    reactive::Reconcile,
+    selection::Selected,
+    shortcuts::ShortcutScope,
+    style::{
+        EditorBorderRadius, EditorFontSize, EditorGap, EditorMargin, EditorPadding, EditorWidth, FontSize, Radius, Spacing,
+        Width,
+    },
+    text_input::{TextInput, TextInputCancelled, TextInputChanged, TextInputFocus, TextInputSubmitted, update_text_input_focus},
+};
+
+/// Nested values deeper than this are not shown.
+const MAX_DEPTH: usize = 8;
+
+/// Lists with more items only show the first items.
+const MAX_LIST_ITEMS: usize = 32;
+
+/// Read-only values are cut off after this many characters.
+const MAX_TEXT_LENGTH: usize = 48;
+
+/// Indentation of the fields per level of nesting, in pixels.
+const FIELD_INDENT: f32 = 12.0;
+
+pub struct InspectorPlugin;
+
+impl Plugin for InspectorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PendingHandleEdits>()
+            .register_type::<InspectorValueInput>()
+            .add_systems(Update, handle_inspector_events.after(update_text_input_focus))
+            .add_systems(Update, handle_inspector_input.after(handle_inspector_events))
+            .add_systems(Update, apply_pending_handle_edits)
+            .add_systems(
+                Update,
+                update_inspector_panel
+                    .after(handle_inspector_events)
+                    .after(handle_inspector_input)
+                    .run_if(inspector_panel_outdated),
+            )
+            .add_systems(PostUpdate, reactive_inspector_panel);
+    }
+}
+
+/// Actions of the rows and values in the inspector panel.
+#[derive(Reflect, Hash, Eq, PartialEq, Clone, Debug)]
+pub enum InspectorAction {
+    /// Clicks a row or a value. What happens depends on the kind of the field, e.g. bools are toggled
+    /// and numbers start editing. `axis` is the Euler angle of a rotation.
+    Click {
+        component: String,
+        path: String,
+        axis: Option<u8>,
+    },
+    /// Removes the component from the inspected entity, from the context menu of its rows.
+    RemoveComponent { component: String },
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FieldKind {
+    /// Header of a component, clicking it collapses the component.
+    Component,
+    /// Struct or list with its fields in the rows below.
+    Group,
+    Number,
+    Text,
+    Bool,
+    /// Enum whose variant can be changed by clicking it, which is possible if all variants are unit variants.
+    Enum { can_cycle: bool },
+    /// Asset path of a Handle.
+    Handle,
+    ReadOnly,
+}
+
+/// A value box in a row of the inspector. Vectors and rotations have a box per axis.
+#[derive(Clone, PartialEq, Debug)]
+pub struct FieldValue {
+    /// Reflect path of the value inside the component.
+    pub path: String,
+    pub axis: Option<u8>,
+    pub text: String,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct InspectorField {
+    /// Type path of the component.
+    pub component: String,
+    /// Reflect path of the field inside the component, empty for the component itself.
+    pub path: String,
+    pub label: String,
+    pub depth: usize,
+    pub kind: FieldKind,
+    pub values: Vec<FieldValue>,
+}
+
+/// The value that is being edited and the text typed so far.
+#[derive(Clone, PartialEq, Debug)]
+pub struct InspectorEdit {
+    pub component: String,
+    pub path: String,
+    pub axis: Option<u8>,
+    pub kind: FieldKind,
+    pub text: String,
+}
+
+/// Panel that shows the reflected components of the selected entity and lets them be edited.
+#[derive(Component, Clone, PartialEq, Default)]
+#[require(ShortcutScope::Inspector)]
+pub struct InspectorPanel {
+    pub entity: Option<Entity>,
+    pub title: String,
+    pub fields: Vec<InspectorField>,
+    /// Type paths of the components whose fields are hidden.
+    pub collapsed: HashSet<String>,
+    pub editing: Option<InspectorEdit>,
+}
+
+/// The text input of a value box, which is shown while the value is edited.
+#[derive(Component, Reflect, Clone)]
+#[reflect(Component)]
+pub struct InspectorValueInput {
+    pub component: String,
+    pub path: String,
+    pub axis: Option<u8>,
+}
+
+/// Handles that were changed to an asset path that is still loading.
+/// The change is applied when the asset has loaded, so the handle gets the right asset type.
+#[derive(Resource, Default)]
+pub struct PendingHandleEdits(Vec<PendingHandleEdit>);
+
+pub struct PendingHandleEdit {
+    entity: Entity,
+    component: String,
+    path: String,
+    handle: Handle<LoadedUntypedAsset>,
+}
+
+struct FieldContext<'a> {
+    component: &'a str,
+    registry: &'a TypeRegistry,
+    asset_server: Option<&'a AssetServer>,
+}
+
+fn format_float(value: f64) -> String {
+    let text = format!("{value:.3}");
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    if text == "-0" { "0".to_string() } else { text.to_string() }
+}
+
+fn truncate(text: String) -> String {
+    match text.char_indices().nth(MAX_TEXT_LENGTH) {
+        Some((index, _)) => format!("{}...", &text[..index]),
+        None => text,
+    }
+}
+
+macro_rules! integer_types {
+    ($macro:ident, $($args:tt)*) => {
+        $macro!($($args)*, i8, i16, i32, i64, isize, u8, u16, u32, u64, usize)
+    };
+}
+
+macro_rules! integer_text {
+    ($value:expr, $($ty:ty),*) => {
+        $(
+            if let Some(number) = $value.try_downcast_ref::<$ty>() {
+                return Some(number.to_string());
+            }
+        )*
+    };
+}
+
+macro_rules! set_integer {
+    ($value:expr, $text:expr, $($ty:ty),*) => {
+        $(
+            if let Some(number) = $value.try_downcast_mut::<$ty>() {
+                return match $text.trim().parse::<$ty>() {
+                    Ok(parsed) => {
+                        *number = parsed;
+                        Ok(())
+                    }
+                    Err(_) => Err(format!("{} is not a valid {}", $text, stringify!($ty))),
+                };
+            }
+        )*
+    };
+}
+
+fn is_number(value: &dyn PartialReflect) -> bool {
+    let Some(type_info) = value.get_represented_type_info() else {
+        return false;
+    };
+    [
+        TypeId::of::<f32>(),
+        TypeId::of::<f64>(),
+        TypeId::of::<i8>(),
+        TypeId::of::<i16>(),
+        TypeId::of::<i32>(),
+        TypeId::of::<i64>(),
+        TypeId::of::<isize>(),
+        TypeId::of::<u8>(),
+        TypeId::of::<u16>(),
+        TypeId::of::<u32>(),
+        TypeId::of::<u64>(),
+        TypeId::of::<usize>(),
+    ]
+    .contains(&type_info.type_id())
+}
+
+fn is_valid_number(text: &str) -> bool {
+    text.trim().parse::<f64>().is_ok()
+}
+
+/// Returns the text of values that are edited as text: numbers, strings and colors.
+fn value_text(value: &dyn PartialReflect) -> Option<String> {
+    if let Some(number) = value.try_downcast_ref::<f32>() {
+        return Some(format_float(*number as f64));
+    }
+    if let Some(number) = value.try_downcast_ref::<f64>() {
+        return Some(format_float(*number));
+    }
+    integer_types!(integer_text, value);
+    if let Some(text) = value.try_downcast_ref::<String>() {
+        return Some(text.clone());
+    }
+    if let Some(name) = value.try_downcast_ref::<Name>() {
+        return Some(name.to_string());
+    }
+    if let Some(color) = value.try_downcast_ref::<Color>() {
+        return Some(Srgba::from(*color).to_hex());
+    }
+    None
+}
+
+/// Sets a value that is edited as text from the typed text.
+fn set_value_text(value: &mut dyn PartialReflect, axis: Option<u8>, text: &str) -> Result<(), String> {
+    if let Some(rotation) = value.try_downcast_mut::<Quat>()
+        && let Some(axis) = axis
+    {
+        let degrees: f32 = text.trim().parse().map_err(|_| format!("{text} is not a number"))?;
+        let (x, y, z) = rotation.to_euler(EulerRot::XYZ);
+        let mut angles = [x, y, z];
+        angles[axis as usize] = degrees.to_radians();
+        *rotation = Quat::from_euler(EulerRot::XYZ, angles[0], angles[1], angles[2]);
+        return Ok(());
+    }
+    if let Some(number) = value.try_downcast_mut::<f32>() {
+        *number = text.trim().parse().map_err(|_| format!("{text} is not a number"))?;
+        return Ok(());
+    }
+    if let Some(number) = value.try_downcast_mut::<f64>() {
+        *number = text.trim().parse().map_err(|_| format!("{text} is not a number"))?;
+        return Ok(());
+    }
+    integer_types!(set_integer, value, text);
+    if let Some(string) = value.try_downcast_mut::<String>() {
+        *string = text.to_string();
+        return Ok(());
+    }
+    if let Some(name) = value.try_downcast_mut::<Name>() {
+        name.set(text.to_string());
+        return Ok(());
+    }
+    if let Some(color) = value.try_downcast_mut::<Color>() {
+        *color = Srgba::hex(text.trim()).map_err(|_| format!("{text} is not a hex color"))?.into();
+        return Ok(());
+    }
+    Err(format!("{} can't be edited", value.reflect_short_type_path()))
+}
+
+/// Returns the names of the variants of an enum if they are all unit variants.
+fn unit_variant_names(value: &dyn PartialReflect) -> Option<Vec<&'static str>> {
+    let Some(TypeInfo::Enum(enum_info)) = value.get_represented_type_info() else {
+        return None;
+    };
+    enum_info
+        .iter()
+        .map(|variant| match variant {
+            VariantInfo::Unit(unit) => Some(unit.name()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Changes an enum to its next variant, wrapping around to the first one.
+fn cycle_variant(value: &mut dyn PartialReflect) -> bool {
+    let ReflectRef::Enum(enum_value) = value.reflect_ref() else {
+        return false;
+    };
+    let Some(variants) = unit_variant_names(value) else {
+        return false;
+    };
+    let index = enum_value.variant_index();
+    let next = variants[(index + 1) % variants.len()];
+    value.try_apply(&DynamicEnum::new(next, DynamicVariant::Unit)).is_ok()
+}
+
+/// Returns the asset handle inside a reflected `Handle<T>`.
+fn untyped_handle(value: &dyn PartialReflect, registry: &TypeRegistry) -> Option<UntypedHandle> {
+    let type_id = value.get_represented_type_info()?.type_id();
+    let reflect_handle = registry.get_type_data::<ReflectHandle>(type_id)?;
+    reflect_handle.downcast_handle_untyped(value.try_as_reflect()?.as_any())
+}
+
+fn leaf_field(context: &FieldContext, path: &str, label: &str, depth: usize, kind: FieldKind, text: String) -> InspectorField {
+    InspectorField {
+        component: context.component.to_string(),
+        path: path.to_string(),
+        label: label.to_string(),
+        depth,
+        kind,
+        values: vec![FieldValue {
+            path: path.to_string(),
+            axis: None,
+            text,
+        }],
+    }
+}
+
+/// Adds the rows for a value and its fields.
+fn add_value(
+    fields: &mut Vec<InspectorField>,
+    context: &FieldContext,
+    value: &dyn PartialReflect,
+    path: &str,
+    label: &str,
+    depth: usize,
+) {
+    let vector = if let Some(vector) = value.try_downcast_ref::<Vec2>() {
+        Some(vector.to_array().to_vec())
+    } else if let Some(vector) = value.try_downcast_ref::<Vec3>() {
+        Some(vector.to_array().to_vec())
+    } else if let Some(vector) = value.try_downcast_ref::<Vec3A>() {
+        Some(vector.to_array().to_vec())
+    } else {
+        value.try_downcast_ref::<Vec4>().map(|vector| vector.to_array().to_vec())
+    };
+    if let Some(vector) = vector {
+        fields.push(InspectorField {
+            component: context.component.to_string(),
+            path: path.to_string(),
+            label: label.to_string(),
+            depth,
+            kind: FieldKind::Number,
+            values: vector
+                .iter()
+                .zip(["x", "y", "z", "w"])
+                .map(|(component, axis_name)| FieldValue {
+                    path: format!("{path}.{axis_name}"),
+                    axis: None,
+                    text: format_float(*component as f64),
+                })
+                .collect(),
+        });
+        return;
+    }
+    if let Some(rotation) = value.try_downcast_ref::<Quat>() {
+        let (x, y, z) = rotation.to_euler(EulerRot::XYZ);
+        fields.push(InspectorField {
+            component: context.component.to_string(),
+            path: path.to_string(),
+            label: format!("{label} (deg)"),
+            depth,
+            kind: FieldKind::Number,
+            values: [x, y, z]
+                .iter()
+                .enumerate()
+                .map(|(axis, angle)| FieldValue {
+                    path: path.to_string(),
+                    axis: Some(axis as u8),
+                    text: format_float(angle.to_degrees() as f64),
+                })
+                .collect(),
+        });
+        return;
+    }
+    if let Some(text) = value_text(value) {
+        let kind = if is_number(value) { FieldKind::Number } else { FieldKind::Text };
+        fields.push(leaf_field(context, path, label, depth, kind, text));
+        return;
+    }
+    if let Some(boolean) = value.try_downcast_ref::<bool>() {
+        fields.push(leaf_field(context, path, label, depth, FieldKind::Bool, boolean.to_string()));
+        return;
+    }
+    if let Some(handle) = untyped_handle(value, context.registry) {
+        let text = context
+            .asset_server
+            .and_then(|asset_server| asset_server.get_path(handle.id()))
+            .map(|asset_path| asset_path.to_string())
+            .unwrap_or_default();
+        fields.push(leaf_field(context, path, label, depth, FieldKind::Handle, text));
+        return;
+    }
+    if depth > MAX_DEPTH {
+        fields.push(leaf_field(context, path, label, depth, FieldKind::ReadOnly, "...".to_string()));
+        return;
+    }
+
+    match value.reflect_ref() {
+        ReflectRef::Enum(enum_value) => {
+            let can_cycle = unit_variant_names(value).is_some_and(|variants| variants.len() > 1);
+            fields.push(leaf_field(
+                context,
+                path,
+                label,
+                depth,
+                FieldKind::Enum { can_cycle },
+                enum_value.variant_name().to_string(),
+            ));
+            add_fields(fields, context, value, path, depth + 1);
+        }
+        // Newtypes are shown as the value they wrap
+        ReflectRef::TupleStruct(tuple_struct) if tuple_struct.field_len() == 1 => {
+            if let Some(field) = tuple_struct.field(0) {
+                add_value(fields, context, field, &format!("{path}.0"), label, depth);
+            }
+        }
+        ReflectRef::Struct(_)
+        | ReflectRef::TupleStruct(_)
+        | ReflectRef::Tuple(_)
+        | ReflectRef::List(_)
+        | ReflectRef::Array(_) => {
+            fields.push(InspectorField {
+                component: context.component.to_string(),
+                path: path.to_string(),
+                label: label.to_string(),
+                depth,
+                kind: FieldKind::Group,
+                values: Vec::new(),
+            });
+            add_fields(fields, context, value, path, depth + 1);
+        }
+        ReflectRef::Map(map) => {
+            fields.push(leaf_field(context, path, label, depth, FieldKind::ReadOnly, format!("{} entries", map.len())));
+        }
+        ReflectRef::Set(set) => {
+            fields.push(leaf_field(context, path, label, depth, FieldKind::ReadOnly, format!("{} entries", set.len())));
+        }
+        _ => {
+            fields.push(leaf_field(context, path, label, depth, FieldKind::ReadOnly, truncate(format!("{value:?}"))));
+        }
+    }
+}
+
+/// Adds the rows for the fields, items or enum variant fields of a value.
+fn add_fields(fields: &mut Vec<InspectorField>, context: &FieldContext, value: &dyn PartialReflect, path: &str, depth: usize) {
+    match value.reflect_ref() {
+        ReflectRef::Struct(struct_value) => {
+            for (index, field) in struct_value.iter_fields().enumerate() {
+                let name = struct_value.name_at(index).unwrap_or_default();
+                add_value(fields, context, field, &format!("{path}.{name}"), name, depth);
+            }
+        }
+        ReflectRef::TupleStruct(tuple_struct) => {
+            for (index, field) in tuple_struct.iter_fields().enumerate() {
+                add_value(fields, context, field, &format!("{path}.{index}"), &index.to_string(), depth);
+            }
+        }
+        ReflectRef::Tuple(tuple) => {
+            for (index, field) in tuple.iter_fields().enumerate() {
+                add_value(fields, context, field, &format!("{path}.{index}"), &index.to_string(), depth);
+            }
+        }
+        ReflectRef::List(list) => {
+            for (index, item) in list.iter().enumerate().take(MAX_LIST_ITEMS) {
+                add_value(fields, context, item, &format!("{path}[{index}]"), &format!("[{index}]"), depth);
+            }
+            if list.len() > MAX_LIST_ITEMS {
+                let more = format!("{} more", list.len() - MAX_LIST_ITEMS);
+                fields.push(leaf_field(context, path, "...", depth, FieldKind::ReadOnly, more));
+            }
+        }
+        ReflectRef::Array(array) => {
+            for (index, item) in array.iter().enumerate().take(MAX_LIST_ITEMS) {
+                add_value(fields, context, item, &format!("{path}[{index}]"), &format!("[{index}]"), depth);
+            }
+        }
+        ReflectRef::Enum(enum_value) => {
+            for (index, field) in enum_value.iter_fields().enumerate() {
+                let name = field.name().map(str::to_string).unwrap_or_else(|| index.to_string());
+                let field_path = match field.name() {
+                    Some(name) => format!("{path}.{name}"),
+                    None => format!("{path}.{index}"),
+                };
+                add_value(fields, context, field.value(), &field_path, &name, depth);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Returns true for the components that Bevy computes from other components, which are not shown.
+fn is_computed_component(type_id: TypeId) -> bool {
+    [
+        TypeId::of::<GlobalTransform>(),
+        TypeId::of::<TransformTreeChanged>(),
+        TypeId::of::<InheritedVisibility>(),
+        TypeId::of::<ViewVisibility>(),
+    ]
+    .contains(&type_id)
+}
+
+/// Returns true when the selection, the inspector panel or the components of the inspected entity changed
+/// since the last frame, so the inspector panel isn't rebuilt from the whole entity every frame.
+fn inspector_panel_outdated(
+    world: &World,
+    system_change_tick: SystemChangeTick,
+    selected: Query<(Entity, Ref<Selected>)>,
+    mut removed_selections: RemovedComponents<Selected>,
+    inspector_panel: Query<Ref<InspectorPanel>>,
+    mut inspected_archetype: Local<Option<(Entity, ArchetypeId)>>,
+) -> bool {
+    // The removals are always read, so they aren't seen again in a later frame
+    let is_selection_changed =
+        removed_selections.read().count() > 0 || selected.iter().any(|(_, selected)| selected.is_added());
+    let is_panel_changed = inspector_panel.iter().any(|inspector_panel| inspector_panel.is_changed());
+
+    let Ok((entity, _)) = selected.single() else {
+        *inspected_archetype = None;
+        return is_selection_changed || is_panel_changed;
+    };
+    // Adding or removing a component moves the entity to another archetype
+    let entity_ref = world.entity(entity);
+    let archetype = entity_ref.archetype();
+    let is_archetype_changed = inspected_archetype.replace((entity, archetype.id())) != Some((entity, archetype.id()));
+    let is_component_changed = archetype
+        .iter_components()
+        .filter(|component_id| {
+            world
+                .components()
+                .get_info(*component_id)
+                .and_then(|info| info.type_id())
+                .is_none_or(|type_id| !is_computed_component(type_id))
+        })
+        .filter_map(|component_id| entity_ref.get_change_ticks_by_id(component_id))
+        .any(|ticks| ticks.is_changed(system_change_tick.last_run(), system_change_tick.this_run()));
+    is_selection_changed || is_panel_changed || is_archetype_changed || is_component_changed
+}
+
+/// Builds the rows of the inspector panel from the reflected components of the selected entity.
+/// An entity is only inspected when it is the only selected entity.
+fn update_inspector_panel(world: &mut World) {
+    let selected: Vec<Entity> = world
+        .query_filtered::<Entity, With<Selected>>()
+        .iter(world)
+        .collect();
+    let Ok(inspector_panel) = world.query::<&InspectorPanel>().single(world) else {
+        return;
+    };
+    let collapsed = inspector_panel.collapsed.clone();
+
+    let entity = match selected.as_slice() {
+        [entity] => Some(*entity),
+        _ => None,
+    };
+    let mut fields = Vec::new();
+    let title = match entity {
+        Some(entity) => {
+            let registry = world.resource::<AppTypeRegistry>().clone();
+            let registry = registry.read();
+            let entity_ref = world.entity(entity);
+            let mut components: Vec<_> = entity_ref
+                .archetype()
+                .iter_components()
+                .filter_map(|component_id| world.components().get_info(component_id)?.type_id())
+                .filter(|type_id| !is_computed_component(*type_id))
+                .filter_map(|type_id| registry.get(type_id))
+                .filter_map(|registration| {
+                    let value = registration.data::<ReflectComponent>()?.reflect(entity_ref)?;
+                    Some((registration.type_info().type_path_table(), value))
+                })
+                .collect();
+            components.sort_by_key(|(type_path, _)| type_path.short_path());
+
+            for (type_path, value) in components {
+                let context = FieldContext {
+                    component: type_path.path(),
+                    registry: &registry,
+                    asset_server: world.get_resource::<AssetServer>(),
+                };
+                fields.push(InspectorField {
+                    component: type_path.path().to_string(),
+                    path: String::new(),
+                    label: type_path.short_path().to_string(),
+                    depth: 0,
+                    kind: FieldKind::Component,
+                    values: Vec::new(),
+                });
+                if collapsed.contains(type_path.path()) {
+                    continue;
+                }
+                // Fields of struct components are shown directly below the header
+                let start = fields.len();
+                add_value(&mut fields, &context, value.as_partial_reflect(), "", "Value", 1);
+                if fields.get(start).is_some_and(|field| field.kind == FieldKind::Group) {
+                    fields.remove(start);
+                    for field in &mut fields[start..] {
+                        field.depth -= 1;
+                    }
+                }
+            }
+            match world.get::<Name>(entity) {
+                Some(name) => format!("{name} ({entity})"),
+                None => format!("Entity {entity}"),
+            }
+        }
+        None if selected.is_empty() => "Nothing selected".to_string(),
+        None => format!("{} entities selected", selected.len()),
+    };
+
+    let mut inspector_panel = world.query::<&mut InspectorPanel>().single_mut(world).expect("checked above");
+    if inspector_panel.entity != entity {
+        inspector_panel.entity = entity;
+        inspector_panel.editing = None;
+    }
+    if inspector_panel.title != title {
+        inspector_panel.title = title;
+    }
+    if inspector_panel.fields != fields {
+        inspector_panel.fields = fields;
+    }
+}
+
+/// Changes a field of a component of the entity through the undo history.
+/// `edit` changes a copy of the field and returns false if it can't be changed.
+pub fn edit_component_field(
+    world: &mut World,
+    entity: Entity,
+    component: &str,
+    path: &str,
+    edit: impl FnOnce(&mut dyn PartialReflect, &TypeRegistry) -> bool,
+) {
+    let registry = world.resource::<AppTypeRegistry>().clone();
+    let registry = registry.read();
+    let Some(registration) = registry.get_with_type_path(component) else {
+        return;
+    };
+    let Some(reflect_component) = registration.data::<ReflectComponent>() else {
+        return;
+    };
+    let Ok(entity_ref) = world.get_entity(entity) else {
+        return;
+    };
+    let Some(Ok(before)) = reflect_component.reflect(entity_ref).map(|value| value.reflect_clone()) else {
+        warn!("Can't edit {component}, because it can't be cloned");
+        return;
+    };
+    let Ok(mut after) = before.reflect_clone() else {
+        return;
+    };
+    let field = if path.is_empty() {
+        Ok(after.as_partial_reflect_mut())
+    } else {
+        after.reflect_path_mut(path)
+    };
+    let Ok(field) = field else {
+        return;
+    };
+    if !edit(field, &registry) {
+        return;
+    }
+
+    let type_name = registration.type_info().type_path_table().short_path().to_string();
+    execute_component_command(
+        world,
+        ComponentCommand::new(entity, registration.type_id(), type_name, Some(before), Some(after)),
+    );
+}
+
+/// Removes a component of the entity through the undo history.
+fn remove_component(world: &mut World, entity: Entity, component: &str) {
+    match ComponentCommand::remove(world, entity, component) {
+        Some(command) => execute_component_command(world, command),
+        None => warn!("Can't remove {component}, because it can't be cloned"),
+    }
+}
+
+fn execute_component_command(world: &mut World, mut command: ComponentCommand) {
+    command.apply(world);
+    world.resource_scope(|world, mut history: Mut<EditorHistory>| {
+        history.push(world, Box::new(command));
+        // Every edit in the inspector is its own undo step
+        history.seal();
+    });
+}
+
+/// Applies the text that was typed for a value.
+fn submit_edit(commands: &mut Commands, entity: Entity, edit: InspectorEdit) {
+    commands.queue(move |world: &mut World| {
+        if edit.kind == FieldKind::Handle {
+            let Some(asset_server) = world.get_resource::<AssetServer>() else {
+                return;
+            };
+            let handle = asset_server.load_untyped(edit.text.trim().to_string());
+            world.resource_mut::<PendingHandleEdits>().0.push(PendingHandleEdit {
+                entity,
+                component: edit.component,
+                path: edit.path,
+                handle,
+            });
+            return;
+        }
+        edit_component_field(world, entity, &edit.component, &edit.path, |field, _| {
+            match set_value_text(field, edit.axis, &edit.text) {
+                Ok(()) => true,
+                Err(error) => {
+                    warn!("{error}");
+                    false
+                }
+            }
+        });
+    });
+}
+
+/// Sets the handles of the pending handle edits whose assets have loaded.
+fn apply_pending_handle_edits(
+    mut commands: Commands,
+    mut pending_handle_edits: ResMut<PendingHandleEdits>,
+    asset_server: Res<AssetServer>,
+    loaded_untyped_assets: Res<Assets<LoadedUntypedAsset>>,
+) {
+    pending_handle_edits.0.retain(|pending| {
+        if asset_server.load_state(&pending.handle).is_failed() {
+            warn!("Failed to load {:?}", asset_server.get_path(&pending.handle));
+            return false;
+        }
+        let Some(loaded) = loaded_untyped_assets.get(&pending.handle) else {
+            return true;
+        };
+        let handle = loaded.handle.clone();
+        let (entity, component, path) = (pending.entity, pending.component.clone(), pending.path.clone());
+        commands.queue(move |world: &mut World| {
+            edit_component_field(world, entity, &component, &path, |field, registry| {
+                let Some(reflect_handle) = field
+                    .get_represented_type_info()
+                    .and_then(|type_info| registry.get_type_data::<ReflectHandle>(type_info.type_id()))
+                else {
+                    return false;
+                };
+                if reflect_handle.asset_type_id() != handle.type_id() {
+                    warn!("The asset at {:?} has the wrong type for {}", handle.path(), field.reflect_short_type_path());
+                    return false;
+                }
+                field.try_apply(reflect_handle.typed(handle).as_partial_reflect()).is_ok()
+            });
+        });
+        false
+    });
+}
+
+/// Toggles bools, cycles enums and starts editing text when a value is clicked.
+/// Clicking a component header collapses the component.
+fn handle_inspector_events(
+    mut commands: Commands,
+    mut ui_event_reader: MessageReader<UiEvent>,
+    mut inspector_panel: Single<&mut InspectorPanel>,
+    mut text_input_focus: ResMut<TextInputFocus>,
+    mut value_inputs: Query<(Entity, &InspectorValueInput, &mut TextInput)>,
+) {
+    for event in ui_event_reader.read() {
+        let Some(entity) = inspector_panel.entity else {
+            continue;
+        };
+        let (component, path, axis) = match event {
+            UiEvent::Inspector(InspectorAction::Click { component, path, axis }) => (component, path, axis),
+            UiEvent::Inspector(InspectorAction::RemoveComponent { component }) => {
+                let component = component.clone();
+                commands.queue(move |world: &mut World| remove_component(world, entity, &component));
+                continue;
+            }
+            _ => continue,
+        };
+        let Some(field) = inspector_panel
+            .fields
+            .iter()
+            .find(|field| field.component == *component && field.path == *path)
+            .or_else(|| {
+                inspector_panel.fields.iter().find(|field| {
+                    field.component == *component && field.values.iter().any(|value| value.path == *path)
+                })
+            })
+        else {
+            continue;
+        };
+
+        let (component, path, axis) = (component.clone(), path.clone(), *axis);
+        match field.kind {
+            FieldKind::Component => {
+                if !inspector_panel.collapsed.remove(&component) {
+                    inspector_panel.collapsed.insert(component);
+                }
+            }
+            FieldKind::Bool => {
+                commands.queue(move |world: &mut World| {
+                    edit_component_field(world, entity, &component, &path, |field, _| {
+                        field.try_downcast_mut::<bool>().map(|value| *value = !*value).is_some()
+                    });
+                });
+            }
+            FieldKind::Enum { can_cycle: true } => {
+                commands.queue(move |world: &mut World| {
+                    edit_component_field(world, entity, &component, &path, |field, _| cycle_variant(field));
+                });
+            }
+            FieldKind::Number | FieldKind::Text | FieldKind::Handle => {
+                let kind = field.kind;
+                let text = field
+                    .values
+                    .iter()
+                    .find(|value| value.path == path && value.axis == axis)
+                    .map(|value| value.text.clone())
+                    .unwrap_or_default();
+                let Some((value_input, _, mut text_input)) = value_inputs.iter_mut().find(|(_, input, _)| {
+                    input.component == component && input.path == path && input.axis == axis
+                }) else {
+                    continue;
+                };
+                // The input may still hold an older value of the field
+                text_input_focus.focus_with_value(value_input, &mut text_input, text.clone());
+                inspector_panel.editing = Some(InspectorEdit { component, path, axis, kind, text });
+            }
+            FieldKind::Group | FieldKind::Enum { can_cycle: false } | FieldKind::ReadOnly => {}
+        }
+    }
+}
+
+/// Keeps the text that is typed for the value that is being edited. Submitting applies the value, and editing
+/// stops when the input is cancelled or loses focus.
+fn handle_inspector_input(
+    mut commands: Commands,
+    mut text_input_changed_reader: MessageReader<TextInputChanged>,
+    mut text_input_submitted_reader: MessageReader<TextInputSubmitted>,
+    mut text_input_cancelled_reader: MessageReader<TextInputCancelled>,
+    mut inspector_panel: Single<&mut InspectorPanel>,
+    text_input_focus: Res<TextInputFocus>,
+    value_inputs: Query<&InspectorValueInput>,
+) {
+    for changed in text_input_changed_reader.read() {
+        if value_inputs.contains(changed.entity)
+            && let Some(edit) = inspector_panel.editing.as_mut()
+        {
+            edit.text = changed.value.clone();
+        }
+    }
+    for submitted in text_input_submitted_reader.read() {
+        if value_inputs.contains(submitted.entity)
+            && let Some(entity) = inspector_panel.entity
+            && let Some(mut edit) = inspector_panel.editing.take()
+        {
+            edit.text = submitted.value.clone();
+            submit_edit(&mut commands, entity, edit);
+        }
+    }
+    for cancelled in text_input_cancelled_reader.read() {
+        if value_inputs.contains(cancelled.entity) {
+            inspector_panel.editing = None;
+        }
+    }
+
+    let is_focused = text_input_focus
+        .entity()
+        .is_some_and(|entity| value_inputs.contains(entity));
+    if inspector_panel.editing.is_some() && !is_focused {
+        inspector_panel.editing = None;
+    }
+}
+
+reactive_element!(InspectorPanel, reactive_inspector_panel, |inspector_panel: &InspectorPanel| {
+    let editing = inspector_panel.editing.clone();
+    let rows = inspector_panel.fields.clone().into_iter().map(move |field| {
+        let is_component = field.kind == FieldKind::Component;
+        let is_editable = !matches!(field.kind, FieldKind::ReadOnly | FieldKind::Enum { can_cycle: false });
+        let editing = editing.clone();
+        let component = field.component.clone();
+        let kind = field.kind;
+        let values = field.values.into_iter().map(move |value| {
+            let edit_text = editing
+                .as_ref()
+                .filter(|edit| edit.component == component && edit.path == value.path && edit.axis == value.axis)
+                .map(|edit| edit.text.clone());
+            (
+                EditorUiElement,
+                Button,
+                ClickAction(UiEvent::Inspector(InspectorAction::Click {
+                    component: component.clone(),
+                    path: value.path.clone(),
+                    axis: value.axis,
+                })),
+                Node {
+                    flex_grow: 1.0,
+                    flex_basis: Val::Px(0.0),
+                    overflow: Overflow::clip(),
+                    ..default()
+                },
+                EditorBorderRadius::all(Radius::Sm),
+                EditorBackgroundColor(
+                    if is_editable { EditorColor::InputField } else { EditorColor::Button },
+                    None,
+                    None,
+                ),
+                children![
+                    (
+                        EditorUiElement,
+                        Node {
+                            display: if edit_text.is_none() { Display::Flex } else { Display::None },
+                            ..default()
+                        },
+                        EditorPadding::axes(Spacing::Sm, Spacing::Xs),
+                        Text::new(value.text.clone()),
+                        TextLayout::new_with_no_wrap(),
+                        EditorTextColor(
+                            if is_editable { EditorColor::InputFieldText } else { EditorColor::FadedText },
+                            None,
+                            None,
+                        ),
+                        EditorFontSize(FontSize::Small)
+                    ),
+                    (
+                        InspectorValueInput {
+                            component: component.clone(),
+                            path: value.path,
+                            axis: value.axis,
+                        },
+                        {
+                            let text_input = TextInput::new(edit_text.clone().unwrap_or(value.text)).with_font_size(FontSize::Small);
+                            if kind == FieldKind::Number {
+                                text_input.with_validator(is_valid_number)
+                            } else {
+                                text_input
+                            }
+                        },
+                        Node {
+                            display: if edit_text.is_some() { Display::Flex } else { Display::None },
+                            flex_grow: 1.0,
+                            ..default()
+                        },
+                    ),
+                ],
+            )
+        });
+
+        (
+            EditorUiElement,
+            Button,
+            ClickAction(UiEvent::Inspector(InspectorAction::Click {
+                component: field.component.clone(),
+                path: field.path.clone(),
+                axis: None,
+            })),
+            ContextMenu::new(|menu| {
+                menu.item(
+                    "&Remove Component",
+                    UiEvent::Inspector(InspectorAction::RemoveComponent { component: field.component.clone() }),
+                )
+            }),
+            Node {
+                display: Display::Flex,
+                flex_shrink: 0.0,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            EditorMargin::top(if is_component { Spacing::Sm } else { Spacing::None }),
+            EditorGap(Spacing::Sm),
+            EditorPadding::axes(Spacing::Sm, Spacing::Xs),
+            EditorBorderRadius::all(Radius::Sm),
+            EditorBackgroundColor(
+                if is_component { EditorColor::Heading } else { EditorColor::Background },
+                None,
+                None,
+            ),
+            children![
+                (
+                    EditorUiElement,
+                    Node {
+                        width: if is_component { Val::Auto } else { Val::Percent(40.0) },
+                        flex_shrink: 0.0,
+                        // Indents nested fields
+                        margin: UiRect::left(Val::Px(field.depth.saturating_sub(1) as f32 * FIELD_INDENT)),
+                        overflow: Overflow::clip(),
+                        ..default()
+                    },
+                    Text::new(field.label),
+                    TextLayout::new_with_no_wrap(),
+                    EditorTextColor(
+                        if is_component { EditorColor::HeadingText } else { EditorColor::FadedText },
+                        None,
+                        None,
+                    ),
+                    EditorFontSize(FontSize::Small)
+                ),
+                (
+                    EditorUiElement,
+                    Node {
+                        display: Display::Flex,
+                        flex_grow: 1.0,
+                        ..default()
+                    },
+                    EditorGap(Spacing::Sm),
+                    Children::spawn(SpawnIter(values)),
+                ),
+            ],
+        )
+    });
+
+    (
+        EditorUiElement,
+        NodeFullscreenDisplay::new(Display::Flex, Display::None).with_slide(SlideEdge::Right).with_fade(),
+        Node {
+            display: Display::Flex,
+            flex_direction: FlexDirection::Column,
+            flex_shrink: 0.0,
+            ..default()
+        },
+        EditorWidth(Width::InspectorPanel),
+        EditorGap(Spacing::Sm),
+        EditorPadding::all(Spacing::Md),
+        EditorBackgroundColor(EditorColor::Background, None, None),
+        children![
+            (
+                EditorUiElement,
+                Text::new(inspector_panel.title.clone()),
+                EditorTextColor(EditorColor::Text, None, None),
+                EditorFontSize(FontSize::Body)
+            ),
+            (
+                EditorUiElement,
+                ScrollView,
+                Node {
+                    display: Display::Flex,
+                    flex_direction: FlexDirection::Column,
+                    flex_grow: 1.0,
+                    overflow: Overflow::scroll_y(),
+                    ..default()
+                },
+                EditorGap(Spacing::Xs),
+                Children::spawn(SpawnIter(rows)),
+            ),
+        ],
+    )
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Resource, Default)]
+    struct Updates(usize);
+
+    fn count_updates(mut updates: ResMut<Updates>) {
+        updates.0 += 1;
+    }
+
+    /// Returns whether the inspector panel would have been updated in the next frame.
+    fn is_updated(app: &mut App) -> bool {
+        let before = app.world().resource::<Updates>().0;
+        app.update();
+        app.world().resource::<Updates>().0 != before
+    }
+
+    #[test]
+    fn panel_is_updated_when_the_inspected_entity_changes() {
+        let mut app = App::new();
+        app.init_resource::<Updates>()
+            .add_systems(Update, count_updates.run_if(inspector_panel_outdated));
+        app.world_mut().spawn(InspectorPanel::default());
+        assert!(is_updated(&mut app));
+        assert!(!is_updated(&mut app));
+
+        let entity = app.world_mut().spawn((Transform::default(), Selected)).id();
+        assert!(is_updated(&mut app));
+        assert!(!is_updated(&mut app));
+
+        app.world_mut().get_mut::<Transform>(entity).unwrap().translation.x = 1.0;
+        assert!(is_updated(&mut app));
+        assert!(!is_updated(&mut app));
+
+        app.world_mut().entity_mut(entity).insert(Name::new("Cube"));
+        assert!(is_updated(&mut app));
+        app.world_mut().entity_mut(entity).remove::<Name>();
+        assert!(is_updated(&mut app));
+        assert!(!is_updated(&mut app));
+
+        // Computed components aren't shown, so their changes don't matter
+        app.world_mut().get_mut::<GlobalTransform>(entity).unwrap().set_changed();
+        assert!(!is_updated(&mut app));
+
+        app.world_mut().entity_mut(entity).remove::<Selected>();
+        assert!(is_updated(&mut app));
+        assert!(!is_updated(&mut app));
+    }
+
+    #[test]
+    fn panel_is_updated_when_the_selection_changes() {
+        let mut app = App::new();
+        app.init_resource::<Updates>()
+            .add_systems(Update, count_updates.run_if(inspector_panel_outdated));
+        app.world_mut().spawn(InspectorPanel::default());
+        let first = app.world_mut().spawn(Selected).id();
+        let second = app.world_mut().spawn(Transform::default()).id();
+        assert!(is_updated(&mut app));
+
+        // Changes of entities that aren't inspected don't matter
+        app.world_mut().get_mut::<Transform>(second).unwrap().translation.x = 1.0;
+        assert!(!is_updated(&mut app));
+
+        app.world_mut().entity_mut(second).insert(Selected);
+        assert!(is_updated(&mut app));
+        // Nothing is inspected while several entities are selected, only their number is shown
+        app.world_mut().get_mut::<Transform>(second).unwrap().translation.x = 2.0;
+        assert!(!is_updated(&mut app));
+
+        app.world_mut().despawn(first);
+        assert!(is_updated(&mut app));
+        assert!(!is_updated(&mut app));
+
+        let mut inspector_panel = app.world_mut().query::<&mut InspectorPanel>();
+        let mut inspector_panel = inspector_panel.single_mut(app.world_mut()).unwrap();
+        inspector_panel.collapsed.insert("Transform".to_string());
+        assert!(is_updated(&mut app));
+    }
+
+    #[test]
+    fn editing_starts_with_the_current_value_of_the_field() {
+        let mut app = App::new();
+        app.add_message::<UiEvent>()
+            .init_resource::<TextInputFocus>()
+            .register_type::<Transform>()
+            .add_systems(
+                Update,
+                (handle_inspector_events, update_inspector_panel.run_if(inspector_panel_outdated)).chain(),
+            )
+            .add_systems(PostUpdate, reactive_inspector_panel);
+        app.world_mut().spawn(InspectorPanel::default());
+        let entity = app.world_mut().spawn((Transform::default(), Selected)).id();
+        app.update();
+
+        // The field changes after the panel was first rendered, e.g. by a gizmo drag or an undo
+        app.world_mut().get_mut::<Transform>(entity).unwrap().translation.x = 2.5;
+        app.update();
+
+        let mut inspector_panel = app.world_mut().query::<&InspectorPanel>();
+        let (component, value) = inspector_panel
+            .single(app.world())
+            .unwrap()
+            .fields
+            .iter()
+            .find_map(|field| {
+                let value = field.values.iter().find(|value| value.text == "2.5")?;
+                Some((field.component.clone(), value.clone()))
+            })
+            .unwrap();
+        app.world_mut().write_message(UiEvent::Inspector(InspectorAction::Click {
+            component: component.clone(),
+            path: value.path.clone(),
+            axis: value.axis,
+        }));
+        app.update();
+
+        let focused = app.world().resource::<TextInputFocus>().entity().unwrap();
+        let input = app.world().get::<InspectorValueInput>(focused).unwrap();
+        assert_eq!((&input.component, &input.path, input.axis), (&component, &value.path, value.axis));
+        assert_eq!(app.world().get::<TextInput>(focused).unwrap().value, "2.5");
+    }
+}
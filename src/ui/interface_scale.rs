@@ -0,0 +1,77 @@
+use bevy::prelude::*;
+
+use crate::ui::{
+    ClickAction, UiEvent,
+    elements::MenuBarButton,
+    menu::{MenuBuilder, MenuItem},
+};
+
+/// The interface scales in the View > Interface Scale menu, in percent.
+pub const INTERFACE_SCALES: [u32; 6] = [75, 100, 125, 150, 175, 200];
+
+pub struct InterfaceScalePlugin;
+
+impl Plugin for InterfaceScalePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, handle_interface_scale_events).add_systems(
+            Update,
+            update_interface_scale_menu_items
+                .after(handle_interface_scale_events)
+                .run_if(resource_changed::<UiScale>),
+        );
+    }
+}
+
+/// The interface scale in percent. The editor UI is scaled with the [`UiScale`], on top of the scale factor
+/// of the window.
+pub fn interface_scale(ui_scale: &UiScale) -> u32 {
+    (ui_scale.0 * 100.0).round() as u32
+}
+
+/// Adds the items of the View > Interface Scale menu.
+pub fn interface_scale_menu(menu: MenuBuilder) -> MenuBuilder {
+    let menu = menu
+        .item("&Increase", UiEvent::IncreaseInterfaceScale)
+        .item("&Decrease", UiEvent::DecreaseInterfaceScale)
+        .separator();
+    INTERFACE_SCALES.into_iter().fold(menu, |menu, percent| {
+        menu.add(MenuItem::new(format!("{percent}%"), UiEvent::SetInterfaceScale(percent)).radio("interface_scale", percent == 100))
+    })
+}
+
+fn handle_interface_scale_events(mut ui_event_reader: MessageReader<UiEvent>, mut ui_scale: ResMut<UiScale>) {
+    let (min, max) = (INTERFACE_SCALES[0], INTERFACE_SCALES[INTERFACE_SCALES.len() - 1]);
+    for event in ui_event_reader.read() {
+        let current = interface_scale(&ui_scale);
+        let percent = match event {
+            UiEvent::SetInterfaceScale(percent) => *percent,
+            UiEvent::IncreaseInterfaceScale => {
+                INTERFACE_SCALES.into_iter().find(|percent| *percent > current).unwrap_or(max)
+            }
+            UiEvent::DecreaseInterfaceScale => {
+                INTERFACE_SCALES.into_iter().rev().find(|percent| *percent < current).unwrap_or(min)
+            }
+            _ => continue,
+        };
+        let scale = percent.clamp(min, max) as f32 / 100.0;
+        if ui_scale.0 != scale {
+            ui_scale.0 = scale;
+        }
+    }
+}
+
+/// Checks the menu item of the current interface scale.
+fn update_interface_scale_menu_items(
+    ui_scale: Res<UiScale>,
+    mut menu_bar_buttons: Query<(&mut MenuBarButton, &ClickAction)>,
+) {
+    let current = interface_scale(&ui_scale);
+    for (mut menu_bar_button, click_action) in menu_bar_buttons.iter_mut() {
+        if let UiEvent::SetInterfaceScale(percent) = click_action.0 {
+            let is_checked = Some(percent == current);
+            if menu_bar_button.is_checked != is_checked {
+                menu_bar_button.is_checked = is_checked;
+            }
+        }
+    }
+}
@@ -0,0 +1,906 @@
+use bevy::{
+    ecs::system::SystemParam,
+    input::{ButtonState, keyboard::KeyboardInput},
+    prelude::*,
+    window::PrimaryWindow,
+};
+
+use crate::ui::{
+    ClickAction, ClickActionDisabled, UiEvent,
+    elements::{EditorUi, MenuBar, MenuBarButton, MenuBarDropdown, MenuBarDropdownRoot, MenuSeparator},
+    shortcuts::{handle_shortcuts, parse_keycode},
+    style::{EditorNegativeTop, Spacing},
+};
+
+const ALT_KEYS: [KeyCode; 2] = [KeyCode::AltLeft, KeyCode::AltRight];
+
+/// Id of the open context menu. Only one context menu is open at a time.
+const CONTEXT_MENU_ID: &str = "context";
+
+pub struct MenuPlugin;
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MenuNavigation>()
+            .register_type::<MenuRadioGroup>()
+            .register_type::<ContextMenu>()
+            .add_systems(Update, navigate_menus.before(handle_shortcuts))
+            .add_systems(Update, open_menus_on_hover)
+            .add_systems(Update, close_menus_on_item_click)
+            .add_systems(Update, close_menus_on_click_outside)
+            .add_systems(Update, open_context_menus.after(close_menus_on_click_outside))
+            .add_systems(
+                Update,
+                update_menu_dropdown_visibility
+                    .after(navigate_menus)
+                    .after(open_menus_on_hover)
+                    .after(close_menus_on_item_click)
+                    .after(close_menus_on_click_outside)
+                    .after(open_context_menus),
+            )
+            .add_systems(Update, despawn_closed_context_menus.after(update_menu_dropdown_visibility))
+            .add_systems(Update, clamp_context_menus)
+            .add_systems(
+                Update,
+                update_menu_button_highlights
+                    .after(navigate_menus)
+                    .run_if(resource_changed::<MenuNavigation>),
+            )
+            .add_systems(Update, toggle_checkable_menu_items);
+    }
+}
+
+/// Radio items with the same group in a menu are unchecked when one of them is clicked.
+#[derive(Component, Reflect, Clone, PartialEq, Debug)]
+#[reflect(Component)]
+pub struct MenuRadioGroup(pub String);
+
+/// An item of a menu that emits an event when it is clicked, see [`MenuBuilder::add`].
+#[derive(Reflect, Clone, Debug)]
+pub struct MenuItem {
+    pub text: String,
+    pub event: UiEvent,
+    pub icon: Option<Handle<Image>>,
+    pub is_enabled: bool,
+    /// None if the item can't be checked.
+    pub is_checked: Option<bool>,
+    pub radio_group: Option<String>,
+}
+
+#[allow(dead_code)]
+impl MenuItem {
+    pub fn new(text: impl Into<String>, event: UiEvent) -> Self {
+        Self {
+            text: text.into(),
+            event,
+            icon: None,
+            is_enabled: true,
+            is_checked: None,
+            radio_group: None,
+        }
+    }
+
+    pub fn with_icon(mut self, icon: Handle<Image>) -> Self {
+        self.icon = Some(icon);
+        self
+    }
+
+    /// Greys out the item, so it can't be clicked.
+    pub fn disabled(mut self) -> Self {
+        self.is_enabled = false;
+        self
+    }
+
+    /// Shows a check mark in front of the item while it is checked. Clicking the item toggles it.
+    pub fn checkbox(mut self, is_checked: bool) -> Self {
+        self.is_checked = Some(is_checked);
+        self
+    }
+
+    /// Shows a dot in front of the item while it is checked. Clicking the item unchecks the other items of the group.
+    pub fn radio(mut self, group: impl Into<String>, is_checked: bool) -> Self {
+        self.is_checked = Some(is_checked);
+        self.radio_group = Some(group.into());
+        self
+    }
+}
+
+#[derive(Reflect, Clone)]
+enum MenuEntry {
+    Item(MenuItem),
+    Separator,
+    Submenu { text: String, menu: MenuBuilder },
+}
+
+/// The items of a menu or submenu, see [`MenuBar::new`].
+#[derive(Reflect, Clone)]
+pub struct MenuBuilder {
+    id: String,
+    entries: Vec<MenuEntry>,
+}
+
+#[allow(dead_code)]
+impl MenuBuilder {
+    fn new(id: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn item(self, text: impl Into<String>, event: UiEvent) -> Self {
+        self.add(MenuItem::new(text, event))
+    }
+
+    pub fn add(mut self, item: MenuItem) -> Self {
+        self.entries.push(MenuEntry::Item(item));
+        self
+    }
+
+    pub fn separator(mut self) -> Self {
+        self.entries.push(MenuEntry::Separator);
+        self
+    }
+
+    /// Adds a submenu that opens when its item is hovered.
+    /// Its id is the id of this menu and the text, e.g. "file.recent" for "Recent" in the "File" menu.
+    pub fn submenu(mut self, text: impl Into<String>, build: impl FnOnce(MenuBuilder) -> MenuBuilder) -> Self {
+        let text = text.into();
+        let menu = build(MenuBuilder::new(format!("{}.{}", self.id, menu_id(&text))));
+        self.entries.push(MenuEntry::Submenu { text, menu });
+        self
+    }
+}
+
+/// The menus of a menu bar, see [`MenuBar::new`].
+pub struct MenuBarBuilder {
+    menus: Vec<(String, MenuBuilder)>,
+}
+
+impl MenuBarBuilder {
+    /// Adds a menu. Its id is the text in lowercase, e.g. "file" for "&File".
+    /// The character after `&` is the mnemonic, which opens the menu with Alt.
+    pub fn menu(mut self, text: impl Into<String>, build: impl FnOnce(MenuBuilder) -> MenuBuilder) -> Self {
+        let text = text.into();
+        let menu = build(MenuBuilder::new(menu_id(&text)));
+        self.menus.push((text, menu));
+        self
+    }
+
+    /// Spawns the menu bar with its menus and returns it.
+    pub fn spawn(self, commands: &mut Commands) -> Entity {
+        let menu_bar = commands.spawn(MenuBar).id();
+        for (text, menu) in self.menus {
+            spawn_menu(commands, menu_bar, text, menu, 0);
+        }
+        menu_bar
+    }
+}
+
+impl MenuBar {
+    /// Builds a menu bar, e.g. `MenuBar::new().menu("File", |menu| menu.item("Save", UiEvent::FileSave))`.
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new() -> MenuBarBuilder {
+        MenuBarBuilder { menus: Vec::new() }
+    }
+}
+
+/// Opens a menu at the cursor when the element is right-clicked, e.g.
+/// `ContextMenu::new(|menu| menu.item("&Close", UiEvent::CloseTab(0)))`.
+/// The innermost hovered element with a context menu gets to open it.
+#[derive(Component, Reflect, Clone)]
+#[reflect(Component)]
+#[require(Interaction)]
+pub struct ContextMenu(MenuBuilder);
+
+impl ContextMenu {
+    pub fn new(build: impl FnOnce(MenuBuilder) -> MenuBuilder) -> Self {
+        Self(build(MenuBuilder::new(CONTEXT_MENU_ID)))
+    }
+}
+
+/// The menu that was opened by a [`ContextMenu`]. It is despawned when it is closed.
+#[derive(Component)]
+struct ContextMenuPopup {
+    /// The cursor position the menu was opened at, in physical pixels.
+    position: Vec2,
+}
+
+pub trait MenuCommandsExt {
+    /// Appends items to the menu with the given id, e.g. "camera" or "file.recent".
+    /// A menu is added to the end of the menu bar if there is no menu with the id yet.
+    fn extend_menu(
+        &mut self,
+        id: impl Into<String>,
+        build: impl FnOnce(MenuBuilder) -> MenuBuilder + Send + 'static,
+    );
+}
+
+impl MenuCommandsExt for Commands<'_, '_> {
+    fn extend_menu(
+        &mut self,
+        id: impl Into<String>,
+        build: impl FnOnce(MenuBuilder) -> MenuBuilder + Send + 'static,
+    ) {
+        let id = id.into();
+        self.queue(move |world: &mut World| {
+            let dropdown = world
+                .query::<(Entity, &MenuBarDropdown)>()
+                .iter(world)
+                .find(|(_, dropdown)| dropdown.id == id)
+                .map(|(entity, _)| entity);
+            let menu = build(MenuBuilder::new(id.clone()));
+            match dropdown {
+                Some(dropdown) => {
+                    // The items are one level deeper than the menus they are in
+                    let mut depth = 0;
+                    let mut ancestor = Some(dropdown);
+                    while let Some(entity) = ancestor {
+                        depth += world.get::<MenuBarDropdown>(entity).is_some() as usize;
+                        ancestor = world.get::<ChildOf>(entity).map(|child_of| child_of.parent());
+                    }
+                    spawn_entries(&mut world.commands(), dropdown, menu.entries, depth);
+                }
+                None => {
+                    let Ok(menu_bar) = world.query_filtered::<Entity, With<MenuBar>>().single(world) else {
+                        warn!("Can't add menu {id}, because there is no menu bar");
+                        return;
+                    };
+                    if id.contains('.') {
+                        warn!("Can't add submenu {id}, because its menu doesn't exist");
+                        return;
+                    }
+                    let text = format!("&{}", capitalize(&id.replace('_', " ")));
+                    spawn_menu(&mut world.commands(), menu_bar, text, menu, 0);
+                }
+            }
+            // Spawn the items right away, so later commands can find new menus
+            world.flush();
+        });
+    }
+}
+
+/// Turns the text of a menu into its id, e.g. "Keyboard Shortcuts" into "keyboard_shortcuts".
+fn menu_id(text: &str) -> String {
+    parse_mnemonic(text).0.to_lowercase().replace(' ', "_")
+}
+
+/// Removes the `&` in front of the mnemonic from the text, e.g. "Save &As" becomes "Save As" with mnemonic 5.
+/// `&&` is a literal `&`.
+fn parse_mnemonic(text: &str) -> (String, Option<usize>) {
+    let mut parsed = String::new();
+    let mut mnemonic = None;
+    let mut characters = text.chars();
+    while let Some(character) = characters.next() {
+        match character {
+            '&' => match characters.next() {
+                Some('&') => parsed.push('&'),
+                Some(character) => {
+                    mnemonic = mnemonic.or(Some(parsed.chars().count()));
+                    parsed.push(character);
+                }
+                None => {}
+            },
+            character => parsed.push(character),
+        }
+    }
+    (parsed, mnemonic)
+}
+
+fn capitalize(text: &str) -> String {
+    let mut characters = text.chars();
+    characters
+        .next()
+        .map_or_else(String::new, |first| first.to_uppercase().chain(characters).collect())
+}
+
+/// Spawns the button that opens the menu and its dropdown. `depth` is the depth of the button.
+fn spawn_menu(commands: &mut Commands, parent: Entity, text: String, menu: MenuBuilder, depth: usize) {
+    let (text, mnemonic) = parse_mnemonic(&text);
+    let button = commands
+        .spawn((
+            MenuBarButton {
+                text,
+                shortcut_text: None,
+                depth,
+                is_dropdown: true,
+                is_enabled: true,
+                is_checked: None,
+                is_radio: false,
+                icon: None,
+                mnemonic,
+                show_mnemonic: false,
+                is_highlighted: false,
+            },
+            ClickAction(UiEvent::OpenMenu { id: menu.id.clone() }),
+        ))
+        .id();
+    let dropdown = commands.spawn(MenuBarDropdown { id: menu.id.clone() }).id();
+    let root = commands.spawn(MenuBarDropdownRoot).add_children(&[button, dropdown]).id();
+    if depth > 0 {
+        // Submenus open to the right of their item instead of below it. The padding and gap of the Node are set by
+        // the EditorPadding and EditorGap that MenuBarDropdown requires, and the top lines up the first item with
+        // the item that opens the submenu.
+        commands.entity(root).insert(Node {
+            position_type: PositionType::Relative,
+            ..default()
+        });
+        commands.entity(dropdown).insert((
+            Node {
+                position_type: PositionType::Absolute,
+                left: Val::Percent(100.0),
+                display: Display::Flex,
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            EditorNegativeTop(Spacing::Sm),
+        ));
+    }
+    commands.entity(parent).add_child(root);
+    spawn_entries(commands, dropdown, menu.entries, depth + 1);
+}
+
+fn spawn_entries(commands: &mut Commands, dropdown: Entity, entries: Vec<MenuEntry>, depth: usize) {
+    for entry in entries {
+        match entry {
+            MenuEntry::Item(item) => {
+                let (text, mnemonic) = parse_mnemonic(&item.text);
+                let mut entity = commands.spawn((
+                    MenuBarButton {
+                        text,
+                        shortcut_text: None,
+                        depth,
+                        is_dropdown: false,
+                        is_enabled: item.is_enabled,
+                        is_checked: item.is_checked,
+                        is_radio: item.radio_group.is_some(),
+                        icon: item.icon,
+                        mnemonic,
+                        show_mnemonic: false,
+                        is_highlighted: false,
+                    },
+                    ClickAction(item.event),
+                    ChildOf(dropdown),
+                ));
+                if !item.is_enabled {
+                    entity.insert(ClickActionDisabled);
+                }
+                if let Some(group) = item.radio_group {
+                    entity.insert(MenuRadioGroup(group));
+                }
+            }
+            MenuEntry::Separator => {
+                commands.spawn((MenuSeparator, ChildOf(dropdown)));
+            }
+            MenuEntry::Submenu { text, menu } => spawn_menu(commands, dropdown, text, menu, depth),
+        }
+    }
+}
+
+/// Opens a submenu when its item is hovered, and closes it when another item of the menu is hovered.
+/// While a menu is open, hovering another button in the menu bar switches to its menu.
+fn open_menus_on_hover(
+    menu_bar_buttons: Query<(&MenuBarButton, &ClickAction, &Interaction, &ChildOf), Changed<Interaction>>,
+    dropdowns: Query<(&MenuBarDropdown, &Visibility)>,
+    mut ui_event_writer: MessageWriter<UiEvent>,
+) {
+    let is_menu_open = dropdowns.iter().any(|(_, visibility)| *visibility == Visibility::Visible);
+    for (menu_bar_button, click_action, interaction, child_of) in menu_bar_buttons.iter() {
+        if *interaction != Interaction::Hovered {
+            continue;
+        }
+        if menu_bar_button.depth == 0 {
+            let is_open = matches!(&click_action.0, UiEvent::OpenMenu { id } if dropdowns
+                .iter()
+                .any(|(dropdown, visibility)| dropdown.id == *id && *visibility == Visibility::Visible));
+            if is_menu_open && !is_open {
+                ui_event_writer.write(click_action.0.clone());
+            }
+        } else if menu_bar_button.is_dropdown {
+            ui_event_writer.write(click_action.0.clone());
+        } else if let Ok((dropdown, Visibility::Visible)) = dropdowns.get(child_of.parent()) {
+            ui_event_writer.write(UiEvent::OpenMenu {
+                id: dropdown.id.clone(),
+            });
+        }
+    }
+}
+
+/// Shows the opened menu and the menus it is in, and hides the other menus.
+fn update_menu_dropdown_visibility(
+    mut menu_bar_dropdowns: Query<(Entity, &MenuBarDropdown, &mut Visibility)>,
+    parents: Query<&ChildOf>,
+    mut ui_event_reader: MessageReader<UiEvent>,
+) {
+    for event in ui_event_reader.read() {
+        let open_dropdowns: Vec<Entity> = match event {
+            UiEvent::OpenMenu { id } => menu_bar_dropdowns
+                .iter()
+                .filter(|(_, menu_bar_dropdown, _)| menu_bar_dropdown.id == *id)
+                .flat_map(|(entity, _, _)| parents.iter_ancestors(entity).chain([entity]))
+                .collect(),
+            UiEvent::CloseMenus => Vec::new(),
+            _ => continue,
+        };
+        for (entity, _, mut visibility) in menu_bar_dropdowns.iter_mut() {
+            visibility.set_if_neq(if open_dropdowns.contains(&entity) {
+                Visibility::Visible
+            } else {
+                Visibility::Hidden
+            });
+        }
+    }
+}
+
+/// Closes the menus when the editor is clicked outside of them.
+fn close_menus_on_click_outside(
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    menu_bar_buttons: Query<&Interaction, With<MenuBarButton>>,
+    dropdowns: Query<(&Visibility, &ComputedNode, &UiGlobalTransform), With<MenuBarDropdown>>,
+    mut ui_event_writer: MessageWriter<UiEvent>,
+) {
+    if mouse_buttons.get_just_pressed().next().is_none() {
+        return;
+    }
+    let open_dropdowns: Vec<Rect> = dropdowns
+        .iter()
+        .filter(|(visibility, _, _)| **visibility == Visibility::Visible)
+        .map(|(_, computed_node, transform)| Rect::from_center_size(transform.translation, computed_node.size()))
+        .collect();
+    if open_dropdowns.is_empty() {
+        return;
+    }
+    let is_over_menu_bar_button = menu_bar_buttons.iter().any(|interaction| *interaction != Interaction::None);
+    let is_over_dropdown = window
+        .physical_cursor_position()
+        .is_some_and(|cursor| open_dropdowns.iter().any(|rect| rect.contains(cursor)));
+    if !is_over_menu_bar_button && !is_over_dropdown {
+        ui_event_writer.write(UiEvent::CloseMenus);
+    }
+}
+
+/// Opens the context menu of the innermost hovered element when the right mouse button is pressed,
+/// replacing the context menu that is already open.
+#[allow(clippy::too_many_arguments)]
+fn open_context_menus(
+    mut commands: Commands,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    ui_scale: Res<UiScale>,
+    editor_ui: Single<Entity, With<EditorUi>>,
+    context_menus: Query<(Entity, &ContextMenu, &Interaction)>,
+    popups: Query<Entity, With<ContextMenuPopup>>,
+    parents: Query<&ChildOf>,
+    mut ui_event_writer: MessageWriter<UiEvent>,
+) {
+    if !mouse_buttons.just_pressed(MouseButton::Right) {
+        return;
+    }
+    let Some(cursor) = window.physical_cursor_position() else {
+        return;
+    };
+    let Some((_, context_menu, _)) = context_menus
+        .iter()
+        .filter(|(_, _, interaction)| **interaction != Interaction::None)
+        .max_by_key(|(entity, _, _)| parents.iter_ancestors(*entity).count())
+    else {
+        return;
+    };
+
+    for popup in popups.iter() {
+        commands.entity(popup).despawn();
+    }
+    // Placed at the cursor for now, clamp_context_menus moves it into the window once its size is known
+    let position = cursor / (window.scale_factor() * ui_scale.0);
+    let dropdown = commands
+        .spawn((
+            MenuBarDropdown {
+                id: CONTEXT_MENU_ID.to_string(),
+            },
+            ContextMenuPopup { position: cursor },
+            Node {
+                position_type: PositionType::Absolute,
+                left: Val::Px(position.x),
+                top: Val::Px(position.y),
+                display: Display::Flex,
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            Visibility::Visible,
+            ChildOf(*editor_ui),
+        ))
+        .id();
+    spawn_entries(&mut commands, dropdown, context_menu.0.entries.clone(), 1);
+    // Closes the other menus
+    ui_event_writer.write(UiEvent::OpenMenu {
+        id: CONTEXT_MENU_ID.to_string(),
+    });
+}
+
+/// Moves context menus that would extend past the right or bottom edge of the window back into it.
+fn clamp_context_menus(
+    window: Single<&Window, With<PrimaryWindow>>,
+    mut popups: Query<(&ContextMenuPopup, &ComputedNode, &mut Node)>,
+) {
+    let window_size = window.physical_size().as_vec2();
+    for (popup, computed_node, mut node) in popups.iter_mut() {
+        let position = popup
+            .position
+            .min(window_size - computed_node.size())
+            .max(Vec2::ZERO)
+            * computed_node.inverse_scale_factor();
+        let (left, top) = (Val::Px(position.x), Val::Px(position.y));
+        if node.left != left || node.top != top {
+            node.left = left;
+            node.top = top;
+        }
+    }
+}
+
+fn despawn_closed_context_menus(mut commands: Commands, popups: Query<(Entity, &Visibility), With<ContextMenuPopup>>) {
+    for (popup, visibility) in popups.iter() {
+        if *visibility == Visibility::Hidden {
+            commands.entity(popup).despawn();
+        }
+    }
+}
+
+/// Closes the menus when one of their items is clicked.
+#[allow(clippy::type_complexity)]
+fn close_menus_on_item_click(
+    menu_bar_buttons: Query<(&MenuBarButton, &Interaction), (Changed<Interaction>, Without<ClickActionDisabled>)>,
+    mut ui_event_writer: MessageWriter<UiEvent>,
+) {
+    for (menu_bar_button, interaction) in menu_bar_buttons.iter() {
+        if *interaction == Interaction::Pressed && menu_bar_button.depth > 0 && !menu_bar_button.is_dropdown {
+            ui_event_writer.write(UiEvent::CloseMenus);
+        }
+    }
+}
+
+/// Checks or unchecks checkbox and radio items when they are clicked.
+/// Items of registered commands are updated by the command registry instead.
+#[allow(clippy::type_complexity)]
+fn toggle_checkable_menu_items(
+    clicked_items: Query<
+        (Entity, &Interaction, &ClickAction, Option<&MenuRadioGroup>, &ChildOf),
+        (Changed<Interaction>, With<MenuBarButton>, Without<ClickActionDisabled>),
+    >,
+    mut menu_bar_buttons: Query<(&mut MenuBarButton, Option<&MenuRadioGroup>)>,
+    children: Query<&Children>,
+) {
+    for (entity, interaction, click_action, radio_group, child_of) in clicked_items.iter() {
+        if *interaction != Interaction::Pressed || matches!(click_action.0, UiEvent::Command(_)) {
+            continue;
+        }
+        match radio_group {
+            Some(radio_group) => {
+                for sibling in children.get(child_of.parent()).into_iter().flatten().copied() {
+                    let Ok((mut menu_bar_button, sibling_group)) = menu_bar_buttons.get_mut(sibling) else {
+                        continue;
+                    };
+                    let is_checked = Some(sibling == entity);
+                    if sibling_group == Some(radio_group) && menu_bar_button.is_checked != is_checked {
+                        menu_bar_button.is_checked = is_checked;
+                    }
+                }
+            }
+            None => {
+                if let Ok((mut menu_bar_button, _)) = menu_bar_buttons.get_mut(entity)
+                    && let Some(is_checked) = menu_bar_button.is_checked
+                {
+                    menu_bar_button.is_checked = Some(!is_checked);
+                }
+            }
+        }
+    }
+}
+
+/// State of the keyboard navigation of the menus.
+#[derive(Resource, Default, Clone, PartialEq, Debug)]
+pub struct MenuNavigation {
+    /// The menu bar was focused with Alt. It stays focused when a menu is closed with Escape.
+    is_focused: bool,
+    /// The menu bar is focused or a menu is open, so key presses go to the menus instead of the shortcuts.
+    is_active: bool,
+    /// The menu bar button or menu item that is selected with the keyboard.
+    highlighted: Option<Entity>,
+    show_mnemonics: bool,
+    /// Alt was pressed without another key, so releasing it focuses the menu bar.
+    is_alt_tapped: bool,
+}
+
+impl MenuNavigation {
+    pub fn is_active(&self) -> bool {
+        self.is_active
+    }
+}
+
+/// The menu bar buttons, menus and items, found through the hierarchy of the menu bar.
+#[derive(SystemParam)]
+struct MenuTree<'w, 's> {
+    menu_bars: Query<'w, 's, &'static Children, With<MenuBar>>,
+    menu_bar_buttons: Query<'w, 's, (&'static MenuBarButton, &'static ClickAction, Has<ClickActionDisabled>)>,
+    dropdowns: Query<'w, 's, (Entity, &'static Visibility), With<MenuBarDropdown>>,
+    children: Query<'w, 's, &'static Children>,
+    parents: Query<'w, 's, &'static ChildOf>,
+}
+
+impl MenuTree<'_, '_> {
+    /// The buttons in the menu bar.
+    fn menus(&self) -> Vec<Entity> {
+        self.menu_bars
+            .iter()
+            .flatten()
+            .filter_map(|root| self.button(*root))
+            .collect()
+    }
+
+    /// The button of a menu bar dropdown root.
+    fn button(&self, root: Entity) -> Option<Entity> {
+        self.children.get(root).ok()?.iter().find(|child| self.menu_bar_buttons.contains(*child))
+    }
+
+    /// The dropdown that is opened by a button.
+    fn dropdown(&self, button: Entity) -> Option<Entity> {
+        let root = self.parents.get(button).ok()?.parent();
+        self.children.get(root).ok()?.iter().find(|child| self.dropdowns.contains(*child))
+    }
+
+    /// The button that opens a dropdown.
+    fn opener(&self, dropdown: Entity) -> Option<Entity> {
+        self.button(self.parents.get(dropdown).ok()?.parent())
+    }
+
+    /// The dropdown that contains an item.
+    fn dropdown_of(&self, item: Entity) -> Option<Entity> {
+        self.parents
+            .iter_ancestors(item)
+            .find(|ancestor| self.dropdowns.contains(*ancestor))
+    }
+
+    fn is_submenu_item(&self, item: Entity) -> bool {
+        self.menu_bar_buttons
+            .get(item)
+            .is_ok_and(|(menu_bar_button, _, _)| menu_bar_button.is_dropdown && menu_bar_button.depth > 0)
+    }
+
+    /// The enabled items of a dropdown, including the items that open submenus.
+    fn items(&self, dropdown: Entity) -> Vec<Entity> {
+        self.children
+            .get(dropdown)
+            .into_iter()
+            .flatten()
+            .filter_map(|child| match self.menu_bar_buttons.contains(*child) {
+                true => Some(*child),
+                false => self.button(*child),
+            })
+            .filter(|item| matches!(self.menu_bar_buttons.get(*item), Ok((_, _, false))))
+            .collect()
+    }
+
+    /// The innermost open dropdown.
+    fn open_dropdown(&self) -> Option<Entity> {
+        self.dropdowns
+            .iter()
+            .filter(|(_, visibility)| **visibility == Visibility::Visible)
+            .max_by_key(|(dropdown, _)| self.depth(*dropdown))
+            .map(|(dropdown, _)| dropdown)
+    }
+
+    /// The number of dropdowns a dropdown is in.
+    fn depth(&self, dropdown: Entity) -> usize {
+        self.parents
+            .iter_ancestors(dropdown)
+            .filter(|ancestor| self.dropdowns.contains(*ancestor))
+            .count()
+    }
+
+    /// The button in the menu bar whose menu contains the entity.
+    fn menu_of(&self, entity: Entity) -> Option<Entity> {
+        let ancestors: Vec<Entity> = self.parents.iter_ancestors(entity).collect();
+        self.menus().into_iter().find(|menu| {
+            self.parents
+                .get(*menu)
+                .is_ok_and(|child_of| ancestors.contains(&child_of.parent()))
+        })
+    }
+
+    fn mnemonic_key(&self, button: Entity) -> Option<KeyCode> {
+        let (menu_bar_button, _, _) = self.menu_bar_buttons.get(button).ok()?;
+        let mnemonic = menu_bar_button.text.chars().nth(menu_bar_button.mnemonic?)?;
+        parse_keycode(&mnemonic.to_uppercase().to_string())
+    }
+
+    /// Opens the dropdown of a button, highlights its first item and returns it.
+    fn open(
+        &self,
+        button: Entity,
+        navigation: &mut MenuNavigation,
+        ui_event_writer: &mut MessageWriter<UiEvent>,
+    ) -> Option<Entity> {
+        let (_, click_action, _) = self.menu_bar_buttons.get(button).ok()?;
+        let dropdown = self.dropdown(button)?;
+        ui_event_writer.write(click_action.0.clone());
+        navigation.highlighted = self.items(dropdown).first().copied().or(Some(button));
+        Some(dropdown)
+    }
+
+    /// Emits the event of an item and closes the menus, or opens the submenu of the item.
+    fn activate(
+        &self,
+        item: Entity,
+        navigation: &mut MenuNavigation,
+        ui_event_writer: &mut MessageWriter<UiEvent>,
+    ) -> Option<Entity> {
+        if self.is_submenu_item(item) {
+            return self.open(item, navigation, ui_event_writer);
+        }
+        let (_, click_action, _) = self.menu_bar_buttons.get(item).ok()?;
+        ui_event_writer.write(click_action.0.clone());
+        ui_event_writer.write(UiEvent::CloseMenus);
+        navigation.is_focused = false;
+        None
+    }
+}
+
+/// Alt focuses the menu bar and Alt with a mnemonic opens a menu. While the menu bar is focused or a menu is open,
+/// the arrow keys move between the menus and items, mnemonics and Enter activate items and Escape closes menus.
+#[allow(clippy::type_complexity)]
+fn navigate_menus(
+    mut keyboard_input_reader: MessageReader<KeyboardInput>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    hovered_items: Query<(Entity, &Interaction), (Changed<Interaction>, With<MenuBarButton>)>,
+    tree: MenuTree,
+    mut menu_navigation: ResMut<MenuNavigation>,
+    mut ui_event_writer: MessageWriter<UiEvent>,
+) {
+    let mut navigation = menu_navigation.clone();
+    let menus = tree.menus();
+    let mut open = tree.open_dropdown();
+    if mouse_buttons.get_just_pressed().next().is_some() {
+        navigation.is_focused = false;
+    }
+    for (item, interaction) in hovered_items.iter() {
+        if *interaction == Interaction::Hovered && (open.is_some() || navigation.is_focused) {
+            navigation.highlighted = Some(item);
+        }
+    }
+
+    for event in keyboard_input_reader.read() {
+        let key = event.key_code;
+        if ALT_KEYS.contains(&key) {
+            match event.state {
+                ButtonState::Pressed if !event.repeat => {
+                    navigation.is_alt_tapped = true;
+                    navigation.show_mnemonics = true;
+                }
+                ButtonState::Released if navigation.is_alt_tapped => {
+                    navigation.is_alt_tapped = false;
+                    if navigation.is_focused || open.is_some() {
+                        ui_event_writer.write(UiEvent::CloseMenus);
+                        navigation.is_focused = false;
+                        open = None;
+                    } else {
+                        navigation.is_focused = true;
+                        navigation.highlighted = menus.first().copied();
+                    }
+                }
+                _ => {}
+            }
+            continue;
+        }
+        if event.state != ButtonState::Pressed {
+            continue;
+        }
+        navigation.is_alt_tapped = false;
+
+        let Some(dropdown) = open else {
+            if !navigation.is_focused && !keys.any_pressed(ALT_KEYS) {
+                continue;
+            }
+            let highlighted = navigation.highlighted.and_then(|menu| menus.iter().position(|m| *m == menu));
+            let menu = match key {
+                KeyCode::ArrowLeft | KeyCode::ArrowRight if navigation.is_focused => {
+                    let offset = if key == KeyCode::ArrowLeft { menus.len() - 1 } else { 1 };
+                    navigation.highlighted = highlighted.map(|index| menus[(index + offset) % menus.len()]);
+                    None
+                }
+                KeyCode::ArrowDown | KeyCode::Enter | KeyCode::NumpadEnter | KeyCode::Space
+                    if navigation.is_focused =>
+                {
+                    navigation.highlighted
+                }
+                KeyCode::Escape if navigation.is_focused => {
+                    navigation.is_focused = false;
+                    None
+                }
+                key => menus.iter().find(|menu| tree.mnemonic_key(**menu) == Some(key)).copied(),
+            };
+            if let Some(menu) = menu {
+                open = tree.open(menu, &mut navigation, &mut ui_event_writer);
+                navigation.is_focused = true;
+                navigation.show_mnemonics = true;
+            }
+            continue;
+        };
+
+        navigation.show_mnemonics = true;
+        let items = tree.items(dropdown);
+        let highlighted = navigation.highlighted.filter(|item| items.contains(item));
+        let is_submenu = tree.depth(dropdown) > 0;
+        let menu = tree.menu_of(dropdown).and_then(|menu| menus.iter().position(|m| *m == menu));
+        match key {
+            KeyCode::ArrowUp | KeyCode::ArrowDown if !items.is_empty() => {
+                let index = highlighted.and_then(|item| items.iter().position(|i| *i == item));
+                let index = match (key, index) {
+                    (KeyCode::ArrowUp, Some(index)) => (index + items.len() - 1) % items.len(),
+                    (KeyCode::ArrowUp, None) => items.len() - 1,
+                    (_, Some(index)) => (index + 1) % items.len(),
+                    (_, None) => 0,
+                };
+                navigation.highlighted = Some(items[index]);
+            }
+            KeyCode::ArrowRight if let Some(item) = highlighted.filter(|item| tree.is_submenu_item(*item)) => {
+                open = tree.open(item, &mut navigation, &mut ui_event_writer);
+            }
+            KeyCode::ArrowLeft | KeyCode::Escape if is_submenu => {
+                // Close the submenu and highlight the item that opened it
+                let opener = tree.opener(dropdown);
+                if let Some(parent_dropdown) = opener.and_then(|opener| tree.dropdown_of(opener))
+                    && let Some(parent_opener) = tree.opener(parent_dropdown)
+                {
+                    open = tree.open(parent_opener, &mut navigation, &mut ui_event_writer);
+                    navigation.highlighted = opener;
+                }
+            }
+            KeyCode::ArrowLeft | KeyCode::ArrowRight => {
+                if let Some(index) = menu {
+                    let offset = if key == KeyCode::ArrowLeft { menus.len() - 1 } else { 1 };
+                    open = tree.open(menus[(index + offset) % menus.len()], &mut navigation, &mut ui_event_writer);
+                }
+            }
+            KeyCode::Escape => {
+                ui_event_writer.write(UiEvent::CloseMenus);
+                // Context menus are not in the menu bar, so it doesn't get focused when they are closed
+                navigation.is_focused = menu.is_some();
+                navigation.highlighted = menu.map(|index| menus[index]);
+                open = None;
+            }
+            KeyCode::Enter | KeyCode::NumpadEnter | KeyCode::Space => {
+                if let Some(item) = highlighted {
+                    open = tree.activate(item, &mut navigation, &mut ui_event_writer);
+                }
+            }
+            key => {
+                if let Some(item) = items.iter().find(|item| tree.mnemonic_key(**item) == Some(key)) {
+                    open = tree.activate(*item, &mut navigation, &mut ui_event_writer);
+                }
+            }
+        }
+    }
+
+    navigation.is_active = navigation.is_focused || open.is_some();
+    if !navigation.is_active {
+        navigation.highlighted = None;
+        navigation.show_mnemonics = keys.any_pressed(ALT_KEYS);
+    }
+    menu_navigation.set_if_neq(navigation);
+}
+
+/// Highlights the menu bar button or item that is selected with the keyboard and shows the mnemonics.
+fn update_menu_button_highlights(
+    menu_navigation: Res<MenuNavigation>,
+    mut menu_bar_buttons: Query<(Entity, &mut MenuBarButton)>,
+) {
+    for (entity, mut menu_bar_button) in menu_bar_buttons.iter_mut() {
+        let is_highlighted = menu_navigation.highlighted == Some(entity);
+        let show_mnemonic = menu_navigation.show_mnemonics && menu_bar_button.mnemonic.is_some();
+        if menu_bar_button.is_highlighted != is_highlighted || menu_bar_button.show_mnemonic != show_mnemonic {
+            menu_bar_button.is_highlighted = is_highlighted;
+            menu_bar_button.show_mnemonic = show_mnemonic;
+        }
+    }
+}
@@ -6,10 +6,29 @@ use bevy::{
 };
 
 pub mod colors;
+pub mod command_palette;
+pub mod command_registry;
 pub mod elements;
+pub mod entity_actions;
+pub mod file_dialog;
 pub mod font;
+pub mod hierarchy;
+pub mod history;
 pub mod icons;
+pub mod inspector;
+pub mod interface_scale;
+pub mod menu;
+pub mod reactive;
+pub mod scene_file;
+pub mod selection;
+pub mod shortcut_editor;
 pub mod shortcuts;
+pub mod style;
+pub mod tabs;
+pub mod text_input;
+pub mod theme;
+pub mod transform_gizmo;
+pub mod transition;
 pub mod fullscreen;
 
 use elements::*;
@@ -17,21 +36,30 @@ use elements::*;
 use bevy::diagnostic::DiagnosticsStore;
 use bevy::diagnostic::FrameTimeDiagnosticsPlugin;
 
+use crate::ui::entity_actions::EntityAction;
 use crate::ui::fullscreen::FullscreenState;
 
+/// Maximum time in seconds between two clicks on the same item to count as a double click.
+pub const DOUBLE_CLICK_TIME: f64 = 0.4;
+
 pub struct UiPlugin {
     pub editor_render_layer: Layer,
+    /// Maximum number of steps that can be undone.
+    pub history_depth: usize,
+    pub transitions: transition::TransitionSettings,
 }
 
 impl Default for UiPlugin {
     fn default() -> Self {
         Self {
             editor_render_layer: 999,
+            history_depth: 100,
+            transitions: transition::TransitionSettings::default(),
         }
     }
 }
 
-#[derive(Message, Hash, Eq, PartialEq, Clone, Debug)]
+#[derive(Message, Reflect, Hash, Eq, PartialEq, Clone, Debug)]
 pub enum UiEvent {
     OpenMenu { id: String },
     CloseMenus,
@@ -47,15 +75,39 @@ pub enum UiEvent {
     SelectTool(Tool),
     SelectTab(usize),
     CloseTab(usize),
+    CloseOtherTabs(usize),
+    CloseTabsToRight(usize),
+    /// Copies the file path of the tab's document to the clipboard.
+    CopyTabPath(usize),
+    RenameTab { index: usize, name: String },
     ToggleFullscreen,
+    /// Sets the interface scale in percent, see [`interface_scale::INTERFACE_SCALES`].
+    SetInterfaceScale(u32),
+    IncreaseInterfaceScale,
+    DecreaseInterfaceScale,
     NextTab,
     PreviousTab,
+    FileDialog(file_dialog::FileDialogAction),
+    Hierarchy(hierarchy::HierarchyAction),
+    Entity(entity_actions::EntityAction),
+    Inspector(inspector::InspectorAction),
+    ShortcutEditor(shortcut_editor::ShortcutEditorAction),
+    CommandPalette(command_palette::CommandPaletteAction),
+    /// Applies the theme with the given name, see [`theme::Themes`].
+    SelectTheme(String),
+    /// A command that was registered in the [`command_registry::CommandRegistry`].
+    Command(command_registry::CommandId),
 }
 
-#[derive(Component, Clone)]
+#[derive(Component, Reflect, Clone)]
+#[reflect(Component)]
 pub struct ClickAction(pub UiEvent);
 
-#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Default)]
+/// Prevents the ClickAction of an element from being emitted, e.g. for greyed out menu items.
+#[derive(Component, Default, Clone, Copy)]
+pub struct ClickActionDisabled;
+
+#[derive(Reflect, Debug, Clone, Copy, Hash, Eq, PartialEq, Default)]
 pub enum Tool {
     #[default]
     Pointer,
@@ -72,6 +124,9 @@ pub struct EditorRenderLayer(Layer);
 #[derive(Resource, Default, Clone, Copy)]
 pub struct CurrentTab(pub Option<usize>);
 
+#[derive(Resource, Default, Clone, Copy)]
+pub struct CurrentTool(pub Tool);
+
 
 impl Plugin for UiPlugin {
     fn build(&self, app: &mut App) {
@@ -86,19 +141,44 @@ impl Plugin for UiPlugin {
 
         // If Bevy UI is not added yet, add it
         if app.get_added_plugins::<bevy::ui::UiPlugin>().is_empty() {
-            app.add_plugins(bevy::ui::UiPlugin::default());
+            app.add_plugins(bevy::ui::UiPlugin);
         }
 
         app
             .add_plugins(fullscreen::FullscreenPlugin)
             .add_plugins(shortcuts::ShortcutsPlugin)
+            .add_plugins(shortcut_editor::ShortcutEditorPlugin)
+            .add_plugins(command_palette::CommandPalettePlugin)
+            .add_plugins(command_registry::CommandRegistryPlugin)
             .add_plugins(colors::ColorsPlugin)
+            .add_plugins(style::StylePlugin)
+            .add_plugins(theme::ThemePlugin)
+            .add_plugins(interface_scale::InterfaceScalePlugin)
+            .add_plugins(text_input::TextInputPlugin)
             .add_plugins(elements::ElementsPlugin)
+            .add_plugins(menu::MenuPlugin)
             .add_plugins(font::FontPlugin)
             .add_plugins(icons::IconsPlugin)
+            .add_plugins(tabs::TabsPlugin)
+            .add_plugins(scene_file::SceneFilePlugin)
+            .add_plugins(file_dialog::FileDialogPlugin)
+            .add_plugins(transform_gizmo::TransformGizmoPlugin)
+            .add_plugins(selection::SelectionPlugin)
+            .add_plugins(hierarchy::HierarchyPlugin)
+            .add_plugins(entity_actions::EntityActionsPlugin)
+            .add_plugins(inspector::InspectorPlugin)
+            .add_plugins(history::HistoryPlugin {
+                max_depth: self.history_depth,
+            })
+            .add_plugins(transition::TransitionPlugin {
+                settings: self.transitions,
+            })
             .add_message::<UiEvent>()
+            .register_type::<EditorUiElement>()
+            .register_type::<ClickAction>()
 
             .insert_resource(CurrentTab(Some(0)))
+            .insert_resource(CurrentTool::default())
             .insert_resource(EditorRenderLayer(self.editor_render_layer))
 
             // Add default UI elements
@@ -112,12 +192,10 @@ impl Plugin for UiPlugin {
             .add_systems(Update, handle_click_action)
             .add_systems(Update, handle_close_app)
             .add_systems(Update, handle_toggle_fullscreen)
-            .add_systems(Update, handle_tab_events)
+            .add_systems(Update, handle_select_tool)
             // Update UI elements
             .add_systems(Update, update_fps_counter)
-            .add_systems(Update, update_menu_dropdown_visibility)
             .add_systems(Update, update_selected_tool_button)
-            .add_systems(Update, update_current_tab.run_if(resource_changed::<CurrentTab>))
         ;
     }
 }
@@ -136,7 +214,8 @@ fn add_render_layer(
     }
 }
 
-#[derive(Component, Default, Clone, Copy)]
+#[derive(Component, Reflect, Default, Clone, Copy)]
+#[reflect(Component)]
 pub struct EditorUiElement;
 
 #[derive(Component)]
@@ -147,11 +226,10 @@ fn setup_ui(
     mut commands: Commands,
     render_layer: Res<EditorRenderLayer>,
     tool_button_icons: Res<icons::ToolButtonIcons>,
-    shortcuts: Res<shortcuts::Shortcuts>,
 ) {
     commands.spawn((
         EditorUiCamera,
-        Camera2d::default(),
+        Camera2d,
         RenderLayers::layer(render_layer.0),
         Camera {
             order: 999,
@@ -159,57 +237,50 @@ fn setup_ui(
         },
     ));
 
+    let menu_bar = MenuBar::new()
+        .menu("&File", |menu| {
+            menu.item("&New", UiEvent::FileNew)
+                .item("&Open", UiEvent::FileOpen)
+                .separator()
+                .item("&Save", UiEvent::FileSave)
+                .item("Save &As", UiEvent::FileSaveAs)
+                .separator()
+                .item("&Close", UiEvent::FileClose)
+                .item("E&xit", UiEvent::FileExit)
+        })
+        .menu("&Edit", |menu| {
+            menu.item("&Undo", UiEvent::Undo)
+                .item("&Redo", UiEvent::Redo)
+                .separator()
+                .item(
+                    "&Keyboard Shortcuts",
+                    UiEvent::ShortcutEditor(shortcut_editor::ShortcutEditorAction::Open),
+                )
+        })
+        .menu("&View", |menu| {
+            menu.item("Toggle &Fullscreen", UiEvent::ToggleFullscreen)
+                .submenu("&Tabs", |menu| {
+                    menu.item("&Next Tab", UiEvent::NextTab)
+                        .item("&Previous Tab", UiEvent::PreviousTab)
+                })
+                .item(
+                    "Command &Palette",
+                    UiEvent::CommandPalette(command_palette::CommandPaletteAction::Open),
+                )
+                // Filled with the theme files by theme::ThemePlugin
+                .submenu("T&heme", |menu| menu)
+                .submenu("Interface &Scale", interface_scale::interface_scale_menu)
+        })
+        // Filled by plugins, see menu::MenuCommandsExt::extend_menu
+        .menu("&Camera", |menu| menu)
+        .menu("&Help", |menu| menu.item("Show &Help", UiEvent::ShowHelp))
+        .spawn(&mut commands);
+
     commands.spawn((
         EditorUi,
-        Button,
-        ClickAction(UiEvent::CloseMenus),
         children![
-            (
-                MenuBar,
-                children![
-                    elements::menu_bar_dropdown!("File".to_string(), "file",
-                        [
-                            MenuBarButton::new("New".to_string(), UiEvent::FileNew, &shortcuts),
-                            MenuBarButton::new("Open".to_string(), UiEvent::FileOpen, &shortcuts),
-                            MenuBarButton::new("Save".to_string(), UiEvent::FileSave, &shortcuts),
-                            MenuBarButton::new("Save As".to_string(), UiEvent::FileSaveAs, &shortcuts),
-                            MenuBarButton::new("Close".to_string(), UiEvent::FileClose, &shortcuts),
-                            MenuBarButton::new("Exit".to_string(), UiEvent::FileExit, &shortcuts),
-                        ]
-                    ),
-                    elements::menu_bar_dropdown!("Edit".to_string(), "edit",
-                        [
-                            MenuBarButton::new("Undo".to_string(), UiEvent::Undo, &shortcuts),
-                            MenuBarButton::new("Redo".to_string(), UiEvent::Redo, &shortcuts),
-                        ]
-                    ),
-                    elements::menu_bar_dropdown!("View".to_string(), "view",
-                        [
-                    	    MenuBarButton::new("Toggle Fullscreen".to_string(), UiEvent::ToggleFullscreen, &shortcuts),
-                            MenuBarButton::new("Next Tab".to_string(), UiEvent::NextTab, &shortcuts),
-                            MenuBarButton::new("Previous Tab".to_string(), UiEvent::PreviousTab, &shortcuts),
-                        ]
-                    ),
-                    elements::menu_bar_dropdown!("Camera".to_string(), "camera",
-                        [
-
-                        ]
-                    ),
-                    elements::menu_bar_dropdown!("Help".to_string(), "help",
-                        [
-                            MenuBarButton::new("Show Help".to_string(), UiEvent::ShowHelp, &shortcuts),
-                        ]
-                    ),
-                ]
-            ),
-            (
-                TabBar,
-                children![
-                    Tab::new(0, "Tab 1".to_string(), true),
-                    Tab::new(1, "Tab 2".to_string(), false),
-                    Tab::new(2, "Tab 3".to_string(), false),
-                ]
-            ),
+            // Tabs are spawned based on the OpenDocuments resource
+            (TabBar,),
             (
                 ToolBar,
                 children![
@@ -241,49 +312,101 @@ fn setup_ui(
                     (FpsCounter { fps: None })
                 ]
             ),
-            (CameraPreview,),
+            (
+                hierarchy::Workspace,
+                children![
+                    hierarchy::HierarchyPanel::default(),
+                    (
+                        CameraPreview,
+                        menu::ContextMenu::new(|menu| {
+                            menu.item("&Add Entity", UiEvent::Entity(EntityAction::Add))
+                                .item("&Paste", UiEvent::Entity(EntityAction::Paste))
+                                .separator()
+                                .item("&Frame Selection", UiEvent::Entity(EntityAction::FrameSelection))
+                        })
+                    ),
+                    inspector::InspectorPanel::default(),
+                ]
+            ),
             StatusBar {
                 text: "Some status".to_string(),
+                pending_chords: None,
             }
         ],
-    ));
+    )).insert_children(0, &[menu_bar]);
 }
 
 /// Updates the camera viewport of the other cameras other than the EditorUiCamera
 /// to match the screen coordinates of the CameraPreview element in the UI.
+/// The UI is laid out in physical pixels, which include the scale factor of the window and the UiScale.
+/// The parts of the CameraPreview that are outside the render target of a camera, e.g. while a panel slides
+/// in, are cut off.
 fn update_camera_viewport(
     mut other_cameras: Query<&mut Camera, Without<EditorUiCamera>>,
-    camera_preview_position: Single<
-        (&UiGlobalTransform, &ComputedNode),
-        (With<CameraPreview>, Changed<UiGlobalTransform>),
-    >,
-    // _: Single<&Camera, Added<EditorUiCamera>>,
+    camera_preview: Single<(&UiGlobalTransform, &ComputedNode), With<CameraPreview>>,
 ) {
-    let center_x = camera_preview_position.0.translation.x;
-    let center_y = camera_preview_position.0.translation.y;
-    let width = camera_preview_position.1.unrounded_size.x;
-    let height = camera_preview_position.1.unrounded_size.y;
-    let top_left_x = center_x - width / 2.0;
-    let top_left_y = center_y - height / 2.0;
+    let (transform, computed_node) = camera_preview.into_inner();
+    let preview_rect = Rect::from_center_size(transform.translation, computed_node.size());
 
     for mut camera in other_cameras.iter_mut() {
-        camera.viewport = Some(Viewport {
-            physical_position: UVec2::new(top_left_x as u32, top_left_y as u32),
-            physical_size: UVec2::new(width as u32, height as u32),
-            ..default()
+        // Unknown until the render target of the camera is created
+        let Some(target_size) = camera.physical_target_size() else {
+            continue;
+        };
+        let viewport = preview_viewport(preview_rect, target_size);
+        let is_changed = camera.viewport.as_ref().is_none_or(|current| {
+            current.physical_position != viewport.physical_position || current.physical_size != viewport.physical_size
         });
+        if is_changed {
+            camera.viewport = Some(viewport);
+        }
+    }
+}
+
+/// Returns the viewport that covers the part of the rect, in physical pixels, that is inside the render target.
+/// A viewport can't be empty, so a rect outside of the target leaves a single pixel.
+fn preview_viewport(rect: Rect, target_size: UVec2) -> Viewport {
+    let target_rect = Rect::from_corners(Vec2::ZERO, target_size.as_vec2());
+    let rect = Rect::from_corners(rect.min.round(), rect.max.round()).intersect(target_rect);
+    Viewport {
+        physical_position: rect.min.as_uvec2().min(target_size.saturating_sub(UVec2::ONE)),
+        physical_size: rect.size().as_uvec2().max(UVec2::ONE),
+        ..default()
     }
 }
 
+/// Returns the scene camera whose viewport is under the cursor, and the ray from that camera through the cursor.
+/// The ray takes the viewport offset set by update_camera_viewport into account.
+pub fn viewport_cursor_ray<'a>(
+    window: &Window,
+    cameras: impl Iterator<Item = (&'a Camera, &'a GlobalTransform)>,
+) -> Option<((&'a Camera, &'a GlobalTransform), Ray3d)> {
+    let cursor_position = window.cursor_position()?;
+    cameras
+        .filter(|(camera, _)| camera.is_active)
+        .find(|(camera, _)| {
+            camera
+                .logical_viewport_rect()
+                .is_some_and(|rect| rect.contains(cursor_position))
+        })
+        .and_then(|(camera, camera_transform)| {
+            let ray = camera.viewport_to_world(camera_transform, cursor_position).ok()?;
+            Some(((camera, camera_transform), ray))
+        })
+}
+
 /// Updates FpsCounter component's fps field based on the FrameTimeDiagnosticsPlugin.
 fn update_fps_counter(
     mut fps_counter: Single<&mut FpsCounter>,
     diagnostics: Res<DiagnosticsStore>,
 ) {
-    fps_counter.fps = diagnostics
+    let fps = diagnostics
         .get(&FrameTimeDiagnosticsPlugin::FPS)
         .and_then(|fps| fps.smoothed())
-        .map(|fps| fps as f32);
+        .map(|fps| fps as u32);
+    if fps_counter.fps != fps {
+        fps_counter.fps = fps;
+    }
 }
 
 /// Updates ToolButton component's is_active field based on the selected tool.
@@ -300,35 +423,9 @@ fn update_selected_tool_button(
     }
 }
 
-/// Updates MenuBarDropdown component's visibility based on the clicked button.
-fn update_menu_dropdown_visibility(
-    mut menu_bar_dropdown: Query<(&MenuBarDropdown, &mut Visibility)>,
-    mut menu_bar_button_clicked_reader: MessageReader<UiEvent>,
-) {
-    // Update dropdown visibility based on the clicked button
-    for event in menu_bar_button_clicked_reader.read() {
-        match event {
-            UiEvent::OpenMenu { id } => {
-                for (menu_bar_dropdown, mut visibility) in menu_bar_dropdown.iter_mut() {
-                    *visibility = if menu_bar_dropdown.id == *id {
-                        Visibility::Visible
-                    } else {
-                        Visibility::Hidden
-                    };
-                }
-            }
-            // All other events close the menus
-            _ => {
-                for (_, mut visibility) in menu_bar_dropdown.iter_mut() {
-                    *visibility = Visibility::Hidden;
-                }
-            }
-        }
-    }
-}
-
+#[allow(clippy::type_complexity)]
 fn handle_click_action(
-    query: Query<(&ClickAction, &Interaction), Changed<Interaction>>,
+    query: Query<(&ClickAction, &Interaction), (Changed<Interaction>, Without<ClickActionDisabled>)>,
     mut ui_event_writer: MessageWriter<UiEvent>,
 ) {
     for (click_action, interaction) in query.iter() {
@@ -349,6 +446,17 @@ pub fn handle_close_app(
     }
 }
 
+fn handle_select_tool(
+    mut ui_event_reader: MessageReader<UiEvent>,
+    mut current_tool: ResMut<CurrentTool>,
+) {
+    for event in ui_event_reader.read() {
+        if let UiEvent::SelectTool(tool) = event {
+            current_tool.0 = *tool;
+        }
+    }
+}
+
 fn handle_toggle_fullscreen(
     mut ui_event_reader: MessageReader<UiEvent>,
     fullscreen_state: Res<State<FullscreenState>>,
@@ -365,41 +473,64 @@ fn handle_toggle_fullscreen(
     }
 }
 
-// Handle next, previous and select tab
-fn handle_tab_events(
-    mut ui_event_reader: MessageReader<UiEvent>,
-    mut current_tab: ResMut<CurrentTab>,
-) {
-    let tab_count = 3;
-    for event in ui_event_reader.read() {
-        if let UiEvent::NextTab = event {
-            // Wrap around to the first tab if the current tab is the last tab
-            current_tab.0 = if tab_count == 0 { None } else { Some((current_tab.0.unwrap_or(0) + 1) % tab_count) };
-        }
-        if let UiEvent::PreviousTab = event {
-            // Wrap around to the last tab if the current tab is the first tab
-            current_tab.0 = if tab_count == 0 { None } else { Some((tab_count + current_tab.0.unwrap_or(0) - 1) % tab_count) };
-        }
-        if let UiEvent::SelectTab(index) = event {
-            // Clamp the index to the range of the tab count
-            current_tab.0 = Some((*index).max(0).min(tab_count - 1));
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the rect of a node in physical pixels, like bevy_ui lays it out, from its logical position and size.
+    fn physical_rect(position: Vec2, size: Vec2, scale_factor: f32, ui_scale: f32) -> Rect {
+        let scale = scale_factor * ui_scale;
+        Rect::from_corners(position * scale, (position + size) * scale)
     }
-}
 
-/// Updates Tab component's is_active field based on the CurrentTab resource.
-fn update_current_tab(
-    current_tab: Res<CurrentTab>,
-    tabs: Query<(Entity, &Tab)>,
-    mut commands: Commands,
-) {
-    for (entity, tab) in tabs.iter() {
-        let new_active=  Some(tab.index) == current_tab.0;
-        if new_active != tab.is_active {
-            commands.entity(entity).insert(Tab {
-                is_active: new_active,
-                ..((*tab).clone())
-            });
-        }
+    fn assert_viewport(viewport: Viewport, position: UVec2, size: UVec2) {
+        assert_eq!((viewport.physical_position, viewport.physical_size), (position, size));
+    }
+
+    #[test]
+    fn viewport_matches_a_node_inside_the_target() {
+        let rect = physical_rect(Vec2::new(300.0, 150.0), Vec2::new(600.0, 300.0), 1.0, 1.0);
+        assert_viewport(preview_viewport(rect, UVec2::new(1920, 1080)), UVec2::new(300, 150), UVec2::new(600, 300));
+    }
+
+    #[test]
+    fn viewport_is_cut_off_at_the_target_edges() {
+        let target = UVec2::new(800, 600);
+
+        // Sliding in from the top left, so the origin is negative
+        let rect = physical_rect(Vec2::new(-100.0, -50.0), Vec2::new(500.0, 350.0), 1.0, 1.0);
+        assert_viewport(preview_viewport(rect, target), UVec2::ZERO, UVec2::new(400, 300));
+
+        let rect = physical_rect(Vec2::new(600.0, 500.0), Vec2::new(400.0, 200.0), 1.0, 1.0);
+        assert_viewport(preview_viewport(rect, target), UVec2::new(600, 500), UVec2::new(200, 100));
+    }
+
+    #[test]
+    fn viewport_of_a_node_larger_than_the_target_covers_the_target() {
+        let rect = physical_rect(Vec2::new(-50.0, -50.0), Vec2::new(1000.0, 800.0), 1.0, 1.0);
+        assert_viewport(preview_viewport(rect, UVec2::new(800, 600)), UVec2::ZERO, UVec2::new(800, 600));
+    }
+
+    #[test]
+    fn viewport_of_a_node_outside_the_target_is_a_pixel_inside_it() {
+        let target = UVec2::new(800, 600);
+        let rect = physical_rect(Vec2::new(900.0, 700.0), Vec2::new(100.0, 100.0), 1.0, 1.0);
+        let viewport = preview_viewport(rect, target);
+        assert_eq!(viewport.physical_size, UVec2::ONE);
+        assert!((viewport.physical_position + viewport.physical_size).cmple(target).all());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn viewport_follows_the_scale_factor_and_ui_scale() {
+        let rect = physical_rect(Vec2::new(100.0, 50.0), Vec2::new(200.0, 100.0), 2.0, 1.5);
+        assert_viewport(preview_viewport(rect, UVec2::new(1920, 1080)), UVec2::new(300, 150), UVec2::new(600, 300));
+
+        // Fractional edges are rounded to the nearest pixel, like the edges of the UI nodes around the preview
+        let rect = physical_rect(Vec2::new(101.0, 33.0), Vec2::new(333.0, 211.0), 1.25, 1.1);
+        assert_viewport(preview_viewport(rect, UVec2::new(1920, 1080)), UVec2::new(139, 45), UVec2::new(458, 291));
+
+        // At a large scale the node no longer fits
+        let rect = physical_rect(Vec2::new(100.0, 50.0), Vec2::new(800.0, 400.0), 2.0, 1.5);
+        assert_viewport(preview_viewport(rect, UVec2::new(1920, 1080)), UVec2::new(300, 150), UVec2::new(1620, 930));
+    }
+}
@@ -0,0 +1,289 @@
+use bevy::{
+    ecs::{component::ComponentId, entity::EntityCloner},
+    prelude::*,
+    reflect::TypeRegistry,
+};
+
+/// The components a reactive element was last rendered with, for every entity of the rendered tree.
+/// [`Reconcile`] compares the next render with it to find out which components changed.
+#[derive(Component, Default)]
+pub struct RenderedTemplate(TemplateNode);
+
+#[derive(Default)]
+struct TemplateNode {
+    components: Vec<(ComponentId, TemplateValue)>,
+    children: Vec<TemplateNode>,
+}
+
+enum TemplateValue {
+    /// Copy of the value, compared with reflection.
+    Reflected(Box<dyn Reflect>),
+    /// Zero sized component, like a marker, which is equal to any other value of it.
+    Marker,
+    /// A component that can't be copied with reflection. Its value can't be compared, so it is only inserted
+    /// when it is new and keeps the value it was first rendered with. Components whose value changes between
+    /// renders need `#[derive(Reflect)]` and `#[reflect(Component)]`.
+    Opaque,
+}
+
+impl TemplateNode {
+    /// Returns true if the node has the component with the same value.
+    fn contains(&self, component: ComponentId, value: &TemplateValue) -> bool {
+        self.components.iter().any(|(other_component, other_value)| {
+            *other_component == component
+                && match (other_value, value) {
+                    (TemplateValue::Reflected(a), TemplateValue::Reflected(b)) => {
+                        a.reflect_partial_eq(b.as_partial_reflect()).unwrap_or(false)
+                    }
+                    (TemplateValue::Marker, TemplateValue::Marker) => true,
+                    (TemplateValue::Opaque, TemplateValue::Opaque) => true,
+                    _ => false,
+                }
+        })
+    }
+
+    fn has(&self, component: ComponentId) -> bool {
+        self.components.iter().any(|(other_component, _)| *other_component == component)
+    }
+}
+
+/// Marks children that are spawned at runtime by a widget instead of by a reactive element,
+/// e.g. the text of a [`TextInput`](crate::ui::text_input::TextInput). [`Reconcile`] leaves them alone.
+#[derive(Component, Default, Clone, Copy)]
+pub struct WidgetChild;
+
+/// Command that patches the entity tree rendered by a reactive element, instead of
+/// despawning and respawning it.
+///
+/// The new `bundle` is spawned as a temporary template tree and compared with the [`RenderedTemplate`] of the
+/// element. Only the components that differ from the last render are moved onto the live entities, so state
+/// that is managed at runtime (layout, interaction, render layers, fonts, ...) is kept and
+/// entity ids stay stable. Children are matched by their index.
+pub struct Reconcile<B: Bundle> {
+    pub entity: Entity,
+    pub bundle: B,
+}
+
+impl<B: Bundle> Command for Reconcile<B> {
+    fn apply(self, world: &mut World) {
+        let Ok(mut live) = world.get_entity_mut(self.entity) else {
+            return;
+        };
+        let previous = live
+            .get_mut::<RenderedTemplate>()
+            .map(|mut rendered_template| std::mem::take(&mut rendered_template.0));
+        let template = world.spawn(self.bundle).id();
+
+        let type_registry = world.resource::<AppTypeRegistry>().clone();
+        let next = template_node(world, &type_registry.read(), template);
+        reconcile_entity(world, self.entity, previous.as_ref(), &next, template);
+
+        world.despawn(template);
+        world.entity_mut(self.entity).insert(RenderedTemplate(next));
+    }
+}
+
+/// Patches `live` with the components of the `template` entity that are different from `previous`,
+/// then recurses into the children.
+fn reconcile_entity(
+    world: &mut World,
+    live: Entity,
+    previous: Option<&TemplateNode>,
+    next: &TemplateNode,
+    template: Entity,
+) {
+    let changed: Vec<ComponentId> = next
+        .components
+        .iter()
+        .filter(|(component, value)| !previous.is_some_and(|previous| previous.contains(*component, value)))
+        .map(|(component, _)| *component)
+        .collect();
+    let removed: Vec<ComponentId> = previous
+        .map(|previous| {
+            previous
+                .components
+                .iter()
+                .map(|(component, _)| *component)
+                .filter(|component| !next.has(*component))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    if !removed.is_empty() {
+        world.entity_mut(live).remove_by_ids(&removed);
+    }
+    if !changed.is_empty() {
+        EntityCloner::build_opt_in(world)
+            .without_required_components(|builder| {
+                builder.allow_by_ids(changed);
+            })
+            .move_components(true)
+            .clone_entity(template, live);
+    }
+
+    let live_children: Vec<Entity> = children_of(world, live)
+        .into_iter()
+        .filter(|child| !world.entity(*child).contains::<WidgetChild>())
+        .collect();
+    let template_children = children_of(world, template);
+
+    for (index, (template_child, next_child)) in template_children.iter().zip(&next.children).enumerate() {
+        match live_children.get(index) {
+            Some(live_child) => reconcile_entity(
+                world,
+                *live_child,
+                previous.and_then(|previous| previous.children.get(index)),
+                next_child,
+                *template_child,
+            ),
+            // New child, move the whole template subtree over to the live entity
+            None => {
+                world.entity_mut(*template_child).insert(ChildOf(live));
+            }
+        }
+    }
+    for live_child in live_children.iter().skip(template_children.len()) {
+        world.despawn(*live_child);
+    }
+}
+
+/// Copies the components of a template entity and its children, excluding the hierarchy which is
+/// reconciled separately.
+fn template_node(world: &World, type_registry: &TypeRegistry, entity: Entity) -> TemplateNode {
+    let hierarchy = [world.component_id::<ChildOf>(), world.component_id::<Children>()];
+    let entity_ref = world.entity(entity);
+    let components = entity_ref
+        .archetype()
+        .components()
+        .iter()
+        .copied()
+        .filter(|component| !hierarchy.contains(&Some(*component)))
+        .map(|component| {
+            let info = world.components().get_info(component);
+            let value = info
+                .and_then(|info| info.type_id())
+                .and_then(|type_id| type_registry.get_type_data::<ReflectComponent>(type_id))
+                .and_then(|reflect_component| reflect_component.reflect(entity_ref))
+                .and_then(|value| value.reflect_clone().ok())
+                .map(TemplateValue::Reflected);
+            let value = match value {
+                Some(value) => value,
+                None if info.is_some_and(|info| info.layout().size() == 0) => TemplateValue::Marker,
+                None => TemplateValue::Opaque,
+            };
+            (component, value)
+        })
+        .collect();
+    let children = children_of(world, entity)
+        .into_iter()
+        .map(|child| template_node(world, type_registry, child))
+        .collect();
+    TemplateNode { components, children }
+}
+
+fn children_of(world: &World, entity: Entity) -> Vec<Entity> {
+    world
+        .get::<Children>(entity)
+        .map(|children| children.to_vec())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::{elements::reactive_element, text_input::TextInput};
+
+    #[derive(Component)]
+    pub struct Counter(u32);
+
+    /// Component that isn't reflected and isn't zero sized.
+    #[derive(Component)]
+    pub struct Opaque(u32);
+
+    reactive_element!(Counter, reactive_counter, |counter: &Counter| {
+        (
+            Node::default(),
+            children![(Button, Opaque(7), Text::new(counter.0.to_string())), Text::new("Count")],
+        )
+    });
+
+    reactive_element!(Counter, reactive_counter_input, |counter: &Counter| {
+        (Node::default(), children![TextInput::new(counter.0.to_string())])
+    });
+
+    fn children(app: &App, entity: Entity) -> Vec<Entity> {
+        app.world().get::<Children>(entity).unwrap().to_vec()
+    }
+
+    #[test]
+    fn children_and_interaction_survive_a_data_change() {
+        let mut app = App::new();
+        app.add_systems(Update, reactive_counter);
+        let counter = app.world_mut().spawn(Counter(1)).id();
+        app.update();
+
+        let rendered = children(&app, counter);
+        assert_eq!(rendered.len(), 2);
+        assert_eq!(app.world().get::<Text>(rendered[0]).unwrap().0, "1");
+        *app.world_mut().get_mut::<Interaction>(rendered[0]).unwrap() = Interaction::Hovered;
+        let entity_count = app.world().entities().len();
+
+        app.world_mut().get_mut::<Counter>(counter).unwrap().0 = 2;
+        app.update();
+
+        assert_eq!(children(&app, counter), rendered);
+        assert_eq!(app.world().get::<Text>(rendered[0]).unwrap().0, "2");
+        assert_eq!(app.world().get::<Interaction>(rendered[0]), Some(&Interaction::Hovered));
+        // The template is despawned again
+        assert_eq!(app.world().entities().len(), entity_count);
+    }
+
+    #[test]
+    fn unchanged_components_are_not_replaced() {
+        let mut app = App::new();
+        app.add_systems(Update, reactive_counter);
+        let counter = app.world_mut().spawn(Counter(1)).id();
+        app.update();
+        let label = children(&app, counter)[1];
+        app.world_mut().get_mut::<Text>(label).unwrap().0 = "Edited".to_string();
+
+        app.world_mut().get_mut::<Counter>(counter).unwrap().0 = 2;
+        app.update();
+
+        assert_eq!(app.world().get::<Text>(label).unwrap().0, "Edited");
+    }
+
+    #[test]
+    fn opaque_components_are_not_reinserted() {
+        let mut app = App::new();
+        app.add_systems(Update, reactive_counter);
+        let counter = app.world_mut().spawn(Counter(1)).id();
+        app.update();
+        let button = children(&app, counter)[0];
+        let last_changed = |app: &App| app.world().entity(button).get_ref::<Opaque>().unwrap().last_changed();
+        let rendered_at = last_changed(&app);
+
+        app.world_mut().get_mut::<Counter>(counter).unwrap().0 = 2;
+        app.update();
+
+        assert_eq!(app.world().get::<Text>(button).unwrap().0, "2");
+        assert_eq!(app.world().get::<Opaque>(button).unwrap().0, 7);
+        assert_eq!(last_changed(&app), rendered_at);
+    }
+
+    #[test]
+    fn text_input_values_are_updated() {
+        let mut app = App::new();
+        app.register_type::<TextInput>()
+            .add_systems(Update, reactive_counter_input);
+        let counter = app.world_mut().spawn(Counter(1)).id();
+        app.update();
+        let text_input = children(&app, counter)[0];
+
+        app.world_mut().get_mut::<Counter>(counter).unwrap().0 = 2;
+        app.update();
+
+        assert_eq!(children(&app, counter), [text_input]);
+        assert_eq!(app.world().get::<TextInput>(text_input).unwrap().value, "2");
+    }
+}
@@ -0,0 +1,420 @@
+use std::{
+    collections::BTreeSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use bevy::{
+    asset::io::file::FileAssetReader,
+    ecs::{entity::EntityHashMap, entity_disabling::Disabled},
+    prelude::*,
+    reflect::{TypeRegistry, serde::TypedReflectSerializer},
+    scene::{DynamicSceneBuilder, ron, serde::SceneDeserializer, serialize_ron},
+};
+
+use crate::ui::{
+    CurrentTab, EditorUiCamera, EditorUiElement, UiEvent,
+    history::EditorHistory,
+    tabs::{self, OpenDocuments},
+};
+
+/// File extension of the scene files written by the editor.
+pub const SCENE_FILE_EXTENSION: &str = "scn.ron";
+
+pub struct SceneFilePlugin;
+
+impl Plugin for SceneFilePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_message::<SaveScene>()
+            .add_message::<OpenScene>()
+            .add_systems(Update, handle_file_events)
+            .add_systems(Update, (save_scenes, open_scenes).after(handle_file_events))
+            .add_systems(
+                Update,
+                swap_document_scenes
+                    .after(tabs::handle_tab_events)
+                    .after(save_scenes)
+                    .after(open_scenes)
+                    .run_if(resource_changed::<OpenDocuments>.or(resource_changed::<CurrentTab>)),
+            );
+    }
+}
+
+/// Saves the scene of the current document to the given file.
+#[derive(Message, Clone, Debug)]
+pub struct SaveScene(pub PathBuf);
+
+/// Opens a scene file in a new tab, or selects its tab if the file is already open.
+#[derive(Message, Clone, Debug)]
+pub struct OpenScene(pub PathBuf);
+
+/// Returns the Bevy asset folder, where the file dialog starts by default.
+pub fn default_scene_folder() -> PathBuf {
+    FileAssetReader::get_base_path().join("assets")
+}
+
+/// Returns the tab name for a scene file, which is the file name without the extension.
+pub fn document_name(path: &Path) -> String {
+    let file_name = path
+        .file_name()
+        .map(|file_name| file_name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    match file_name.strip_suffix(&format!(".{SCENE_FILE_EXTENSION}")) {
+        Some(name) => name.to_string(),
+        None => file_name,
+    }
+}
+
+/// Query filter for the root entities of the scene that is edited, which are the entities with a Transform
+/// that are not part of the editor itself. Cameras are not part of the scene either, so the camera of the
+/// preview keeps rendering when the scene of another tab is spawned.
+pub type SceneRootFilter = (
+    With<Transform>,
+    Without<ChildOf>,
+    Without<Camera>,
+    Without<EditorUiElement>,
+    Without<EditorUiCamera>,
+);
+
+/// Returns the root entities of the scene, see [`SceneRootFilter`].
+pub fn scene_roots(world: &mut World) -> Vec<Entity> {
+    world
+        .query_filtered::<Entity, SceneRootFilter>()
+        .iter(world)
+        .collect()
+}
+
+/// Returns the scene roots and all of their descendants.
+pub fn scene_entities(world: &mut World) -> Vec<Entity> {
+    let roots = scene_roots(world);
+    let mut children = world.query::<&Children>();
+    let children = children.query(world);
+    roots
+        .into_iter()
+        .flat_map(|root| std::iter::once(root).chain(children.iter_descendants(root)))
+        // Deleted entities are disabled until they are dropped from the history
+        .filter(|entity| world.get::<Disabled>(*entity).is_none())
+        .collect()
+}
+
+/// Builds a scene from the given entities, leaving out the components that Bevy computes.
+pub fn build_scene(world: &World, entities: &[Entity]) -> DynamicScene {
+    DynamicSceneBuilder::from_world(world)
+        .deny_component::<GlobalTransform>()
+        .deny_component::<InheritedVisibility>()
+        .deny_component::<ViewVisibility>()
+        .extract_entities(entities.iter().copied())
+        .build()
+}
+
+/// Returns the names of the components of the entities that aren't registered for reflection.
+/// [`build_scene`] leaves them out of the scene without an error.
+fn unreflected_components(world: &World, entities: &[Entity], registry: &TypeRegistry) -> BTreeSet<String> {
+    entities
+        .iter()
+        .flat_map(|entity| world.inspect_entity(*entity).into_iter().flatten())
+        .filter(|info| {
+            info.type_id()
+                .is_none_or(|type_id| registry.get_type_data::<ReflectComponent>(type_id).is_none())
+        })
+        .map(|info| info.name().to_string())
+        .collect()
+}
+
+/// Removes the components that can't be serialized from the scene and returns their type paths.
+/// Asset handles can't be serialized with reflection, so this includes components like Mesh3d and MeshMaterial3d,
+/// even when their asset was loaded from a file.
+fn retain_serializable(scene: &mut DynamicScene, registry: &TypeRegistry) -> BTreeSet<String> {
+    let mut skipped = BTreeSet::new();
+    for entity in &mut scene.entities {
+        entity.components.retain(|component| {
+            let serializable = serialize_ron(TypedReflectSerializer::new(component.as_ref(), registry)).is_ok();
+            if !serializable {
+                skipped.insert(component.reflect_type_path().to_string());
+            }
+            serializable
+        });
+    }
+    skipped
+}
+
+/// Writes the scene entities to a `.scn.ron` file and returns the names of the components that were skipped.
+/// Components that aren't reflected or can't be serialized are skipped with a warning, see [`retain_serializable`].
+/// Meshes and materials are therefore not saved yet, only the entities that use them.
+pub fn save_scene(world: &mut World, path: &Path) -> Result<BTreeSet<String>> {
+    let entities = scene_entities(world);
+    let mut scene = build_scene(world, &entities);
+
+    let registry = world.resource::<AppTypeRegistry>().read();
+    let mut skipped = unreflected_components(world, &entities, &registry);
+    skipped.extend(retain_serializable(&mut scene, &registry));
+    if !skipped.is_empty() {
+        warn!(
+            "Skipped components that can't be serialized while saving {}: {}",
+            path.display(),
+            skipped.iter().cloned().collect::<Vec<_>>().join(", ")
+        );
+    }
+    let serialized = scene.serialize(&registry)?;
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, serialized)?;
+    Ok(skipped)
+}
+
+/// Reads a scene from a `.scn.ron` file. The scene can be spawned with [`DynamicScene::write_to_world`].
+pub fn load_scene(world: &World, path: &Path) -> Result<DynamicScene> {
+    let serialized = fs::read_to_string(path)?;
+    let registry = world.resource::<AppTypeRegistry>().read();
+    let scene = ron::Options::default().from_str_seed(
+        &serialized,
+        SceneDeserializer {
+            type_registry: &registry,
+        },
+    )?;
+    Ok(scene)
+}
+
+/// Saves the current document to its file. Documents without a file are saved as a new file,
+/// so the file dialog is opened for them.
+fn handle_file_events(
+    mut commands: Commands,
+    mut ui_event_reader: MessageReader<UiEvent>,
+    mut save_scene_writer: MessageWriter<SaveScene>,
+    open_documents: Res<OpenDocuments>,
+    current_tab: Res<CurrentTab>,
+) {
+    for event in ui_event_reader.read() {
+        if let UiEvent::FileSave = event
+            && let Some(document) = current_tab.0.and_then(|index| open_documents.get(index))
+        {
+            match &document.path {
+                Some(path) => {
+                    save_scene_writer.write(SaveScene(path.clone()));
+                }
+                None => {
+                    commands.write_message(UiEvent::FileSaveAs);
+                }
+            }
+        }
+    }
+}
+
+/// Saves the scene and remembers the file in the document, so the next Save writes to the same file.
+fn save_scenes(mut commands: Commands, mut save_scene_reader: MessageReader<SaveScene>) {
+    for SaveScene(path) in save_scene_reader.read() {
+        let path = path.clone();
+        commands.queue(move |world: &mut World| {
+            if let Err(error) = save_scene(world, &path) {
+                error!("Failed to save scene to {}: {error}", path.display());
+                return;
+            }
+            info!("Saved scene to {}", path.display());
+
+            // The entities in the world belong to the spawned document, even if another tab was selected this frame
+            let mut open_documents = world.resource_mut::<OpenDocuments>();
+            if let Some(index) = open_documents.spawned.and_then(|id| open_documents.index_of(id))
+                && let Some(document) = open_documents.get_mut(index)
+            {
+                document.name = document_name(&path);
+                document.path = Some(path);
+            }
+        });
+    }
+}
+
+/// Loads the scene into a new document and selects its tab. The scene is spawned by swap_document_scenes.
+fn open_scenes(mut commands: Commands, mut open_scene_reader: MessageReader<OpenScene>) {
+    for OpenScene(path) in open_scene_reader.read() {
+        let path = path.clone();
+        commands.queue(move |world: &mut World| {
+            if let Some(index) = world.resource::<OpenDocuments>().index_of_path(&path) {
+                world.resource_mut::<CurrentTab>().0 = Some(index);
+                return;
+            }
+            match load_scene(world, &path) {
+                Ok(scene) => {
+                    let index = world
+                        .resource_mut::<OpenDocuments>()
+                        .add(document_name(&path), Some(path), Some(scene));
+                    world.resource_mut::<CurrentTab>().0 = Some(index);
+                }
+                Err(error) => error!("Failed to open scene {}: {error}", path.display()),
+            }
+        });
+    }
+}
+
+/// Replaces the scene entities in the world when another tab is selected.
+/// The entities of the previous document are kept in that document and the scene of the selected document is spawned.
+fn swap_document_scenes(world: &mut World) {
+    let open_documents = world.resource::<OpenDocuments>();
+    let selected = world
+        .resource::<CurrentTab>()
+        .0
+        .and_then(|index| open_documents.get(index))
+        .map(|document| document.id);
+    let spawned = open_documents.spawned;
+    if selected == spawned {
+        return;
+    }
+
+    // The history refers to entities that are about to be despawned
+    world.resource_scope(|world, mut history: Mut<EditorHistory>| history.clear(world));
+
+    let entities = scene_entities(world);
+    let scene = build_scene(world, &entities);
+    for root in scene_roots(world) {
+        world.despawn(root);
+    }
+
+    let mut open_documents = world.resource_mut::<OpenDocuments>();
+    // The previous document is gone if it was closed
+    if let Some(index) = spawned.and_then(|id| open_documents.index_of(id))
+        && let Some(document) = open_documents.get_mut(index)
+    {
+        document.scene = Some(scene);
+    }
+    let next_scene = selected
+        .and_then(|id| open_documents.index_of(id))
+        .and_then(|index| open_documents.get_mut(index))
+        .and_then(|document| document.scene.take());
+    open_documents.spawned = selected;
+
+    if let Some(next_scene) = next_scene
+        && let Err(error) = next_scene.write_to_world(world, &mut EntityHashMap::default())
+    {
+        error!("Failed to spawn the scene of the selected tab: {error}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::{query::Allow, system::RunSystemOnce};
+
+    use super::*;
+    use crate::ui::history::{DespawnCommand, EditorCommand};
+
+    /// App without a window or renderer, with the demo scene of the example spawned.
+    fn demo_scene_app() -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<Mesh>()
+            .init_asset::<StandardMaterial>()
+            .insert_resource(EditorHistory::new(10))
+            .init_resource::<OpenDocuments>()
+            .insert_resource(CurrentTab(Some(0)));
+        app.world_mut().run_system_once(crate::setup_scene).unwrap();
+        app
+    }
+
+    /// Returns the transforms of the scene roots, from bottom to top.
+    fn root_transforms(world: &mut World) -> Vec<Transform> {
+        let mut transforms: Vec<Transform> = scene_roots(world)
+            .into_iter()
+            .map(|root| *world.get::<Transform>(root).unwrap())
+            .collect();
+        transforms.sort_by(|a, b| a.translation.y.total_cmp(&b.translation.y));
+        transforms
+    }
+
+    /// Returns the type paths of the reflected components of the scene roots, from bottom to top.
+    fn root_components(world: &mut World) -> Vec<BTreeSet<String>> {
+        let mut roots = scene_roots(world);
+        roots.sort_by(|a, b| {
+            let y = |entity: &Entity| world.get::<Transform>(*entity).unwrap().translation.y;
+            y(a).total_cmp(&y(b))
+        });
+        let registry = world.resource::<AppTypeRegistry>().read();
+        roots
+            .into_iter()
+            .map(|root| {
+                world
+                    .inspect_entity(root)
+                    .unwrap()
+                    .filter_map(|info| registry.get(info.type_id()?))
+                    .map(|registration| registration.type_info().type_path().to_string())
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn camera_count(world: &mut World) -> usize {
+        world.query_filtered::<(), With<Camera3d>>().iter(world).count()
+    }
+
+    /// Saves the scene to a temporary file, despawns it and opens the file again.
+    fn save_and_open(world: &mut World, name: &str) -> BTreeSet<String> {
+        let path = std::env::temp_dir().join(format!("granite_{name}_{}.{SCENE_FILE_EXTENSION}", std::process::id()));
+        let skipped = save_scene(world, &path).unwrap();
+        for root in scene_roots(world) {
+            world.despawn(root);
+        }
+        let scene = load_scene(world, &path);
+        fs::remove_file(&path).unwrap();
+        scene.unwrap().write_to_world(world, &mut EntityHashMap::default()).unwrap();
+        skipped
+    }
+
+    #[test]
+    fn demo_scene_is_saved_and_opened_again() {
+        let mut app = demo_scene_app();
+        let world = app.world_mut();
+        let saved = root_transforms(world);
+        assert_eq!(saved.len(), 2, "the camera is not part of the scene");
+        let components = root_components(world);
+
+        let skipped = save_and_open(world, "demo");
+
+        // The meshes and materials of the demo scene are created in code, so their handles can't be saved
+        let handles = BTreeSet::from([
+            Mesh3d::type_path().to_string(),
+            MeshMaterial3d::<StandardMaterial>::type_path().to_string(),
+        ]);
+        assert_eq!(skipped, handles);
+        assert_eq!(root_transforms(world), saved);
+        let expected: Vec<BTreeSet<String>> = components.iter().map(|components| components - &skipped).collect();
+        assert_eq!(root_components(world), expected);
+        assert_eq!(camera_count(world), 1);
+    }
+
+    #[test]
+    fn deleted_entities_are_not_saved() {
+        let mut app = demo_scene_app();
+        let world = app.world_mut();
+        let root = scene_roots(world)[0];
+        world.spawn((Name::new("Kept"), Transform::default(), ChildOf(root)));
+        let deleted = world.spawn((Name::new("Deleted"), Transform::default(), ChildOf(root))).id();
+        let mut despawn = DespawnCommand { entities: vec![deleted] };
+        despawn.apply(world);
+        world.resource_scope(|world, mut history: Mut<EditorHistory>| history.push(world, Box::new(despawn)));
+        assert!(world.get::<Disabled>(deleted).is_some());
+
+        save_and_open(world, "deleted");
+
+        // Deleted entities would come back disabled, so they are looked for as well
+        let mut names = world.query_filtered::<&Name, Allow<Disabled>>();
+        let names: Vec<&str> = names.iter(world).map(Name::as_str).collect();
+        assert_eq!(names, ["Kept"]);
+    }
+
+    #[test]
+    fn camera_is_kept_when_switching_documents() {
+        let mut app = demo_scene_app();
+        let world = app.world_mut();
+        let scene = root_transforms(world);
+
+        let index = world.resource_mut::<OpenDocuments>().add_untitled();
+        world.resource_mut::<CurrentTab>().0 = Some(index);
+        swap_document_scenes(world);
+        assert!(root_transforms(world).is_empty());
+        assert_eq!(camera_count(world), 1);
+
+        world.resource_mut::<CurrentTab>().0 = Some(0);
+        swap_document_scenes(world);
+        assert_eq!(root_transforms(world), scene);
+        assert_eq!(camera_count(world), 1);
+    }
+}
@@ -0,0 +1,102 @@
+use bevy::{
+    camera::{primitives::Aabb, visibility::RenderLayers},
+    prelude::*,
+    window::PrimaryWindow,
+};
+
+use crate::ui::{
+    CurrentTool, EditorRenderLayer, EditorUiCamera, EditorUiElement, Tool, elements::CameraPreview, viewport_cursor_ray,
+};
+
+pub struct SelectionPlugin;
+
+impl Plugin for SelectionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, select_on_click)
+            .add_systems(Update, draw_selection);
+    }
+}
+
+/// Marks a scene entity as selected in the editor.
+#[derive(Component, Default, Clone, Copy)]
+pub struct Selected;
+
+/// Selects the entity under the cursor when clicking in the CameraPreview with the pointer tool.
+/// Shift-click adds or removes the entity from the selection, clicking on empty space clears the selection.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn select_on_click(
+    mut commands: Commands,
+    current_tool: Res<CurrentTool>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    camera_preview: Single<&Interaction, With<CameraPreview>>,
+    cameras: Query<(&Camera, &GlobalTransform), (With<Camera3d>, Without<EditorUiCamera>)>,
+    editor_render_layer: Res<EditorRenderLayer>,
+    editor_ui_elements: Query<(), With<EditorUiElement>>,
+    render_layers: Query<&RenderLayers>,
+    selected: Query<Entity, With<Selected>>,
+    mut mesh_ray_cast: MeshRayCast,
+) {
+    if current_tool.0 != Tool::Pointer
+        || !mouse_buttons.just_pressed(MouseButton::Left)
+        // The cursor is over another UI element, e.g. an open menu
+        || **camera_preview == Interaction::None
+    {
+        return;
+    }
+    let Some((_, ray)) = viewport_cursor_ray(&window, cameras.iter()) else {
+        return;
+    };
+
+    let editor_layers = RenderLayers::layer(editor_render_layer.0);
+    let filter = |entity: Entity| {
+        !editor_ui_elements.contains(entity)
+            && render_layers
+                .get(entity)
+                .map_or(true, |layers| !layers.intersects(&editor_layers))
+    };
+    let settings = MeshRayCastSettings::default().with_filter(&filter);
+    let hit = mesh_ray_cast
+        .cast_ray(ray, &settings)
+        .first()
+        .map(|(entity, _)| *entity);
+
+    let multi_select = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    set_selection(&mut commands, &selected, hit, multi_select);
+}
+
+/// Selects the entity, or clears the selection when the entity is None.
+/// With multi_select, the entity is added to or removed from the selection instead.
+pub fn set_selection(
+    commands: &mut Commands,
+    selected: &Query<Entity, With<Selected>>,
+    entity: Option<Entity>,
+    multi_select: bool,
+) {
+    if !multi_select {
+        for selected_entity in selected.iter().filter(|selected_entity| Some(*selected_entity) != entity) {
+            commands.entity(selected_entity).remove::<Selected>();
+        }
+    }
+    if let Some(entity) = entity {
+        if multi_select && selected.contains(entity) {
+            commands.entity(entity).remove::<Selected>();
+        } else {
+            commands.entity(entity).insert(Selected);
+        }
+    }
+}
+
+/// Draws the bounding box of the selected entities.
+fn draw_selection(
+    mut gizmos: Gizmos,
+    selected: Query<(&GlobalTransform, &Aabb), With<Selected>>,
+) {
+    for (global_transform, aabb) in selected.iter() {
+        let transform = global_transform.mul_transform(
+            Transform::from_translation(aabb.center.into()).with_scale((aabb.half_extents * 2.0).into()),
+        );
+        gizmos.cuboid(transform, Color::srgb(1.0, 0.6, 0.1));
+    }
+}
@@ -0,0 +1,403 @@
+use bevy::{prelude::*, ui::FocusPolicy};
+
+use crate::ui::{
+    ClickAction, EditorUiElement, UiEvent,
+    colors::{EditorBackgroundColor, EditorColor, EditorTextColor},
+    command_registry::CommandRegistry,
+    elements::{EditorUi, Modal, ScrollView, reactive_element},
+    reactive::Reconcile,
+    shortcuts::{
+        CHORD_TIMEOUT, Chord, RecordingShortcut, Shortcut, ShortcutScope, Shortcuts, ShortcutsFile, handle_shortcuts,
+        save_shortcut_config,
+    },
+    style::{
+        EditorBorderRadius, EditorFontSize, EditorGap, EditorHeight, EditorPadding, EditorWidth, FontSize, Height, Radius,
+        Spacing, Width,
+    },
+};
+
+/// Maximum number of chords of a recorded shortcut.
+const MAX_CHORDS: usize = 2;
+
+pub struct ShortcutEditorPlugin;
+
+impl Plugin for ShortcutEditorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, open_shortcut_editor)
+            .add_systems(Update, handle_shortcut_editor_events.after(open_shortcut_editor))
+            .add_systems(Update, record_shortcut.after(handle_shortcuts))
+            .add_systems(
+                Update,
+                update_shortcut_editor
+                    .after(handle_shortcut_editor_events)
+                    .after(record_shortcut),
+            )
+            .add_systems(PostUpdate, reactive_shortcut_editor);
+    }
+}
+
+/// Actions of the buttons in the shortcut editor. Rows are referred to by their index.
+#[derive(Reflect, Hash, Eq, PartialEq, Clone, Copy, Debug)]
+pub enum ShortcutEditorAction {
+    Open,
+    /// Records the next key combination that is pressed as the shortcut of the row.
+    Record(usize),
+    /// Removes the shortcut of the row.
+    Clear(usize),
+    ResetAll,
+    Save,
+    Close,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct ShortcutEditorRow {
+    pub event: UiEvent,
+    pub label: String,
+    pub shortcut: Option<String>,
+    /// Labels of the other events with the same shortcut.
+    pub conflicts: Vec<String>,
+}
+
+/// Modal dialog that lists the shortcuts and lets them be changed and saved to the shortcuts file.
+#[derive(Component, Clone, PartialEq, Default)]
+#[require(Modal, ShortcutScope::ShortcutEditor)]
+pub struct ShortcutEditor {
+    pub rows: Vec<ShortcutEditorRow>,
+    /// The row whose shortcut is being recorded.
+    pub recording: Option<usize>,
+    /// The chords that were recorded so far.
+    pub recorded: Vec<Chord>,
+    pub message: Option<String>,
+}
+
+/// Opens the shortcut editor, unless it is already open.
+fn open_shortcut_editor(
+    mut commands: Commands,
+    mut ui_event_reader: MessageReader<UiEvent>,
+    editor_ui: Single<Entity, With<EditorUi>>,
+    shortcut_editors: Query<(), With<ShortcutEditor>>,
+) {
+    for event in ui_event_reader.read() {
+        if let UiEvent::ShortcutEditor(ShortcutEditorAction::Open) = event
+            && shortcut_editors.is_empty()
+        {
+            commands.entity(*editor_ui).with_child(ShortcutEditor::default());
+        }
+    }
+}
+
+fn handle_shortcut_editor_events(
+    mut commands: Commands,
+    mut ui_event_reader: MessageReader<UiEvent>,
+    mut shortcut_editors: Query<(Entity, &mut ShortcutEditor)>,
+    mut shortcuts: ResMut<Shortcuts>,
+    mut recording_shortcut: ResMut<RecordingShortcut>,
+    shortcuts_file: Res<ShortcutsFile>,
+    registry: Res<AppTypeRegistry>,
+) {
+    for event in ui_event_reader.read() {
+        let UiEvent::ShortcutEditor(action) = event else {
+            continue;
+        };
+        let Ok((entity, mut shortcut_editor)) = shortcut_editors.single_mut() else {
+            continue;
+        };
+        shortcut_editor.recording = None;
+        shortcut_editor.recorded.clear();
+        recording_shortcut.0 = None;
+
+        match *action {
+            ShortcutEditorAction::Open => {}
+            ShortcutEditorAction::Record(index) => {
+                if let Some(row) = shortcut_editor.rows.get(index) {
+                    recording_shortcut.0 = Some(row.event.clone());
+                    shortcut_editor.recording = Some(index);
+                    shortcut_editor.message = Some("Press the new shortcut, optionally followed by a second chord, or Escape to cancel".to_string());
+                }
+            }
+            ShortcutEditorAction::Clear(index) => {
+                if let Some(row) = shortcut_editor.rows.get(index) {
+                    shortcuts.set_shortcut(row.event.clone(), None);
+                    shortcut_editor.message = None;
+                }
+            }
+            ShortcutEditorAction::ResetAll => {
+                shortcuts.reset();
+                shortcut_editor.message = None;
+            }
+            ShortcutEditorAction::Save => {
+                let result = save_shortcut_config(&shortcuts_file.0, &shortcuts.to_config(), &registry.read());
+                shortcut_editor.message = Some(match result {
+                    Ok(()) => format!("Saved to {}", shortcuts_file.0.display()),
+                    Err(error) => format!("Failed to save: {error}"),
+                });
+            }
+            ShortcutEditorAction::Close => {
+                commands.entity(entity).despawn();
+            }
+        }
+    }
+}
+
+/// Records the chords that are pressed as the new shortcut. Modifier keys are added to the key that is pressed
+/// while they are held. The shortcut is done after [`MAX_CHORDS`] chords, or when no chord is pressed within
+/// [`CHORD_TIMEOUT`]. Escape cancels.
+fn record_shortcut(
+    keys: Res<ButtonInput<KeyCode>>,
+    time: Res<Time<Real>>,
+    mut shortcuts: ResMut<Shortcuts>,
+    registry: Res<CommandRegistry>,
+    mut recording_shortcut: ResMut<RecordingShortcut>,
+    mut shortcut_editors: Query<&mut ShortcutEditor>,
+    mut pressed_at: Local<f32>,
+) {
+    let Some(event) = recording_shortcut.0.clone() else {
+        return;
+    };
+    let Ok(mut shortcut_editor) = shortcut_editors.single_mut() else {
+        recording_shortcut.0 = None;
+        return;
+    };
+    let now = time.elapsed_secs();
+    match Chord::just_pressed(&keys) {
+        Some(chord) if chord.key == KeyCode::Escape => {
+            recording_shortcut.0 = None;
+            shortcut_editor.recording = None;
+            shortcut_editor.recorded.clear();
+            shortcut_editor.message = None;
+            return;
+        }
+        Some(chord) => {
+            shortcut_editor.recorded.push(chord);
+            *pressed_at = now;
+            if shortcut_editor.recorded.len() < MAX_CHORDS {
+                return;
+            }
+        }
+        None if !shortcut_editor.recorded.is_empty() && now - *pressed_at > CHORD_TIMEOUT => {}
+        None => return,
+    }
+
+    recording_shortcut.0 = None;
+    shortcut_editor.recording = None;
+    let shortcut = Shortcut {
+        chords: std::mem::take(&mut shortcut_editor.recorded),
+    };
+    shortcuts.set_shortcut(event.clone(), Some(shortcut.clone()));
+
+    let conflicts = shortcuts.conflicts(&event);
+    shortcut_editor.message = (!conflicts.is_empty()).then(|| {
+        let labels: Vec<String> = conflicts.iter().map(|event| registry.event_label(event)).collect();
+        format!("{shortcut} overlaps the shortcut of {}", labels.join(", "))
+    });
+}
+
+/// Lists the shortcuts in the shortcut editor.
+fn update_shortcut_editor(
+    shortcuts: Res<Shortcuts>,
+    registry: Res<CommandRegistry>,
+    mut shortcut_editors: Query<&mut ShortcutEditor>,
+) {
+    for mut shortcut_editor in shortcut_editors.iter_mut() {
+        let rows: Vec<ShortcutEditorRow> = shortcuts
+            .bindable_events()
+            .into_iter()
+            .map(|event| ShortcutEditorRow {
+                label: match shortcuts.get_scope(&event) {
+                    ShortcutScope::Global => registry.event_label(&event),
+                    scope => format!("{} ({})", registry.event_label(&event), scope.label()),
+                },
+                shortcut: shortcuts.get_shortcut(&event).map(|shortcut| shortcut.to_string()),
+                conflicts: shortcuts.conflicts(&event).iter().map(|event| registry.event_label(event)).collect(),
+                event,
+            })
+            .collect();
+        if shortcut_editor.rows != rows {
+            shortcut_editor.rows = rows;
+        }
+    }
+}
+
+fn shortcut_editor_button(text: impl Into<String>, action: ShortcutEditorAction) -> impl Bundle {
+    (
+        EditorUiElement,
+        Button,
+        ClickAction(UiEvent::ShortcutEditor(action)),
+        EditorPadding::axes(Spacing::Md, Spacing::Sm),
+        EditorBorderRadius::all(Radius::Md),
+        EditorBackgroundColor(EditorColor::Button, Some(EditorColor::MenuBarButtonHover), None),
+        children![(
+            EditorUiElement,
+            Text::new(text.into()),
+            EditorTextColor(EditorColor::Text, None, None),
+            EditorFontSize(FontSize::Body)
+        )],
+    )
+}
+
+reactive_element!(ShortcutEditor, reactive_shortcut_editor, |shortcut_editor: &ShortcutEditor| {
+    let recording = shortcut_editor.recording;
+    let recorded = Shortcut {
+        chords: shortcut_editor.recorded.clone(),
+    };
+    let rows = shortcut_editor.rows.clone().into_iter().enumerate().map(move |(index, row)| {
+        let is_recording = recording == Some(index);
+        let shortcut_text = if is_recording && recorded.chords.is_empty() {
+            "Press keys...".to_string()
+        } else if is_recording {
+            format!("{recorded} ...")
+        } else {
+            row.shortcut.clone().unwrap_or_else(|| "None".to_string())
+        };
+        (
+            EditorUiElement,
+            Node {
+                display: Display::Flex,
+                flex_shrink: 0.0,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            EditorGap(Spacing::Sm),
+            EditorPadding::axes(Spacing::Md, Spacing::Xs),
+            children![
+                (
+                    EditorUiElement,
+                    Node {
+                        flex_grow: 1.0,
+                        ..default()
+                    },
+                    Text::new(row.label),
+                    EditorTextColor(EditorColor::Text, None, None),
+                    EditorFontSize(FontSize::Body)
+                ),
+                (
+                    EditorUiElement,
+                    Button,
+                    ClickAction(UiEvent::ShortcutEditor(ShortcutEditorAction::Record(index))),
+                    EditorWidth(Width::ShortcutButton),
+                    EditorPadding::axes(Spacing::Md, Spacing::Xs),
+                    EditorBorderRadius::all(Radius::Sm),
+                    EditorBackgroundColor(
+                        if is_recording { EditorColor::MenuBarButtonHover } else { EditorColor::Button },
+                        Some(EditorColor::MenuBarButtonHover),
+                        None,
+                    ),
+                    children![(
+                        EditorUiElement,
+                        Text::new(shortcut_text),
+                        EditorTextColor(
+                            if !row.conflicts.is_empty() {
+                                EditorColor::Error
+                            } else if row.shortcut.is_some() || is_recording {
+                                EditorColor::Text
+                            } else {
+                                EditorColor::FadedText
+                            },
+                            None,
+                            None,
+                        ),
+                        EditorFontSize(FontSize::Body)
+                    )],
+                ),
+                (
+                    EditorUiElement,
+                    Button,
+                    ClickAction(UiEvent::ShortcutEditor(ShortcutEditorAction::Clear(index))),
+                    EditorPadding::axes(Spacing::Sm, Spacing::Xs),
+                    EditorBorderRadius::all(Radius::Sm),
+                    EditorBackgroundColor(EditorColor::Background, Some(EditorColor::MenuBarButtonHover), None),
+                    children![(
+                        EditorUiElement,
+                        Text::new("x"),
+                        EditorTextColor(EditorColor::FadedText, None, None),
+                        EditorFontSize(FontSize::Body)
+                    )],
+                ),
+            ],
+        )
+    });
+
+    (
+        EditorUiElement,
+        // Covers the editor so the rest of the UI can't be used while the dialog is open
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        FocusPolicy::Block,
+        GlobalZIndex(10000),
+        EditorBackgroundColor(EditorColor::Overlay, None, None),
+        children![(
+            EditorUiElement,
+            Node {
+                display: Display::Flex,
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            EditorWidth(Width::ShortcutEditor),
+            EditorGap(Spacing::Md),
+            EditorPadding::all(Spacing::Lg),
+            EditorBorderRadius::all(Radius::Lg),
+            EditorBackgroundColor(EditorColor::Background, None, None),
+            children![
+                (
+                    EditorUiElement,
+                    Text::new("Keyboard Shortcuts"),
+                    EditorTextColor(EditorColor::Text, None, None),
+                    EditorFontSize(FontSize::Title)
+                ),
+                (
+                    EditorUiElement,
+                    ScrollView,
+                    Node {
+                        display: Display::Flex,
+                        flex_direction: FlexDirection::Column,
+                        overflow: Overflow::scroll_y(),
+                        ..default()
+                    },
+                    EditorHeight(Height::ShortcutList),
+                    EditorGap(Spacing::Xs),
+                    EditorPadding::all(Spacing::Sm),
+                    EditorBorderRadius::all(Radius::Md),
+                    EditorBackgroundColor(EditorColor::Button, None, None),
+                    Children::spawn(SpawnIter(rows)),
+                ),
+                (
+                    EditorUiElement,
+                    Node {
+                        display: if shortcut_editor.message.is_some() { Display::Flex } else { Display::None },
+                        ..default()
+                    },
+                    Text::new(shortcut_editor.message.clone().unwrap_or_default()),
+                    EditorTextColor(EditorColor::FadedText, None, None),
+                    EditorFontSize(FontSize::Body)
+                ),
+                (
+                    EditorUiElement,
+                    Node {
+                        display: Display::Flex,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    EditorGap(Spacing::Md),
+                    children![
+                        shortcut_editor_button("Reset All", ShortcutEditorAction::ResetAll),
+                        (
+                            EditorUiElement,
+                            Node {
+                                flex_grow: 1.0,
+                                ..default()
+                            },
+                        ),
+                        shortcut_editor_button("Close", ShortcutEditorAction::Close),
+                        shortcut_editor_button("Save", ShortcutEditorAction::Save),
+                    ],
+                ),
+            ],
+        )],
+    )
+});
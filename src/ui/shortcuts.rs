@@ -1,113 +1,943 @@
-use std::fmt::{Display, Formatter};
+use std::{
+    any::TypeId,
+    fmt::{Display, Formatter},
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
-use bevy::{platform::collections::HashMap, prelude::*};
-use smallvec::{SmallVec, smallvec};
+use bevy::{
+    asset::io::file::FileAssetReader,
+    platform::collections::HashMap,
+    prelude::*,
+    reflect::{
+        DynamicEnum, DynamicVariant, TypeInfo, TypeRegistry, Typed,
+        serde::{TypedReflectDeserializer, TypedReflectSerializer},
+    },
+    scene::ron,
+    window::PrimaryWindow,
+};
 
-use crate::ui::UiEvent;
+use crate::ui::{
+    Tool, UiEvent,
+    command_palette::CommandPaletteAction,
+    elements::{Modal, StatusBar},
+    entity_actions::EntityAction,
+    hierarchy::HierarchyAction,
+    menu::MenuNavigation,
+    shortcut_editor::ShortcutEditorAction,
+    text_input::TextInputFocus,
+};
+
+/// Time in seconds in which the next chord of a multi-chord shortcut has to be pressed.
+pub const CHORD_TIMEOUT: f32 = 1.5;
 
 pub struct ShortcutsPlugin;
 
 impl Plugin for ShortcutsPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(Shortcuts::default())
-            .add_systems(Update, handle_shortcuts);
+        app.init_resource::<Shortcuts>()
+            .init_resource::<ShortcutsFile>()
+            .init_resource::<RecordingShortcut>()
+            .register_type::<ShortcutConfig>()
+            .add_systems(PreStartup, load_shortcuts_file)
+            .init_resource::<PendingChords>()
+            .init_resource::<ShortcutScopes>()
+            .register_type::<ShortcutScope>()
+            .add_systems(Update, update_shortcut_scopes)
+            .add_systems(Update, handle_shortcuts.after(update_shortcut_scopes))
+            .add_systems(
+                Update,
+                update_pending_chords_status
+                    .after(handle_shortcuts)
+                    .run_if(resource_changed::<PendingChords>),
+            );
     }
 }
 
 #[derive(Resource, Debug)]
 pub struct Shortcuts {
-    pub shortcuts: HashMap<UiEvent, Shortcut>
+    pub shortcuts: HashMap<UiEvent, Shortcut>,
+    /// The shortcuts before they were changed by the user, including those of registered commands.
+    pub defaults: HashMap<UiEvent, Shortcut>,
+    /// The scope in which the shortcut of an event applies. Events that aren't in here are global.
+    pub scopes: HashMap<UiEvent, ShortcutScope>,
 }
 
 impl Shortcuts {
     pub fn get_shortcut(&self, event: &UiEvent) -> Option<Shortcut> {
         self.shortcuts.get(event).cloned()
     }
+
+    /// Binds the event to the shortcut, or removes its shortcut if None.
+    pub fn set_shortcut(&mut self, event: UiEvent, shortcut: Option<Shortcut>) {
+        match shortcut {
+            Some(shortcut) => {
+                self.shortcuts.insert(event, shortcut);
+            }
+            None => {
+                self.shortcuts.remove(&event);
+            }
+        }
+    }
+
+    /// Adds a default shortcut, which is also bound unless the event already has a shortcut.
+    pub fn add_default(&mut self, event: UiEvent, shortcut: Shortcut) {
+        self.shortcuts.entry(event.clone()).or_insert_with(|| shortcut.clone());
+        self.defaults.insert(event, shortcut);
+    }
+
+    /// Binds the default shortcuts again.
+    pub fn reset(&mut self) {
+        self.shortcuts = self.defaults.clone();
+    }
+
+    pub fn get_scope(&self, event: &UiEvent) -> ShortcutScope {
+        self.scopes.get(event).cloned().unwrap_or_default()
+    }
+
+    /// Makes the shortcut of the event only apply in the given scope.
+    pub fn set_scope(&mut self, event: UiEvent, scope: ShortcutScope) {
+        self.scopes.insert(event, scope);
+    }
+
+    /// Returns the other events in the same scope whose shortcut overlaps the shortcut of the given event,
+    /// see [`Shortcut::overlaps`]. A shortcut in an inner scope overriding one in an outer scope is not a conflict.
+    pub fn conflicts(&self, event: &UiEvent) -> Vec<UiEvent> {
+        let Some(shortcut) = self.shortcuts.get(event) else {
+            return Vec::new();
+        };
+        let scope = self.get_scope(event);
+        let mut conflicts: Vec<UiEvent> = self
+            .shortcuts
+            .iter()
+            .filter(|(other_event, other_shortcut)| {
+                *other_event != event && self.get_scope(other_event) == scope && other_shortcut.overlaps(shortcut)
+            })
+            .map(|(other_event, _)| other_event.clone())
+            .collect();
+        conflicts.sort_by_key(event_label);
+        conflicts
+    }
+
+    /// Returns the events that can be bound, which are the events with a default shortcut and the events
+    /// that were bound in the shortcuts file, sorted by their label.
+    pub fn bindable_events(&self) -> Vec<UiEvent> {
+        let mut events: Vec<UiEvent> = self.defaults.keys().cloned().collect();
+        for event in self.shortcuts.keys() {
+            if !events.contains(event) {
+                events.push(event.clone());
+            }
+        }
+        events.sort_by_key(event_label);
+        events
+    }
+
+    /// Applies the bindings of a shortcuts file over the current shortcuts.
+    pub fn apply_config(&mut self, config: &ShortcutConfig) {
+        for binding in &config.bindings {
+            match binding.shortcut.as_deref().map(Shortcut::from_str).transpose() {
+                Ok(shortcut) => self.set_shortcut(binding.event.clone(), shortcut),
+                Err(error) => warn!("Skipped the shortcut of {}: {error}", event_label(&binding.event)),
+            }
+        }
+    }
+
+    /// Returns the bindings that differ from the defaults, which is what the shortcuts file stores.
+    /// New default shortcuts therefore still apply to users that changed other shortcuts.
+    pub fn to_config(&self) -> ShortcutConfig {
+        let mut bindings: Vec<ShortcutBinding> = self
+            .bindable_events()
+            .into_iter()
+            .filter_map(|event| {
+                let shortcut = self.shortcuts.get(&event);
+                let default = self.defaults.get(&event);
+                (shortcut != default).then(|| ShortcutBinding {
+                    shortcut: shortcut.map(Shortcut::to_string),
+                    event,
+                })
+            })
+            .collect();
+        bindings.sort_by_key(|binding| event_label(&binding.event));
+        ShortcutConfig { bindings }
+    }
+}
+
+/// The shortcuts file, which is loaded at startup and written when the shortcuts are saved in the editor.
+/// Defaults to `shortcuts.ron` next to the asset folder, insert the resource to use another file.
+#[derive(Resource, Clone, Debug)]
+pub struct ShortcutsFile(pub PathBuf);
+
+impl Default for ShortcutsFile {
+    fn default() -> Self {
+        Self(FileAssetReader::get_base_path().join("shortcuts.ron"))
+    }
+}
+
+/// The event whose shortcut is being recorded. No shortcuts are handled while recording.
+#[derive(Resource, Default, Debug)]
+pub struct RecordingShortcut(pub Option<UiEvent>);
+
+/// Contents of the shortcuts file, the bindings that differ from the default shortcuts.
+#[derive(Reflect, Default, Debug)]
+pub struct ShortcutConfig {
+    pub bindings: Vec<ShortcutBinding>,
+}
+
+/// A shortcut in the shortcuts file, written like it is displayed, e.g. `Some("Ctrl+Shift+S")` or
+/// `Some("Ctrl+K Ctrl+S")`.
+/// Removed shortcuts are None.
+#[derive(Reflect, Debug)]
+pub struct ShortcutBinding {
+    pub event: UiEvent,
+    pub shortcut: Option<String>,
+}
+
+/// Returns a readable name for an event, e.g. "File Save As" for `UiEvent::FileSaveAs`
+/// and "Select Tool Move" for `UiEvent::SelectTool(Tool::Move)`. Registered commands are named by their id,
+/// see [`CommandRegistry::event_label`](crate::ui::command_registry::CommandRegistry::event_label) for their label.
+pub fn event_label(event: &UiEvent) -> String {
+    match event {
+        UiEvent::Command(id) => id.to_string(),
+        event => split_words(&format!("{event:?}")),
+    }
+}
+
+/// Puts spaces between the words of a camel case name and replaces the parentheses of tuple variants.
+pub fn split_words(name: &str) -> String {
+    let mut label = String::with_capacity(name.len() + 4);
+    let mut previous: Option<char> = None;
+    for character in name.chars() {
+        match character {
+            '(' => label.push(' '),
+            ')' => {}
+            _ => {
+                if character.is_uppercase() && previous.is_some_and(char::is_lowercase) {
+                    label.push(' ');
+                }
+                label.push(character);
+            }
+        }
+        previous = Some(character);
+    }
+    label
+}
+
+/// Reads a shortcuts file.
+pub fn load_shortcut_config(path: &Path, registry: &TypeRegistry) -> Result<ShortcutConfig> {
+    let serialized = fs::read_to_string(path)?;
+    let registration = registry
+        .get(TypeId::of::<ShortcutConfig>())
+        .ok_or("ShortcutConfig is not registered")?;
+    let value = ron::Options::default()
+        .from_str_seed(&serialized, TypedReflectDeserializer::new(registration, registry))?;
+    let config = ShortcutConfig::from_reflect(value.as_ref()).ok_or("invalid shortcuts file")?;
+    Ok(config)
+}
+
+/// Writes a shortcuts file.
+pub fn save_shortcut_config(path: &Path, config: &ShortcutConfig, registry: &TypeRegistry) -> Result {
+    let serialized = ron::ser::to_string_pretty(
+        &TypedReflectSerializer::new(config, registry),
+        ron::ser::PrettyConfig::default(),
+    )?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, serialized)?;
+    Ok(())
 }
 
+/// Applies the shortcuts file over the default shortcuts, if the file exists.
+fn load_shortcuts_file(
+    mut shortcuts: ResMut<Shortcuts>,
+    shortcuts_file: Res<ShortcutsFile>,
+    registry: Res<AppTypeRegistry>,
+) {
+    if !shortcuts_file.0.exists() {
+        return;
+    }
+    match load_shortcut_config(&shortcuts_file.0, &registry.read()) {
+        Ok(config) => {
+            shortcuts.apply_config(&config);
+            for event in shortcuts.bindable_events() {
+                for other_event in shortcuts.conflicts(&event) {
+                    // Each conflict is found from both sides, only warn once
+                    if event_label(&event) < event_label(&other_event) {
+                        warn!(
+                            "{} and {} have the same shortcut",
+                            event_label(&event),
+                            event_label(&other_event)
+                        );
+                    }
+                }
+            }
+        }
+        Err(error) => warn!("Failed to load shortcuts from {}: {error}", shortcuts_file.0.display()),
+    }
+}
+
+/// Context in which a shortcut applies, so the same keys can do different things in e.g. the viewport
+/// and the hierarchy panel.
+///
+/// An element with this component makes its scope active while the cursor is over it or a text input in it
+/// has focus, see [`ShortcutScopes`]. Plugins can add their own scopes with `Custom`.
+#[derive(Component, Reflect, Hash, Eq, PartialEq, Clone, Debug, Default)]
+#[reflect(Component)]
+pub enum ShortcutScope {
+    #[default]
+    Global,
+    Viewport,
+    Hierarchy,
+    Inspector,
+    FileDialog,
+    ShortcutEditor,
+    CommandPalette,
+    Custom(String),
+}
+
+impl ShortcutScope {
+    pub fn label(&self) -> String {
+        match self {
+            ShortcutScope::Custom(name) => name.clone(),
+            scope => split_words(&format!("{scope:?}")),
+        }
+    }
+}
+
+/// The active shortcut scopes, from the outermost to the innermost. Global is always the first.
+/// The shortcuts of inner scopes win over those of outer scopes.
+#[derive(Resource, Debug)]
+pub struct ShortcutScopes(pub Vec<ShortcutScope>);
+
+impl Default for ShortcutScopes {
+    fn default() -> Self {
+        Self(vec![ShortcutScope::Global])
+    }
+}
+
+impl ShortcutScopes {
+    /// Returns how deep the scope is in the stack, or None if it isn't active.
+    pub fn depth(&self, scope: &ShortcutScope) -> Option<usize> {
+        self.0.iter().rposition(|active| active == scope)
+    }
+}
+
+/// Set of modifier keys. The left and right keys of a modifier are the same modifier.
+#[derive(Hash, Eq, PartialEq, Clone, Copy, Default, Debug)]
+pub struct Modifiers(u8);
+
+impl Modifiers {
+    pub const NONE: Self = Self(0);
+    pub const CONTROL: Self = Self(1);
+    pub const SHIFT: Self = Self(1 << 1);
+    pub const ALT: Self = Self(1 << 2);
+    pub const SUPER: Self = Self(1 << 3);
+    /// The modifier of most shortcuts, Cmd on macOS and Ctrl elsewhere.
+    pub const PRIMARY: Self = if cfg!(target_os = "macos") { Self::SUPER } else { Self::CONTROL };
+
+    /// The modifiers in the order they are displayed, with their keys.
+    const KEYS: [(Modifiers, [KeyCode; 2]); 4] = [
+        (Self::CONTROL, [KeyCode::ControlLeft, KeyCode::ControlRight]),
+        (Self::SHIFT, [KeyCode::ShiftLeft, KeyCode::ShiftRight]),
+        (Self::ALT, [KeyCode::AltLeft, KeyCode::AltRight]),
+        (Self::SUPER, [KeyCode::SuperLeft, KeyCode::SuperRight]),
+    ];
+
+    pub const fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Returns the modifiers that are held down.
+    pub fn pressed(keys: &ButtonInput<KeyCode>) -> Self {
+        Self::KEYS
+            .iter()
+            .filter(|(_, modifier_keys)| keys.any_pressed(*modifier_keys))
+            .fold(Self::NONE, |modifiers, (modifier, _)| modifiers.union(*modifier))
+    }
+
+    /// Returns true if the key is one of the modifier keys.
+    pub fn is_modifier_key(key: KeyCode) -> bool {
+        Self::KEYS.iter().any(|(_, modifier_keys)| modifier_keys.contains(&key))
+    }
+
+    fn names(self) -> impl Iterator<Item = &'static str> {
+        Self::KEYS
+            .iter()
+            .filter(move |(modifier, _)| self.contains(*modifier))
+            .map(|(modifier, _)| match *modifier {
+                Self::CONTROL => "Ctrl",
+                Self::SHIFT => "Shift",
+                Self::ALT => "Alt",
+                _ if cfg!(target_os = "macos") => "Cmd",
+                _ if cfg!(target_os = "windows") => "Win",
+                _ => "Super",
+            })
+    }
+}
+
+impl std::ops::BitOr for Modifiers {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        self.union(other)
+    }
+}
+
+/// A key that is pressed while exactly the given modifiers are held.
+#[derive(Hash, Eq, PartialEq, Clone, Copy, Debug)]
+pub struct Chord {
+    pub modifiers: Modifiers,
+    pub key: KeyCode,
+}
+
+/// A sequence of chords that are pressed one after another, e.g. Ctrl+K Ctrl+S.
+/// Most shortcuts are a single chord.
 #[derive(Hash, Eq, PartialEq, Clone, Debug)]
 pub struct Shortcut {
-    pub keys: SmallVec<[KeyCode; 3]>,
+    pub chords: Vec<Chord>,
 }
 
 fn format_keycode(keycode: KeyCode) -> String {
-    match keycode {
-        KeyCode::ControlLeft => "Ctrl".to_string(),
-        KeyCode::ShiftLeft => "Shift".to_string(),
-        KeyCode::AltLeft => "Alt".to_string(),
-        KeyCode::SuperLeft => {
-            if cfg!(target_os = "windows") {
-                "Win".to_string()
-            } else {
-                "Cmd".to_string()
-            }
-        },
-        _ => {
-            let formatted = format!("{:?}", keycode);
-            // if starts with "Key", remove the "Key"
-            if formatted.starts_with("Key") {
-                formatted[3..].to_string()
-            } else {
-                formatted
-            }
+    let formatted = format!("{:?}", keycode);
+    // KeyA and Digit1 are shown as A and 1
+    match formatted.strip_prefix("Key").or_else(|| formatted.strip_prefix("Digit")) {
+        Some(key) => key.to_string(),
+        None => formatted,
+    }
+}
+
+/// Finds the key with the given name, the inverse of format_keycode.
+pub fn parse_keycode(name: &str) -> Option<KeyCode> {
+    let TypeInfo::Enum(enum_info) = KeyCode::type_info() else {
+        return None;
+    };
+    [format!("Key{name}"), format!("Digit{name}"), name.to_string()].into_iter().find_map(|variant| {
+        enum_info.variant(&variant)?;
+        KeyCode::from_reflect(&DynamicEnum::new(variant, DynamicVariant::Unit))
+    })
+}
+
+impl Chord {
+    pub const fn new(modifiers: Modifiers, key: KeyCode) -> Self {
+        Self { modifiers, key }
+    }
+
+    /// Returns the chord of the key that was just pressed, with the modifiers that are held.
+    /// Modifier keys on their own are not a chord.
+    pub fn just_pressed(keys: &ButtonInput<KeyCode>) -> Option<Self> {
+        let key = keys.get_just_pressed().find(|key| !Modifiers::is_modifier_key(**key))?;
+        Some(Self::new(Modifiers::pressed(keys), *key))
+    }
+
+    /// Returns true if the chord has no Ctrl, Alt or Super modifier, so it could also be typed text.
+    pub fn is_plain(&self) -> bool {
+        !self.modifiers.contains(Modifiers::CONTROL)
+            && !self.modifiers.contains(Modifiers::ALT)
+            && !self.modifiers.contains(Modifiers::SUPER)
+    }
+}
+
+impl Display for Chord {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), core::fmt::Error> {
+        for name in self.modifiers.names() {
+            write!(f, "{name}+")?;
+        }
+        write!(f, "{}", format_keycode(self.key))
+    }
+}
+
+impl FromStr for Chord {
+    type Err = String;
+
+    /// Parses a chord in the format it is displayed in, e.g. "Ctrl+Shift+S".
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut modifiers = Modifiers::NONE;
+        let mut parts: Vec<&str> = text.split('+').map(str::trim).collect();
+        let key = parts.pop().unwrap_or_default();
+        for part in parts {
+            modifiers = modifiers
+                | match part.to_lowercase().as_str() {
+                    "ctrl" | "control" => Modifiers::CONTROL,
+                    "shift" => Modifiers::SHIFT,
+                    "alt" | "option" => Modifiers::ALT,
+                    "cmd" | "win" | "super" | "meta" => Modifiers::SUPER,
+                    _ => return Err(format!("{part} is not a modifier in {text}")),
+                };
+        }
+        let key = parse_keycode(key).ok_or_else(|| format!("{key} is not a key in {text}"))?;
+        Ok(Self::new(modifiers, key))
+    }
+}
+
+impl Shortcut {
+    /// Shortcut of a single chord.
+    pub fn new(modifiers: Modifiers, key: KeyCode) -> Self {
+        Self {
+            chords: vec![Chord::new(modifiers, key)],
         }
     }
+
+    /// Shortcut of a single chord with the primary modifier, see [`Modifiers::PRIMARY`].
+    pub fn primary(key: KeyCode) -> Self {
+        Self::new(Modifiers::PRIMARY, key)
+    }
+
+    /// Adds a chord that is pressed after the current chords.
+    pub fn then(mut self, modifiers: Modifiers, key: KeyCode) -> Self {
+        self.chords.push(Chord::new(modifiers, key));
+        self
+    }
+
+    /// Returns true if the first chord could also be typed text, see [`Chord::is_plain`].
+    pub fn is_plain(&self) -> bool {
+        self.chords.first().is_none_or(Chord::is_plain)
+    }
+
+    /// Returns true if the chords start with the given chords.
+    pub fn starts_with(&self, chords: &[Chord]) -> bool {
+        self.chords.starts_with(chords)
+    }
+
+    /// Returns true if pressing one of the shortcuts interferes with the other, which is when they are the
+    /// same or one is the start of the other. Ctrl+K Ctrl+S makes Ctrl+K wait for the next chord, for example.
+    pub fn overlaps(&self, other: &Shortcut) -> bool {
+        self.starts_with(&other.chords) || other.starts_with(&self.chords)
+    }
 }
 
 impl Display for Shortcut {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), core::fmt::Error> {
-        write!(f, "{}", self.keys.iter().map(|key| format_keycode(*key)).collect::<Vec<String>>().join("+"))
+        let chords: Vec<String> = self.chords.iter().map(Chord::to_string).collect();
+        write!(f, "{}", chords.join(" "))
     }
 }
 
+impl FromStr for Shortcut {
+    type Err = String;
+
+    /// Parses a shortcut in the format it is displayed in, chords separated by spaces, e.g. "Ctrl+K Ctrl+S".
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        // Spaces around the + are part of the chord, "Ctrl + S" is one chord
+        let text = text.split('+').map(str::trim).collect::<Vec<&str>>().join("+");
+        let chords = text
+            .split_whitespace()
+            .map(Chord::from_str)
+            .collect::<Result<Vec<Chord>, String>>()?;
+        if chords.is_empty() {
+            return Err("The shortcut has no keys".to_string());
+        }
+        Ok(Self { chords })
+    }
+}
 
 impl Default for Shortcuts {
     fn default() -> Self {
         let mut map = HashMap::new();
 
         // General
-        map.insert(UiEvent::NextTab, Shortcut { keys: smallvec![KeyCode::ControlLeft, KeyCode::Tab] });
-        map.insert(UiEvent::PreviousTab, Shortcut { keys: smallvec![KeyCode::ControlLeft, KeyCode::ShiftLeft, KeyCode::Tab] });
+        map.insert(UiEvent::NextTab, Shortcut::new(Modifiers::CONTROL, KeyCode::Tab));
+        map.insert(UiEvent::PreviousTab, Shortcut::new(Modifiers::CONTROL | Modifiers::SHIFT, KeyCode::Tab));
 
         // Menu: File
-        map.insert(UiEvent::FileNew, Shortcut { keys: smallvec![KeyCode::ControlLeft, KeyCode::KeyN] });
-        map.insert(UiEvent::FileOpen, Shortcut { keys: smallvec![KeyCode::ControlLeft, KeyCode::KeyO] });
-        map.insert(UiEvent::FileSave, Shortcut { keys: smallvec![KeyCode::ControlLeft, KeyCode::KeyS] });
-        map.insert(UiEvent::FileSaveAs, Shortcut { keys: smallvec![KeyCode::ControlLeft, KeyCode::ShiftLeft, KeyCode::KeyS] });
-        map.insert(UiEvent::FileClose, Shortcut { keys: smallvec![KeyCode::ControlLeft, KeyCode::KeyW] });
-        map.insert(UiEvent::FileExit, Shortcut { keys:
+        map.insert(UiEvent::FileNew, Shortcut::primary(KeyCode::KeyN));
+        map.insert(UiEvent::FileOpen, Shortcut::primary(KeyCode::KeyO));
+        map.insert(UiEvent::FileSave, Shortcut::primary(KeyCode::KeyS));
+        map.insert(UiEvent::FileSaveAs, Shortcut::new(Modifiers::PRIMARY | Modifiers::SHIFT, KeyCode::KeyS));
+        map.insert(UiEvent::FileClose, Shortcut::primary(KeyCode::KeyW));
+        map.insert(UiEvent::FileExit,
             if cfg!(target_os = "macos") {
-                smallvec![KeyCode::SuperLeft, KeyCode::KeyQ]
+                Shortcut::new(Modifiers::SUPER, KeyCode::KeyQ)
             } else {
-                smallvec![KeyCode::AltLeft, KeyCode::F4]
+                Shortcut::new(Modifiers::ALT, KeyCode::F4)
             }
-        });
+        );
 
         // Edit
-        map.insert(UiEvent::Undo, Shortcut { keys: smallvec![KeyCode::ControlLeft, KeyCode::KeyZ] });
-        map.insert(UiEvent::Redo, Shortcut { keys: smallvec![KeyCode::ControlLeft, KeyCode::ShiftLeft, KeyCode::KeyZ] });
+        map.insert(UiEvent::Undo, Shortcut::primary(KeyCode::KeyZ));
+        map.insert(UiEvent::Redo, Shortcut::new(Modifiers::PRIMARY | Modifiers::SHIFT, KeyCode::KeyZ));
+        map.insert(
+            UiEvent::CommandPalette(CommandPaletteAction::Open),
+            Shortcut::new(Modifiers::PRIMARY | Modifiers::SHIFT, KeyCode::KeyP),
+        );
+        map.insert(
+            UiEvent::ShortcutEditor(ShortcutEditorAction::Open),
+            Shortcut::primary(KeyCode::KeyK).then(Modifiers::PRIMARY, KeyCode::KeyS),
+        );
 
         // View
-        map.insert(UiEvent::ToggleFullscreen, Shortcut { keys: smallvec![KeyCode::F11] });
+        map.insert(UiEvent::ToggleFullscreen, Shortcut::new(Modifiers::NONE, KeyCode::F11));
+        map.insert(UiEvent::IncreaseInterfaceScale, Shortcut::primary(KeyCode::Equal));
+        map.insert(UiEvent::DecreaseInterfaceScale, Shortcut::primary(KeyCode::Minus));
+        map.insert(UiEvent::SetInterfaceScale(100), Shortcut::primary(KeyCode::Digit0));
 
         // Help
-        map.insert(UiEvent::ShowHelp, Shortcut { keys: smallvec![KeyCode::F1] });
+        map.insert(UiEvent::ShowHelp, Shortcut::new(Modifiers::NONE, KeyCode::F1));
+
+        let mut scopes = HashMap::new();
+
+        // Viewport
+        for (tool, key) in [
+            (Tool::Pointer, KeyCode::KeyQ),
+            (Tool::Move, KeyCode::KeyW),
+            (Tool::Rotate, KeyCode::KeyE),
+            (Tool::Scale, KeyCode::KeyR),
+        ] {
+            map.insert(UiEvent::SelectTool(tool), Shortcut::new(Modifiers::NONE, key));
+            scopes.insert(UiEvent::SelectTool(tool), ShortcutScope::Viewport);
+        }
+        for (action, key) in [
+            (EntityAction::Delete, KeyCode::Delete),
+            (EntityAction::FrameSelection, KeyCode::KeyF),
+        ] {
+            map.insert(UiEvent::Entity(action), Shortcut::new(Modifiers::NONE, key));
+            scopes.insert(UiEvent::Entity(action), ShortcutScope::Viewport);
+        }
+
+        // Hierarchy
+        let rename = UiEvent::Hierarchy(HierarchyAction::RenameSelected);
+        map.insert(rename.clone(), Shortcut::new(Modifiers::NONE, KeyCode::F2));
+        scopes.insert(rename, ShortcutScope::Hierarchy);
+
+        // Shortcut editor
+        let close = UiEvent::ShortcutEditor(ShortcutEditorAction::Close);
+        map.insert(close.clone(), Shortcut::new(Modifiers::NONE, KeyCode::Escape));
+        scopes.insert(close, ShortcutScope::ShortcutEditor);
+
+        Self {
+            shortcuts: map.clone(),
+            defaults: map,
+            scopes,
+        }
+    }
+}
+
+/// The chords of a multi-chord shortcut that were pressed so far, while waiting for the next chord.
+#[derive(Resource, Default, Debug)]
+pub struct PendingChords {
+    pub chords: Vec<Chord>,
+    /// Time in seconds at which the last chord was pressed.
+    pub pressed_at: f32,
+}
+
+/// Finds the active shortcut scopes. They are the scopes of the focused text input and its ancestors, or otherwise
+/// of the innermost element with a scope under the cursor and its ancestors.
+/// While a [`Modal`] is open only the scopes inside it are active.
+fn update_shortcut_scopes(
+    mut shortcut_scopes: ResMut<ShortcutScopes>,
+    text_input_focus: Res<TextInputFocus>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    elements: Query<(Entity, &ComputedNode, &UiGlobalTransform, &InheritedVisibility), With<ShortcutScope>>,
+    modals: Query<Entity, With<Modal>>,
+    parents: Query<&ChildOf>,
+    scopes: Query<&ShortcutScope>,
+) {
+    let modal = modals.iter().last();
+    let is_inside_modal = |entity: Entity| {
+        modal.is_none_or(|modal| entity == modal || parents.iter_ancestors(entity).any(|ancestor| ancestor == modal))
+    };
 
-        Self { shortcuts: map }
+    let cursor_position = windows.iter().find_map(Window::physical_cursor_position);
+    let hovered = cursor_position.and_then(|cursor_position| {
+        elements
+            .iter()
+            .filter(|(entity, computed_node, transform, visibility)| {
+                visibility.get()
+                    && Rect::from_center_size(transform.translation, computed_node.size()).contains(cursor_position)
+                    && is_inside_modal(*entity)
+            })
+            .max_by_key(|(entity, ..)| parents.iter_ancestors(*entity).count())
+            .map(|(entity, ..)| entity)
+    });
+    let innermost = text_input_focus
+        .entity()
+        .filter(|entity| is_inside_modal(*entity))
+        .or(hovered)
+        .or(modal);
+
+    let mut stack = vec![ShortcutScope::Global];
+    if let Some(innermost) = innermost {
+        let mut inner_scopes: Vec<ShortcutScope> = std::iter::once(innermost)
+            .chain(parents.iter_ancestors(innermost))
+            .filter_map(|entity| scopes.get(entity).ok())
+            .filter(|scope| **scope != ShortcutScope::Global)
+            .cloned()
+            .collect();
+        inner_scopes.reverse();
+        stack.extend(inner_scopes);
+    }
+    if shortcut_scopes.0 != stack {
+        shortcut_scopes.0 = stack;
     }
 }
 
+/// Sends the UiEvent of the shortcuts that were pressed.
+///
+/// A chord that starts a longer shortcut is kept in [`PendingChords`] until the next chord is pressed,
+/// the sequence is cancelled if that chord doesn't continue any shortcut or isn't pressed within [`CHORD_TIMEOUT`].
+/// Only the shortcuts of the active scopes apply, and when several scopes have a matching shortcut the innermost
+/// scope wins, see [`ShortcutScopes`].
+/// Shortcuts without a modifier key are ignored while a text input has focus, because those keys are typed.
+#[allow(clippy::too_many_arguments)]
 pub fn handle_shortcuts(
     shortcuts: Res<Shortcuts>,
+    shortcut_scopes: Res<ShortcutScopes>,
     keys: Res<ButtonInput<KeyCode>>,
+    time: Res<Time<Real>>,
+    text_input_focus: Res<TextInputFocus>,
+    recording_shortcut: Res<RecordingShortcut>,
+    menu_navigation: Res<MenuNavigation>,
+    mut pending_chords: ResMut<PendingChords>,
     mut ui_event_writer: MessageWriter<UiEvent>,
 ) {
-    for (event, shortcut) in shortcuts.shortcuts.iter() {
-        if keys.all_pressed(shortcut.keys.iter().cloned()) {
-            if keys.any_just_pressed(shortcut.keys.iter().cloned()) {
-                info!("Shortcut pressed {:?} --> {:?}", shortcut, event);
-                ui_event_writer.write(event.clone());
+    let now = time.elapsed_secs();
+    // Key presses go to the shortcut editor or the menus instead
+    if recording_shortcut.0.is_some() || menu_navigation.is_active() {
+        if !pending_chords.chords.is_empty() {
+            pending_chords.chords.clear();
+        }
+        return;
+    }
+    let Some(chord) = Chord::just_pressed(&keys) else {
+        if !pending_chords.chords.is_empty() && now - pending_chords.pressed_at > CHORD_TIMEOUT {
+            pending_chords.chords.clear();
+        }
+        return;
+    };
+
+    let mut chords = std::mem::take(&mut pending_chords.chords);
+    let is_pending = !chords.is_empty();
+    chords.push(chord);
+    let candidates: Vec<(usize, &UiEvent, &Shortcut)> = shortcuts
+        .shortcuts
+        .iter()
+        .filter(|(_, shortcut)| !(text_input_focus.is_focused() && shortcut.is_plain()))
+        .filter(|(_, shortcut)| shortcut.starts_with(&chords))
+        .filter_map(|(event, shortcut)| Some((shortcut_scopes.depth(&shortcuts.get_scope(event))?, event, shortcut)))
+        .collect();
+    let innermost = candidates.iter().map(|(depth, ..)| *depth).max();
+    let mut candidates: Vec<(&UiEvent, &Shortcut)> = candidates
+        .into_iter()
+        .filter(|(depth, ..)| Some(*depth) == innermost)
+        .map(|(_, event, shortcut)| (event, shortcut))
+        .collect();
+    // Conflicting shortcuts are only warned about, so pick the same event every time instead of
+    // depending on the order of the map
+    candidates.sort_by_key(|(event, _)| event_label(event));
+
+    // A longer shortcut wins over one that is complete, otherwise it could never be pressed
+    if candidates.iter().any(|(_, shortcut)| shortcut.chords.len() > chords.len()) {
+        info!("Waiting for the next chord after {}", Shortcut { chords: chords.clone() });
+        pending_chords.chords = chords;
+        pending_chords.pressed_at = now;
+    } else if let Some((event, shortcut)) = candidates.first() {
+        if candidates.len() > 1 {
+            let others: Vec<String> = candidates[1..].iter().map(|(event, _)| format!("{event:?}")).collect();
+            warn!("{shortcut} is also bound to {}, only {event:?} is sent", others.join(", "));
+        }
+        info!("Shortcut pressed {} --> {:?}", shortcut, event);
+        ui_event_writer.write((*event).clone());
+    } else if is_pending {
+        info!("{} is not a shortcut", Shortcut { chords });
+    }
+}
+
+/// Shows the pending chords in the status bar.
+fn update_pending_chords_status(pending_chords: Res<PendingChords>, mut status_bars: Query<&mut StatusBar>) {
+    let pending = (!pending_chords.chords.is_empty()).then(|| {
+        let shortcut = Shortcut {
+            chords: pending_chords.chords.clone(),
+        };
+        format!("({shortcut}) was pressed, waiting for the next key...")
+    });
+    for mut status_bar in status_bars.iter_mut() {
+        if status_bar.pending_chords != pending {
+            status_bar.pending_chords = pending.clone();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::{message::Messages, system::RunSystemOnce};
+
+    use super::*;
+
+    /// Returns the keys to hold for the chord, using the left key of each modifier.
+    fn chord_keys(chord: &Chord) -> Vec<KeyCode> {
+        Modifiers::KEYS
+            .iter()
+            .filter(|(modifier, _)| chord.modifiers.contains(*modifier))
+            .map(|(_, [left, _])| *left)
+            .chain([chord.key])
+            .collect()
+    }
+
+    /// Presses the chords one after another, each with the given keys held, and returns the events that were sent.
+    fn press(shortcuts: Shortcuts, scopes: Vec<ShortcutScope>, chords: &[Vec<KeyCode>]) -> Vec<UiEvent> {
+        let mut world = World::new();
+        world.insert_resource(shortcuts);
+        world.insert_resource(ShortcutScopes(scopes));
+        world.init_resource::<Time<Real>>();
+        world.init_resource::<TextInputFocus>();
+        world.init_resource::<RecordingShortcut>();
+        world.init_resource::<MenuNavigation>();
+        world.init_resource::<PendingChords>();
+        world.init_resource::<Messages<UiEvent>>();
+        for keys in chords {
+            let mut input = ButtonInput::<KeyCode>::default();
+            for key in keys {
+                input.press(*key);
+            }
+            world.insert_resource(input);
+            world.run_system_once(handle_shortcuts).unwrap();
+        }
+        world.resource_mut::<Messages<UiEvent>>().drain().collect()
+    }
+
+    fn press_global(chords: &[Vec<KeyCode>]) -> Vec<UiEvent> {
+        press(Shortcuts::default(), vec![ShortcutScope::Global], chords)
+    }
+
+    #[test]
+    fn default_shortcuts_send_their_events() {
+        let defaults = Shortcuts::default();
+        assert_eq!(defaults.shortcuts, defaults.defaults);
+        for (event, shortcut) in defaults.defaults.iter() {
+            assert!(defaults.conflicts(event).is_empty(), "{shortcut} of {event:?} has conflicts");
+
+            let mut scopes = vec![ShortcutScope::Global];
+            let scope = defaults.get_scope(event);
+            if scope != ShortcutScope::Global {
+                scopes.push(scope);
+            }
+            let chords: Vec<Vec<KeyCode>> = shortcut.chords.iter().map(chord_keys).collect();
+            assert_eq!(press(Shortcuts::default(), scopes, &chords), vec![event.clone()], "{shortcut}");
+        }
+    }
+
+    #[test]
+    fn modifiers_must_match_exactly() {
+        let primary = chord_keys(&Chord::new(Modifiers::PRIMARY, KeyCode::KeyZ));
+        let primary_shift = chord_keys(&Chord::new(Modifiers::PRIMARY | Modifiers::SHIFT, KeyCode::KeyZ));
+        let primary_alt = chord_keys(&Chord::new(Modifiers::PRIMARY | Modifiers::ALT, KeyCode::KeyZ));
+
+        assert_eq!(press_global(&[primary]), vec![UiEvent::Undo]);
+        assert_eq!(press_global(&[primary_shift]), vec![UiEvent::Redo]);
+        assert_eq!(press_global(&[primary_alt]), vec![]);
+        assert_eq!(press_global(&[vec![KeyCode::KeyZ]]), vec![]);
+    }
+
+    #[test]
+    fn right_modifier_keys_match_like_left_ones() {
+        assert_eq!(press_global(&[vec![KeyCode::ControlRight, KeyCode::Tab]]), vec![UiEvent::NextTab]);
+        assert_eq!(
+            press_global(&[vec![KeyCode::ControlRight, KeyCode::ShiftRight, KeyCode::Tab]]),
+            vec![UiEvent::PreviousTab]
+        );
+        assert_eq!(
+            press_global(&[vec![KeyCode::ControlLeft, KeyCode::ShiftRight, KeyCode::Tab]]),
+            vec![UiEvent::PreviousTab]
+        );
+    }
+
+    #[test]
+    fn scope_depth_is_its_position_in_the_stack() {
+        let scopes = ShortcutScopes(vec![
+            ShortcutScope::Global,
+            ShortcutScope::Viewport,
+            ShortcutScope::Custom("Gizmo".to_string()),
+        ]);
+        assert_eq!(scopes.depth(&ShortcutScope::Global), Some(0));
+        assert_eq!(scopes.depth(&ShortcutScope::Viewport), Some(1));
+        assert_eq!(scopes.depth(&ShortcutScope::Custom("Gizmo".to_string())), Some(2));
+        assert_eq!(scopes.depth(&ShortcutScope::Hierarchy), None);
+    }
+
+    #[test]
+    fn innermost_scope_wins() {
+        let shortcuts = || {
+            let mut shortcuts = Shortcuts::default();
+            shortcuts.set_shortcut(UiEvent::ShowHelp, Some(Shortcut::new(Modifiers::NONE, KeyCode::KeyF)));
+            shortcuts.set_shortcut(
+                UiEvent::Hierarchy(HierarchyAction::ToggleDock),
+                Some(Shortcut::new(Modifiers::NONE, KeyCode::KeyF)),
+            );
+            shortcuts.set_scope(UiEvent::Hierarchy(HierarchyAction::ToggleDock), ShortcutScope::Hierarchy);
+            shortcuts
+        };
+        let f = [vec![KeyCode::KeyF]];
+
+        assert_eq!(press(shortcuts(), vec![ShortcutScope::Global], &f), vec![UiEvent::ShowHelp]);
+        assert_eq!(
+            press(shortcuts(), vec![ShortcutScope::Global, ShortcutScope::Viewport], &f),
+            vec![UiEvent::Entity(EntityAction::FrameSelection)]
+        );
+        assert_eq!(
+            press(shortcuts(), vec![ShortcutScope::Global, ShortcutScope::Hierarchy], &f),
+            vec![UiEvent::Hierarchy(HierarchyAction::ToggleDock)]
+        );
+        // The hierarchy is inside the viewport here, so it is the innermost scope
+        assert_eq!(
+            press(
+                shortcuts(),
+                vec![ShortcutScope::Global, ShortcutScope::Viewport, ShortcutScope::Hierarchy],
+                &f
+            ),
+            vec![UiEvent::Hierarchy(HierarchyAction::ToggleDock)]
+        );
+        assert_eq!(
+            press(shortcuts(), vec![ShortcutScope::Global, ShortcutScope::Hierarchy], &[vec![KeyCode::Delete]]),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn conflicting_shortcuts_send_the_first_event_by_label() {
+        let f = [vec![KeyCode::KeyF]];
+        for events in [[UiEvent::ShowHelp, UiEvent::FileNew], [UiEvent::FileNew, UiEvent::ShowHelp]] {
+            let mut shortcuts = Shortcuts::default();
+            for event in events {
+                shortcuts.set_shortcut(event, Some(Shortcut::new(Modifiers::NONE, KeyCode::KeyF)));
             }
+            assert_eq!(press(shortcuts, vec![ShortcutScope::Global], &f), vec![UiEvent::FileNew]);
+        }
+    }
+
+    #[test]
+    fn primary_modifier_is_cmd_on_macos_and_ctrl_elsewhere() {
+        let super_s = Shortcut::new(Modifiers::SUPER, KeyCode::KeyS).to_string();
+        if cfg!(target_os = "macos") {
+            assert_eq!(Modifiers::PRIMARY, Modifiers::SUPER);
+            assert_eq!(Shortcut::primary(KeyCode::KeyS).to_string(), "Cmd+S");
+            assert_eq!(super_s, "Cmd+S");
+        } else {
+            assert_eq!(Modifiers::PRIMARY, Modifiers::CONTROL);
+            assert_eq!(Shortcut::primary(KeyCode::KeyS).to_string(), "Ctrl+S");
+            assert_eq!(super_s, if cfg!(target_os = "windows") { "Win+S" } else { "Super+S" });
+        }
+        assert_eq!(Shortcut::from_str(&super_s), Ok(Shortcut::new(Modifiers::SUPER, KeyCode::KeyS)));
+        assert!(!Shortcut::primary(KeyCode::KeyS).is_plain());
+    }
+
+    #[test]
+    fn shortcuts_are_parsed_as_they_are_displayed() {
+        for text in ["Ctrl+K Ctrl+S", "Ctrl+Shift+S", "Alt+F4", "F11", "Ctrl+0", "Ctrl+Equal", "Shift+Tab"] {
+            assert_eq!(Shortcut::from_str(text).unwrap().to_string(), text);
+        }
+        assert_eq!(
+            Shortcut::from_str("ctrl + K  control+S"),
+            Ok(Shortcut::new(Modifiers::CONTROL, KeyCode::KeyK).then(Modifiers::CONTROL, KeyCode::KeyS))
+        );
+        for (_, shortcut) in Shortcuts::default().defaults {
+            assert_eq!(Shortcut::from_str(&shortcut.to_string()), Ok(shortcut));
         }
+        assert!(Shortcut::from_str("").is_err());
+        assert!(Shortcut::from_str("Ctrl+Nothing").is_err());
+        assert!(Shortcut::from_str("Hyper+S").is_err());
     }
-}
\ No newline at end of file
+}
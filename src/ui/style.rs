@@ -0,0 +1,466 @@
+use bevy::{prelude::*, ui::UiSystems};
+
+pub struct StylePlugin;
+
+impl Plugin for StylePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<UiStyle>()
+            .register_type::<EditorFontSize>()
+            .register_type::<EditorBorderRadius>()
+            .register_type::<EditorPadding>()
+            .register_type::<EditorGap>()
+            .register_type::<EditorMargin>()
+            .register_type::<EditorWidth>()
+            .register_type::<EditorHeight>()
+            .register_type::<EditorIconSize>()
+            .register_type::<EditorTop>()
+            .register_type::<EditorNegativeTop>()
+            .add_systems(
+                PostUpdate,
+                (update_font_sizes, update_border_radii, update_node_sizes).before(UiSystems::Prepare),
+            );
+    }
+}
+
+/// How much room the editor UI takes. A theme can set it, see [`crate::ui::theme::Theme`].
+#[derive(Reflect, Default, Clone, Copy, Eq, PartialEq, Debug)]
+pub enum Density {
+    Compact,
+    #[default]
+    Comfortable,
+}
+
+#[derive(Reflect, Hash, Eq, PartialEq, Clone, Copy, Debug)]
+pub enum FontSize {
+    /// Small labels, like the expand buttons in the hierarchy.
+    Caption,
+    /// Values and labels in the inspector.
+    Small,
+    Body,
+    /// Titles of dialogs.
+    Title,
+}
+
+#[derive(Reflect, Hash, Eq, PartialEq, Clone, Copy, Debug)]
+pub enum Spacing {
+    None,
+    Xs,
+    Sm,
+    Md,
+    Lg,
+}
+
+#[derive(Reflect, Hash, Eq, PartialEq, Clone, Copy, Debug)]
+pub enum Radius {
+    None,
+    Xs,
+    Sm,
+    Md,
+    Lg,
+}
+
+/// Heights of the bars and of the controls in them.
+#[derive(Reflect, Hash, Eq, PartialEq, Clone, Copy, Debug)]
+pub enum Height {
+    MenuBar,
+    /// Buttons in the menu bar.
+    MenuButton,
+    /// Items of the menu dropdowns.
+    MenuItem,
+    TabBar,
+    /// Tabs and tool button groups.
+    Control,
+    /// The status bar and the FPS counter in it.
+    StatusBar,
+    /// Lines between groups of tool buttons.
+    Separator,
+    /// The list of shortcuts in the shortcut editor.
+    ShortcutList,
+    /// The list of folders and files in the file dialog.
+    FileList,
+    /// Distance of the command palette from the top, below the menu and tab bars.
+    CommandPaletteTop,
+}
+
+/// Widths of the panels and of fixed columns.
+#[derive(Reflect, Hash, Eq, PartialEq, Clone, Copy, Debug)]
+pub enum Width {
+    /// Column for the check marks of menu items.
+    MenuCheck,
+    HierarchyPanel,
+    InspectorPanel,
+    ShortcutEditor,
+    /// Buttons that show and record the shortcuts in the shortcut editor.
+    ShortcutButton,
+    FileDialog,
+    CommandPalette,
+}
+
+/// Widths and heights of icons and of the square buttons around them.
+#[derive(Reflect, Hash, Eq, PartialEq, Clone, Copy, Debug)]
+pub enum IconSize {
+    /// Icons of menu items and the expand buttons in the hierarchy.
+    Sm,
+    /// Icons and close buttons of tabs.
+    Md,
+    /// Icons of tool buttons.
+    Lg,
+    /// Tool buttons.
+    Xl,
+}
+
+/// The sizes of the style tokens, which are used by the elements through the [`EditorFontSize`],
+/// [`EditorBorderRadius`], [`EditorPadding`], [`EditorMargin`], [`EditorGap`], [`EditorWidth`], [`EditorHeight`],
+/// [`EditorIconSize`], [`EditorTop`] and [`EditorNegativeTop`] components.
+#[derive(Resource, Default, Clone, Copy, Debug)]
+pub struct UiStyle {
+    pub density: Density,
+}
+
+impl UiStyle {
+    pub fn font_size(&self, font_size: FontSize) -> f32 {
+        match (self.density, font_size) {
+            (Density::Compact, FontSize::Caption) => 10.0,
+            (Density::Compact, FontSize::Small) => 11.0,
+            (Density::Compact, FontSize::Body) => 12.0,
+            (Density::Compact, FontSize::Title) => 14.0,
+            (Density::Comfortable, FontSize::Caption) => 11.0,
+            (Density::Comfortable, FontSize::Small) => 12.0,
+            (Density::Comfortable, FontSize::Body) => 13.0,
+            (Density::Comfortable, FontSize::Title) => 15.0,
+        }
+    }
+
+    pub fn spacing(&self, spacing: Spacing) -> Val {
+        Val::Px(match (self.density, spacing) {
+            (_, Spacing::None) => 0.0,
+            (Density::Compact, Spacing::Xs) => 1.0,
+            (Density::Compact, Spacing::Sm) => 3.0,
+            (Density::Compact, Spacing::Md) => 6.0,
+            (Density::Compact, Spacing::Lg) => 10.0,
+            (Density::Comfortable, Spacing::Xs) => 2.0,
+            (Density::Comfortable, Spacing::Sm) => 5.0,
+            (Density::Comfortable, Spacing::Md) => 10.0,
+            (Density::Comfortable, Spacing::Lg) => 15.0,
+        })
+    }
+
+    /// Radii don't depend on the density.
+    pub fn radius(&self, radius: Radius) -> Val {
+        Val::Px(match radius {
+            Radius::None => 0.0,
+            Radius::Xs => 2.0,
+            Radius::Sm => 3.0,
+            Radius::Md => 4.0,
+            Radius::Lg => 6.0,
+        })
+    }
+
+    pub fn height(&self, height: Height) -> Val {
+        Val::Px(match (self.density, height) {
+            (Density::Compact, Height::MenuBar) => 30.0,
+            (Density::Compact, Height::MenuButton) => 24.0,
+            (Density::Compact, Height::MenuItem) => 22.0,
+            (Density::Compact, Height::TabBar) => 36.0,
+            (Density::Compact, Height::Control) => 30.0,
+            (Density::Comfortable, Height::MenuBar) => 39.0,
+            (Density::Comfortable, Height::MenuButton) => 29.0,
+            (Density::Comfortable, Height::MenuItem) => 26.0,
+            (Density::Comfortable, Height::TabBar) => 50.0,
+            (Density::Comfortable, Height::Control) => 38.0,
+            (Density::Compact, Height::StatusBar) => 16.0,
+            (Density::Compact, Height::Separator) => 12.0,
+            (Density::Comfortable, Height::StatusBar) => 20.0,
+            (Density::Comfortable, Height::Separator) => 14.0,
+            (Density::Compact, Height::ShortcutList) => 300.0,
+            (Density::Compact, Height::FileList) => 260.0,
+            (Density::Compact, Height::CommandPaletteTop) => 66.0,
+            (Density::Comfortable, Height::ShortcutList) => 360.0,
+            (Density::Comfortable, Height::FileList) => 300.0,
+            (Density::Comfortable, Height::CommandPaletteTop) => 80.0,
+        })
+    }
+
+    pub fn width(&self, width: Width) -> Val {
+        Val::Px(match (self.density, width) {
+            (Density::Compact, Width::MenuCheck) => 10.0,
+            (Density::Compact, Width::HierarchyPanel) => 200.0,
+            (Density::Compact, Width::InspectorPanel) => 260.0,
+            (Density::Comfortable, Width::MenuCheck) => 12.0,
+            (Density::Comfortable, Width::HierarchyPanel) => 240.0,
+            (Density::Comfortable, Width::InspectorPanel) => 300.0,
+            (Density::Compact, Width::ShortcutEditor) => 420.0,
+            (Density::Compact, Width::ShortcutButton) => 140.0,
+            (Density::Compact, Width::FileDialog) => 480.0,
+            (Density::Compact, Width::CommandPalette) => 480.0,
+            (Density::Comfortable, Width::ShortcutEditor) => 480.0,
+            (Density::Comfortable, Width::ShortcutButton) => 160.0,
+            (Density::Comfortable, Width::FileDialog) => 560.0,
+            (Density::Comfortable, Width::CommandPalette) => 560.0,
+        })
+    }
+
+    pub fn icon_size(&self, icon_size: IconSize) -> Val {
+        Val::Px(match (self.density, icon_size) {
+            (Density::Compact, IconSize::Sm) => 12.0,
+            (Density::Compact, IconSize::Md) => 16.0,
+            (Density::Compact, IconSize::Lg) => 18.0,
+            (Density::Compact, IconSize::Xl) => 22.0,
+            (Density::Comfortable, IconSize::Sm) => 14.0,
+            (Density::Comfortable, IconSize::Md) => 18.0,
+            (Density::Comfortable, IconSize::Lg) => 20.0,
+            (Density::Comfortable, IconSize::Xl) => 25.0,
+        })
+    }
+}
+
+/// Sets the font size of a text or text span.
+#[derive(Component, Reflect, Clone, Copy)]
+#[reflect(Component)]
+#[require(TextFont)]
+pub struct EditorFontSize(pub FontSize);
+
+#[derive(Component, Reflect, Clone, Copy)]
+#[reflect(Component)]
+#[require(BorderRadius)]
+pub struct EditorBorderRadius {
+    pub top_left: Radius,
+    pub top_right: Radius,
+    pub bottom_right: Radius,
+    pub bottom_left: Radius,
+}
+
+impl EditorBorderRadius {
+    pub fn all(radius: Radius) -> Self {
+        Self::new(radius, radius, radius, radius)
+    }
+
+    pub fn top(radius: Radius) -> Self {
+        Self::new(radius, radius, Radius::None, Radius::None)
+    }
+
+    pub fn new(top_left: Radius, top_right: Radius, bottom_right: Radius, bottom_left: Radius) -> Self {
+        Self { top_left, top_right, bottom_right, bottom_left }
+    }
+}
+
+/// Sets the padding of a Node.
+#[derive(Component, Reflect, Clone, Copy)]
+#[reflect(Component)]
+#[require(Node)]
+pub struct EditorPadding {
+    pub left: Spacing,
+    pub right: Spacing,
+    pub top: Spacing,
+    pub bottom: Spacing,
+}
+
+impl EditorPadding {
+    pub fn all(spacing: Spacing) -> Self {
+        Self::new(spacing, spacing, spacing, spacing)
+    }
+
+    pub fn axes(horizontal: Spacing, vertical: Spacing) -> Self {
+        Self::new(horizontal, horizontal, vertical, vertical)
+    }
+
+    pub fn new(left: Spacing, right: Spacing, top: Spacing, bottom: Spacing) -> Self {
+        Self { left, right, top, bottom }
+    }
+}
+
+/// Sets the margin of a Node.
+#[derive(Component, Reflect, Clone, Copy)]
+#[reflect(Component)]
+#[require(Node)]
+pub struct EditorMargin {
+    pub left: Spacing,
+    pub right: Spacing,
+    pub top: Spacing,
+    pub bottom: Spacing,
+}
+
+impl EditorMargin {
+    pub fn axes(horizontal: Spacing, vertical: Spacing) -> Self {
+        Self::new(horizontal, horizontal, vertical, vertical)
+    }
+
+    pub fn top(spacing: Spacing) -> Self {
+        Self::new(Spacing::None, Spacing::None, spacing, Spacing::None)
+    }
+
+    pub fn new(left: Spacing, right: Spacing, top: Spacing, bottom: Spacing) -> Self {
+        Self { left, right, top, bottom }
+    }
+}
+
+/// Sets the column and row gap of a Node.
+#[derive(Component, Reflect, Clone, Copy)]
+#[reflect(Component)]
+#[require(Node)]
+pub struct EditorGap(pub Spacing);
+
+/// Sets the height of a Node.
+#[derive(Component, Reflect, Clone, Copy)]
+#[reflect(Component)]
+#[require(Node)]
+pub struct EditorHeight(pub Height);
+
+/// Sets the width of a Node.
+#[derive(Component, Reflect, Clone, Copy)]
+#[reflect(Component)]
+#[require(Node)]
+pub struct EditorWidth(pub Width);
+
+/// Sets the width and height of a Node.
+#[derive(Component, Reflect, Clone, Copy)]
+#[reflect(Component)]
+#[require(Node)]
+pub struct EditorIconSize(pub IconSize);
+
+/// Sets the top of a Node, e.g. to place a dialog below the bars.
+#[derive(Component, Reflect, Clone, Copy)]
+#[reflect(Component)]
+#[require(Node)]
+pub struct EditorTop(pub Height);
+
+/// Moves an absolutely positioned Node up by a spacing, by setting its top to the negative spacing.
+#[derive(Component, Reflect, Clone, Copy)]
+#[reflect(Component)]
+#[require(Node)]
+pub struct EditorNegativeTop(pub Spacing);
+
+/// Updates the font sizes when the style or the token of an element changed, or when its TextFont was replaced,
+/// e.g. by a reactive element.
+fn update_font_sizes(ui_style: Res<UiStyle>, mut text_fonts: Query<(&mut TextFont, Ref<EditorFontSize>)>) {
+    for (mut text_font, editor_font_size) in text_fonts.iter_mut() {
+        if !ui_style.is_changed() && !editor_font_size.is_changed() && !text_font.is_changed() {
+            continue;
+        }
+        let font_size = ui_style.font_size(editor_font_size.0);
+        if text_font.font_size != font_size {
+            text_font.font_size = font_size;
+        }
+    }
+}
+
+fn update_border_radii(
+    ui_style: Res<UiStyle>,
+    mut border_radii: Query<(&mut BorderRadius, Ref<EditorBorderRadius>)>,
+) {
+    for (mut border_radius, editor_border_radius) in border_radii.iter_mut() {
+        if !ui_style.is_changed() && !editor_border_radius.is_changed() && !border_radius.is_changed() {
+            continue;
+        }
+        let radius = BorderRadius::new(
+            ui_style.radius(editor_border_radius.top_left),
+            ui_style.radius(editor_border_radius.top_right),
+            ui_style.radius(editor_border_radius.bottom_right),
+            ui_style.radius(editor_border_radius.bottom_left),
+        );
+        if *border_radius != radius {
+            *border_radius = radius;
+        }
+    }
+}
+
+/// Updates the paddings, margins, gaps and sizes of the nodes, like [`update_font_sizes`].
+#[allow(clippy::type_complexity)]
+fn update_node_sizes(
+    ui_style: Res<UiStyle>,
+    mut nodes: Query<
+        (
+            &mut Node,
+            (Option<Ref<EditorPadding>>, Option<Ref<EditorMargin>>, Option<Ref<EditorGap>>),
+            (Option<Ref<EditorWidth>>, Option<Ref<EditorHeight>>, Option<Ref<EditorIconSize>>),
+            (Option<Ref<EditorTop>>, Option<Ref<EditorNegativeTop>>),
+        ),
+        Or<(
+            With<EditorPadding>,
+            With<EditorMargin>,
+            With<EditorGap>,
+            With<EditorWidth>,
+            With<EditorHeight>,
+            With<EditorIconSize>,
+            With<EditorTop>,
+            With<EditorNegativeTop>,
+        )>,
+    >,
+) {
+    for (mut node, (padding, margin, gap), (width, height, icon_size), (top, negative_top)) in nodes.iter_mut() {
+        let is_token_changed = padding.as_ref().is_some_and(Ref::is_changed)
+            || margin.as_ref().is_some_and(Ref::is_changed)
+            || gap.as_ref().is_some_and(Ref::is_changed)
+            || width.as_ref().is_some_and(Ref::is_changed)
+            || height.as_ref().is_some_and(Ref::is_changed)
+            || icon_size.as_ref().is_some_and(Ref::is_changed)
+            || top.as_ref().is_some_and(Ref::is_changed)
+            || negative_top.as_ref().is_some_and(Ref::is_changed);
+        if !ui_style.is_changed() && !is_token_changed && !node.is_changed() {
+            continue;
+        }
+        if let Some(padding) = padding {
+            let padding = UiRect::new(
+                ui_style.spacing(padding.left),
+                ui_style.spacing(padding.right),
+                ui_style.spacing(padding.top),
+                ui_style.spacing(padding.bottom),
+            );
+            if node.padding != padding {
+                node.padding = padding;
+            }
+        }
+        if let Some(margin) = margin {
+            let margin = UiRect::new(
+                ui_style.spacing(margin.left),
+                ui_style.spacing(margin.right),
+                ui_style.spacing(margin.top),
+                ui_style.spacing(margin.bottom),
+            );
+            if node.margin != margin {
+                node.margin = margin;
+            }
+        }
+        if let Some(gap) = gap {
+            let gap = ui_style.spacing(gap.0);
+            if node.column_gap != gap || node.row_gap != gap {
+                node.column_gap = gap;
+                node.row_gap = gap;
+            }
+        }
+        if let Some(width) = width {
+            let width = ui_style.width(width.0);
+            if node.width != width {
+                node.width = width;
+            }
+        }
+        if let Some(height) = height {
+            let height = ui_style.height(height.0);
+            if node.height != height {
+                node.height = height;
+            }
+        }
+        if let Some(icon_size) = icon_size {
+            let size = ui_style.icon_size(icon_size.0);
+            if node.width != size || node.height != size {
+                node.width = size;
+                node.height = size;
+            }
+        }
+        if let Some(top) = top {
+            let top = ui_style.height(top.0);
+            if node.top != top {
+                node.top = top;
+            }
+        }
+        if let Some(negative_top) = negative_top {
+            let top = match ui_style.spacing(negative_top.0) {
+                Val::Px(spacing) => Val::Px(-spacing),
+                spacing => spacing,
+            };
+            if node.top != top {
+                node.top = top;
+            }
+        }
+    }
+}
@@ -0,0 +1,337 @@
+use std::path::{Path, PathBuf};
+
+use bevy::prelude::*;
+
+use crate::ui::{CurrentTab, UiEvent, elements::{Tab, TabBar}, text_input::TextClipboard};
+
+pub struct TabsPlugin;
+
+impl Plugin for TabsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(OpenDocuments::default())
+            .add_systems(Update, handle_document_events)
+            .add_systems(Update, handle_tab_events.after(handle_document_events))
+            .add_systems(
+                Update,
+                sync_tabs
+                    .after(handle_tab_events)
+                    .run_if(resource_changed::<OpenDocuments>.or(resource_changed::<CurrentTab>)),
+            );
+    }
+}
+
+/// Identifies a document independent of its tab index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DocumentId(u64);
+
+/// A document that is open in the editor and shown as a tab.
+pub struct Document {
+    pub id: DocumentId,
+    pub name: String,
+    /// The scene file the document was opened from or last saved to.
+    pub path: Option<PathBuf>,
+    /// The scene entities of the document while it is not the current tab,
+    /// or the scene that was loaded from the file before it is spawned.
+    pub scene: Option<DynamicScene>,
+}
+
+/// The documents that are open in the editor, in tab order.
+#[derive(Resource)]
+pub struct OpenDocuments {
+    pub documents: Vec<Document>,
+    /// The document whose scene entities are in the world. This is the first document at startup,
+    /// so the scene that the app spawns belongs to it.
+    pub spawned: Option<DocumentId>,
+    /// Used to give new documents a unique name.
+    untitled_count: usize,
+    next_id: u64,
+}
+
+impl Default for OpenDocuments {
+    fn default() -> Self {
+        let mut open_documents = Self {
+            documents: Vec::new(),
+            spawned: Some(DocumentId(0)),
+            untitled_count: 0,
+            next_id: 0,
+        };
+        open_documents.add_untitled();
+        open_documents
+    }
+}
+
+impl OpenDocuments {
+    pub fn len(&self) -> usize {
+        self.documents.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.documents.is_empty()
+    }
+
+    /// Adds a document and returns its tab index.
+    pub fn add(&mut self, name: String, path: Option<PathBuf>, scene: Option<DynamicScene>) -> usize {
+        let id = DocumentId(self.next_id);
+        self.next_id += 1;
+        self.documents.push(Document { id, name, path, scene });
+        self.documents.len() - 1
+    }
+
+    /// Adds a new document named "Untitled N" and returns its tab index.
+    pub fn add_untitled(&mut self) -> usize {
+        self.untitled_count += 1;
+        self.add(format!("Untitled {}", self.untitled_count), None, None)
+    }
+
+    pub fn get(&self, index: usize) -> Option<&Document> {
+        self.documents.get(index)
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut Document> {
+        self.documents.get_mut(index)
+    }
+
+    /// Returns the tab index of the document with the given id.
+    pub fn index_of(&self, id: DocumentId) -> Option<usize> {
+        self.documents.iter().position(|document| document.id == id)
+    }
+
+    /// Returns the tab index of the document that was opened from or saved to the given path.
+    pub fn index_of_path(&self, path: &Path) -> Option<usize> {
+        self.documents.iter().position(|document| document.path.as_deref() == Some(path))
+    }
+
+    /// Removes the document at the given tab index, if it exists.
+    pub fn remove(&mut self, index: usize) -> Option<Document> {
+        (index < self.documents.len()).then(|| self.documents.remove(index))
+    }
+
+    /// Renames the document at the given tab index, if it exists.
+    pub fn rename(&mut self, index: usize, name: String) {
+        if let Some(document) = self.documents.get_mut(index) {
+            document.name = name;
+        }
+    }
+}
+
+/// Opens and closes documents based on the file and tab events.
+fn handle_document_events(
+    mut ui_event_reader: MessageReader<UiEvent>,
+    mut open_documents: ResMut<OpenDocuments>,
+    mut current_tab: ResMut<CurrentTab>,
+    mut text_clipboard: ResMut<TextClipboard>,
+) {
+    for event in ui_event_reader.read() {
+        match event {
+            UiEvent::FileNew => {
+                current_tab.0 = Some(open_documents.add_untitled());
+            }
+            UiEvent::FileClose => {
+                if let Some(index) = current_tab.0 {
+                    close_document(&mut open_documents, &mut current_tab, index);
+                }
+            }
+            UiEvent::CloseTab(index) => {
+                close_document(&mut open_documents, &mut current_tab, *index);
+            }
+            UiEvent::CloseOtherTabs(index) => {
+                // Close from the back, so the indices of the tabs that are still to be closed don't change
+                for other in (0..open_documents.len()).rev().filter(|other| other != index) {
+                    close_document(&mut open_documents, &mut current_tab, other);
+                }
+            }
+            UiEvent::CloseTabsToRight(index) => {
+                for other in (index + 1..open_documents.len()).rev() {
+                    close_document(&mut open_documents, &mut current_tab, other);
+                }
+            }
+            UiEvent::CopyTabPath(index) => {
+                if let Some(path) = open_documents.get(*index).and_then(|document| document.path.as_ref()) {
+                    text_clipboard.set_text(path.display().to_string());
+                }
+            }
+            UiEvent::RenameTab { index, name } => {
+                open_documents.rename(*index, name.clone());
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Removes a document and re-indexes the current tab, so the same document stays selected
+/// or the neighbouring tab is selected when the current one is closed.
+fn close_document(open_documents: &mut OpenDocuments, current_tab: &mut CurrentTab, index: usize) {
+    if open_documents.remove(index).is_none() {
+        return;
+    }
+    current_tab.0 = match current_tab.0 {
+        _ if open_documents.is_empty() => None,
+        Some(current) if current > index => Some(current - 1),
+        Some(current) => Some(current.min(open_documents.len() - 1)),
+        None => None,
+    };
+}
+
+// Handle next, previous and select tab
+pub(crate) fn handle_tab_events(
+    mut ui_event_reader: MessageReader<UiEvent>,
+    open_documents: Res<OpenDocuments>,
+    mut current_tab: ResMut<CurrentTab>,
+) {
+    let tab_count = open_documents.len();
+    for event in ui_event_reader.read() {
+        if let UiEvent::NextTab = event {
+            // Wrap around to the first tab if the current tab is the last tab
+            current_tab.0 = if tab_count == 0 { None } else { Some((current_tab.0.unwrap_or(0) + 1) % tab_count) };
+        }
+        if let UiEvent::PreviousTab = event {
+            // Wrap around to the last tab if the current tab is the first tab
+            current_tab.0 = if tab_count == 0 { None } else { Some((tab_count + current_tab.0.unwrap_or(0) - 1) % tab_count) };
+        }
+        if let UiEvent::SelectTab(index) = event {
+            // Clamp the index to the range of the tab count
+            current_tab.0 = if tab_count == 0 { None } else { Some((*index).min(tab_count - 1)) };
+        }
+    }
+}
+
+/// Spawns, updates and despawns the Tab entities in the TabBar so they match the OpenDocuments
+/// and CurrentTab resources.
+fn sync_tabs(
+    mut commands: Commands,
+    open_documents: Res<OpenDocuments>,
+    current_tab: Res<CurrentTab>,
+    tab_bar: Single<(Entity, Option<&Children>), With<TabBar>>,
+    mut tabs: Query<&mut Tab>,
+) {
+    let (tab_bar, children) = *tab_bar;
+    let children = children.map(|children| children.to_vec()).unwrap_or_default();
+
+    for (index, document) in open_documents.documents.iter().enumerate() {
+        let new_tab = Tab::new(
+            index,
+            document.name.clone(),
+            current_tab.0 == Some(index),
+            document.path.is_some(),
+        );
+        match children.get(index).and_then(|child| tabs.get_mut(*child).ok()) {
+            Some(mut tab) => {
+                if *tab != new_tab {
+                    *tab = new_tab;
+                }
+            }
+            None => {
+                commands.entity(tab_bar).with_child(new_tab);
+            }
+        }
+    }
+    for child in children.iter().skip(open_documents.len()) {
+        commands.entity(*child).despawn();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    /// Returns documents named "0" to "count - 1", with the given tab as the current tab.
+    fn documents(count: usize, current: usize) -> (OpenDocuments, CurrentTab) {
+        let mut open_documents = OpenDocuments::default();
+        open_documents.documents.clear();
+        for index in 0..count {
+            open_documents.add(index.to_string(), None, None);
+        }
+        (open_documents, CurrentTab(Some(current)))
+    }
+
+    fn names(open_documents: &OpenDocuments) -> Vec<&str> {
+        open_documents.documents.iter().map(|document| document.name.as_str()).collect()
+    }
+
+    /// Sends a tab event to [`handle_document_events`] and returns the open documents and the current tab.
+    fn send(event: UiEvent, count: usize, current: usize) -> (OpenDocuments, CurrentTab) {
+        let (open_documents, current_tab) = documents(count, current);
+        let mut world = World::new();
+        world.insert_resource(open_documents);
+        world.insert_resource(current_tab);
+        world.init_resource::<TextClipboard>();
+        world.init_resource::<Messages<UiEvent>>();
+        world.write_message(event);
+        world.run_system_once(handle_document_events).unwrap();
+        (world.remove_resource().unwrap(), world.remove_resource().unwrap())
+    }
+
+    #[test]
+    fn closing_a_tab_keeps_the_current_document_selected() {
+        // Before the current tab
+        let (mut open_documents, mut current_tab) = documents(4, 2);
+        close_document(&mut open_documents, &mut current_tab, 0);
+        assert_eq!(names(&open_documents), ["1", "2", "3"]);
+        assert_eq!(current_tab.0, Some(1));
+
+        // After the current tab
+        let (mut open_documents, mut current_tab) = documents(4, 2);
+        close_document(&mut open_documents, &mut current_tab, 3);
+        assert_eq!(names(&open_documents), ["0", "1", "2"]);
+        assert_eq!(current_tab.0, Some(2));
+
+        // A tab that doesn't exist
+        let (mut open_documents, mut current_tab) = documents(4, 2);
+        close_document(&mut open_documents, &mut current_tab, 4);
+        assert_eq!(open_documents.len(), 4);
+        assert_eq!(current_tab.0, Some(2));
+    }
+
+    #[test]
+    fn closing_the_current_tab_selects_its_neighbour() {
+        let (mut open_documents, mut current_tab) = documents(4, 1);
+        close_document(&mut open_documents, &mut current_tab, 1);
+        assert_eq!(names(&open_documents), ["0", "2", "3"]);
+        assert_eq!(current_tab.0, Some(1));
+
+        // The last tab selects the tab before it
+        let (mut open_documents, mut current_tab) = documents(4, 3);
+        close_document(&mut open_documents, &mut current_tab, 3);
+        assert_eq!(names(&open_documents), ["0", "1", "2"]);
+        assert_eq!(current_tab.0, Some(2));
+    }
+
+    #[test]
+    fn closing_the_only_tab_selects_nothing() {
+        let (mut open_documents, mut current_tab) = documents(1, 0);
+        close_document(&mut open_documents, &mut current_tab, 0);
+        assert!(open_documents.is_empty());
+        assert_eq!(current_tab.0, None);
+    }
+
+    #[test]
+    fn closing_other_tabs_keeps_only_the_given_tab() {
+        let (open_documents, current_tab) = send(UiEvent::CloseOtherTabs(2), 5, 4);
+        assert_eq!(names(&open_documents), ["2"]);
+        assert_eq!(current_tab.0, Some(0));
+
+        let (open_documents, current_tab) = send(UiEvent::CloseOtherTabs(0), 3, 0);
+        assert_eq!(names(&open_documents), ["0"]);
+        assert_eq!(current_tab.0, Some(0));
+    }
+
+    #[test]
+    fn closing_tabs_to_the_right_keeps_the_tabs_up_to_the_given_tab() {
+        // The current tab is closed, so the given tab is selected
+        let (open_documents, current_tab) = send(UiEvent::CloseTabsToRight(1), 5, 3);
+        assert_eq!(names(&open_documents), ["0", "1"]);
+        assert_eq!(current_tab.0, Some(1));
+
+        // The current tab is kept
+        let (open_documents, current_tab) = send(UiEvent::CloseTabsToRight(2), 5, 0);
+        assert_eq!(names(&open_documents), ["0", "1", "2"]);
+        assert_eq!(current_tab.0, Some(0));
+
+        // Nothing is to the right of the last tab
+        let (open_documents, current_tab) = send(UiEvent::CloseTabsToRight(2), 3, 2);
+        assert_eq!(names(&open_documents), ["0", "1", "2"]);
+        assert_eq!(current_tab.0, Some(2));
+    }
+}
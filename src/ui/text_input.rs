@@ -0,0 +1,771 @@
+use bevy::{
+    input::{
+        ButtonState,
+        keyboard::{Key, KeyboardInput},
+    },
+    prelude::*,
+    text::TextLayoutInfo,
+    ui::UiSystems,
+    window::{Ime, PrimaryWindow},
+};
+
+use crate::ui::{
+    EditorUiElement,
+    colors::{EditorBackgroundColor, EditorColor, EditorTextColor, UiColors},
+    reactive::WidgetChild,
+    style::{EditorBorderRadius, EditorFontSize, EditorPadding, FontSize, Radius, Spacing},
+};
+
+pub struct TextInputPlugin;
+
+impl Plugin for TextInputPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TextInputFocus>()
+            .init_resource::<TextClipboard>()
+            .add_message::<TextInputChanged>()
+            .add_message::<TextInputSubmitted>()
+            .add_message::<TextInputCancelled>()
+            .register_type::<TextInput>()
+            .register_type::<AutoFocus>()
+            .add_systems(Update, add_text_input_text)
+            .add_systems(Update, update_text_input_focus)
+            .add_systems(Update, handle_text_input_keyboard.after(update_text_input_focus))
+            .add_systems(Update, update_ime.after(handle_text_input_keyboard))
+            .add_systems(PostUpdate, update_text_input_text)
+            .add_systems(PostUpdate, update_text_input_caret.after(UiSystems::PostLayout));
+    }
+}
+
+/// Single line text field. Clicking it gives it keyboard focus, after which typed text, IME input and the
+/// usual editing keys are handled, including selecting with Shift and Ctrl+A/C/X/V.
+///
+/// The value is sent with [`TextInputChanged`] on every edit and with [`TextInputSubmitted`] when Enter is pressed.
+/// Escape restores the value it had when it got focus and sends [`TextInputCancelled`].
+#[derive(Component, Reflect, Clone, Debug)]
+// Clone is needed to copy the value with reflection, which reactive elements use to update it
+#[reflect(Component, Clone)]
+#[require(EditorUiElement, Button)]
+#[require(Node {
+    flex_grow: 1.0,
+    overflow: Overflow::clip(),
+    ..default()
+})]
+#[require(EditorPadding::axes(Spacing::Sm, Spacing::Xs))]
+#[require(EditorBorderRadius::all(Radius::Sm))]
+#[require(EditorBackgroundColor::new(EditorColor::InputField))]
+pub struct TextInput {
+    pub value: String,
+    /// Shown in faded text while the value is empty and the input doesn't have focus.
+    pub placeholder: String,
+    /// Maximum number of characters.
+    pub max_length: Option<usize>,
+    pub font_size: FontSize,
+    /// Values for which this returns false are shown in the error color and can't be submitted.
+    #[reflect(ignore)]
+    pub validator: Option<fn(&str) -> bool>,
+}
+
+impl Default for TextInput {
+    fn default() -> Self {
+        Self {
+            value: String::new(),
+            placeholder: String::new(),
+            max_length: None,
+            font_size: FontSize::Body,
+            validator: None,
+        }
+    }
+}
+
+impl TextInput {
+    pub fn new(value: impl Into<String>) -> Self {
+        Self {
+            value: value.into(),
+            ..default()
+        }
+    }
+
+    pub fn with_placeholder(mut self, placeholder: impl Into<String>) -> Self {
+        self.placeholder = placeholder.into();
+        self
+    }
+
+    pub fn with_max_length(mut self, max_length: usize) -> Self {
+        self.max_length = Some(max_length);
+        self
+    }
+
+    pub fn with_font_size(mut self, font_size: FontSize) -> Self {
+        self.font_size = font_size;
+        self
+    }
+
+    pub fn with_validator(mut self, validator: fn(&str) -> bool) -> Self {
+        self.validator = Some(validator);
+        self
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.validator.is_none_or(|validator| validator(&self.value))
+    }
+}
+
+/// Gives a text input focus when it is spawned, with its whole value selected.
+#[derive(Component, Reflect, Default, Clone, Copy)]
+#[reflect(Component)]
+pub struct AutoFocus;
+
+/// The text input that has keyboard focus, and its caret and selection.
+/// Positions are character indices into the value.
+#[derive(Resource, Default, Debug)]
+pub struct TextInputFocus {
+    entity: Option<Entity>,
+    caret: usize,
+    /// Where the selection started, the selection is between the anchor and the caret.
+    anchor: Option<usize>,
+    /// The value when the input got focus, which is restored by Escape.
+    original: String,
+}
+
+impl TextInputFocus {
+    pub fn entity(&self) -> Option<Entity> {
+        self.entity
+    }
+
+    pub fn is_focused(&self) -> bool {
+        self.entity.is_some()
+    }
+
+    /// Gives the text input focus and selects its whole value.
+    pub fn focus(&mut self, entity: Entity, text_input: &TextInput) {
+        let length = text_input.value.chars().count();
+        *self = Self {
+            entity: Some(entity),
+            caret: length,
+            anchor: (length > 0).then_some(0),
+            original: text_input.value.clone(),
+        };
+    }
+
+    /// Sets the value of the text input, then gives it focus and selects the value.
+    pub fn focus_with_value(&mut self, entity: Entity, text_input: &mut TextInput, value: impl Into<String>) {
+        text_input.value = value.into();
+        self.focus(entity, text_input);
+    }
+
+    pub fn blur(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Returns the selected range, ordered and clamped to the given value.
+    fn selection(&self, value: &str) -> Option<(usize, usize)> {
+        let length = value.chars().count();
+        let caret = self.caret.min(length);
+        let anchor = self.anchor?.min(length);
+        (anchor != caret).then(|| (anchor.min(caret), anchor.max(caret)))
+    }
+}
+
+/// Clipboard for the text that is copied, cut and pasted in the editor. With the `system_clipboard` feature
+/// this is the clipboard of the operating system. Without it, or when that clipboard isn't available,
+/// the text is only shared within the editor.
+#[derive(Resource)]
+#[cfg_attr(not(feature = "system_clipboard"), derive(Default))]
+pub struct TextClipboard {
+    #[cfg(feature = "system_clipboard")]
+    system: Option<arboard::Clipboard>,
+    text: String,
+}
+
+#[cfg(feature = "system_clipboard")]
+impl Default for TextClipboard {
+    fn default() -> Self {
+        Self {
+            system: arboard::Clipboard::new()
+                .inspect_err(|error| warn!("The system clipboard is not available, text is only copied in the editor: {error}"))
+                .ok(),
+            text: String::new(),
+        }
+    }
+}
+
+impl TextClipboard {
+    pub fn get_text(&mut self) -> String {
+        #[cfg(feature = "system_clipboard")]
+        if let Some(system) = &mut self.system {
+            match system.get_text() {
+                Ok(text) => return text,
+                // The clipboard is empty or holds something else, like an image
+                Err(arboard::Error::ContentNotAvailable) => return String::new(),
+                Err(error) => warn!("Failed to paste from the system clipboard: {error}"),
+            }
+        }
+        self.text.clone()
+    }
+
+    pub fn set_text(&mut self, text: impl Into<String>) {
+        let text = text.into();
+        #[cfg(feature = "system_clipboard")]
+        if let Some(system) = &mut self.system
+            && let Err(error) = system.set_text(text.as_str())
+        {
+            warn!("Failed to copy to the system clipboard: {error}");
+        }
+        self.text = text;
+    }
+}
+
+/// Sent when the value of a text input was edited.
+#[derive(Message, Clone, Debug)]
+pub struct TextInputChanged {
+    pub entity: Entity,
+    pub value: String,
+}
+
+/// Sent when Enter is pressed in a text input with a valid value.
+#[derive(Message, Clone, Debug)]
+pub struct TextInputSubmitted {
+    pub entity: Entity,
+    pub value: String,
+}
+
+/// Sent when Escape is pressed in a text input.
+#[derive(Message, Clone, Debug)]
+pub struct TextInputCancelled {
+    pub entity: Entity,
+}
+
+/// Marks the text entity of a text input. Its spans show the text before, in and after the selection.
+#[derive(Component, Default, Clone, Copy)]
+pub struct TextInputText;
+
+/// Marks the caret of a text input, a thin bar that is placed over the text between the characters.
+#[derive(Component, Default, Clone, Copy)]
+pub struct TextInputCaret;
+
+/// Returns the byte index of a character index.
+fn byte_index(value: &str, index: usize) -> usize {
+    value.char_indices().nth(index).map_or(value.len(), |(byte_index, _)| byte_index)
+}
+
+/// Returns the character index of the start of the previous word.
+fn previous_word(value: &str, index: usize) -> usize {
+    let characters: Vec<char> = value.chars().collect();
+    let mut index = index.min(characters.len());
+    while index > 0 && characters[index - 1].is_whitespace() {
+        index -= 1;
+    }
+    while index > 0 && !characters[index - 1].is_whitespace() {
+        index -= 1;
+    }
+    index
+}
+
+/// Returns the character index of the end of the next word.
+fn next_word(value: &str, index: usize) -> usize {
+    let characters: Vec<char> = value.chars().collect();
+    let mut index = index.min(characters.len());
+    while index < characters.len() && characters[index].is_whitespace() {
+        index += 1;
+    }
+    while index < characters.len() && !characters[index].is_whitespace() {
+        index += 1;
+    }
+    index
+}
+
+/// Replaces the selection, or inserts at the caret, with the given text. The text is cut off at the maximum length.
+fn insert_text(text_input: &mut TextInput, focus: &mut TextInputFocus, text: &str) {
+    let (start, end) = focus
+        .selection(&text_input.value)
+        .unwrap_or((focus.caret, focus.caret));
+    let remaining = text_input.value.chars().count() - (end - start);
+    let allowed = text_input.max_length.map_or(usize::MAX, |max_length| max_length.saturating_sub(remaining));
+    let text: String = text.chars().filter(|character| !character.is_control()).take(allowed).collect();
+
+    let (start_byte, end_byte) = (byte_index(&text_input.value, start), byte_index(&text_input.value, end));
+    text_input.value.replace_range(start_byte..end_byte, &text);
+    focus.caret = start + text.chars().count();
+    focus.anchor = None;
+}
+
+/// Deletes the selection, or the characters between the caret and the given index.
+fn delete_to(text_input: &mut TextInput, focus: &mut TextInputFocus, index: usize) {
+    let (start, end) = focus
+        .selection(&text_input.value)
+        .unwrap_or((focus.caret.min(index), focus.caret.max(index)));
+    let (start_byte, end_byte) = (byte_index(&text_input.value, start), byte_index(&text_input.value, end));
+    text_input.value.replace_range(start_byte..end_byte, "");
+    focus.caret = start;
+    focus.anchor = None;
+}
+
+/// Spawns the text of new text inputs.
+fn add_text_input_text(mut commands: Commands, text_inputs: Query<(Entity, &TextInput), Added<TextInput>>) {
+    for (entity, text_input) in text_inputs.iter() {
+        let span = || {
+            (
+                EditorUiElement,
+                TextSpan::default(),
+                EditorTextColor(EditorColor::InputFieldText, None, None),
+                EditorFontSize(text_input.font_size),
+            )
+        };
+        commands.entity(entity).with_child((
+            EditorUiElement,
+            WidgetChild,
+            TextInputText,
+            Text::default(),
+            TextLayout::new_with_no_wrap(),
+            EditorFontSize(text_input.font_size),
+            children![span(), (span(), TextBackgroundColor(Color::NONE)), span()],
+        ));
+        commands.entity(entity).with_child((
+            EditorUiElement,
+            WidgetChild,
+            TextInputCaret,
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Px(1.0),
+                ..default()
+            },
+            EditorBackgroundColor::new(EditorColor::InputFieldText),
+            Visibility::Hidden,
+        ));
+    }
+}
+
+/// Focuses a text input when it is clicked or spawned with [`AutoFocus`], and removes the focus when something
+/// else is clicked or the input is despawned.
+pub fn update_text_input_focus(
+    mut focus: ResMut<TextInputFocus>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    text_inputs: Query<(Entity, &TextInput, &Interaction)>,
+    auto_focus: Query<(Entity, &TextInput), Added<AutoFocus>>,
+) {
+    if let Some(entity) = focus.entity
+        && !text_inputs.contains(entity)
+    {
+        focus.blur();
+    }
+    if mouse_buttons.just_pressed(MouseButton::Left) {
+        match text_inputs
+            .iter()
+            .find(|(_, _, interaction)| **interaction == Interaction::Pressed)
+        {
+            Some((entity, _, _)) if focus.entity == Some(entity) => {}
+            Some((entity, text_input, _)) => {
+                focus.focus(entity, text_input);
+                // Clicking puts the caret at the end instead of selecting everything
+                focus.anchor = None;
+            }
+            None => focus.blur(),
+        }
+    }
+    for (entity, text_input) in auto_focus.iter() {
+        focus.focus(entity, text_input);
+    }
+}
+
+/// Edits the focused text input with the keyboard and IME.
+#[allow(clippy::too_many_arguments)]
+fn handle_text_input_keyboard(
+    mut keyboard_input_reader: MessageReader<KeyboardInput>,
+    mut ime_reader: MessageReader<Ime>,
+    mut text_input_changed_writer: MessageWriter<TextInputChanged>,
+    mut text_input_submitted_writer: MessageWriter<TextInputSubmitted>,
+    mut text_input_cancelled_writer: MessageWriter<TextInputCancelled>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut focus: ResMut<TextInputFocus>,
+    mut clipboard: ResMut<TextClipboard>,
+    mut text_inputs: Query<&mut TextInput>,
+) {
+    let Some(entity) = focus.entity else {
+        keyboard_input_reader.clear();
+        ime_reader.clear();
+        return;
+    };
+    let Ok(mut text_input) = text_inputs.get_mut(entity) else {
+        return;
+    };
+    let primary_modifier = keys.any_pressed([
+        KeyCode::ControlLeft,
+        KeyCode::ControlRight,
+        KeyCode::SuperLeft,
+        KeyCode::SuperRight,
+    ]);
+    let alt = keys.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]);
+    let shift = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+
+    let value_before = text_input.value.clone();
+    let length = text_input.value.chars().count();
+    focus.caret = focus.caret.min(length);
+
+    for event in keyboard_input_reader.read() {
+        if event.state != ButtonState::Pressed {
+            continue;
+        }
+        let length = text_input.value.chars().count();
+        let caret = focus.caret;
+        // Moves the caret, extending the selection while Shift is held
+        let move_caret = |focus: &mut TextInputFocus, index: usize| {
+            if shift {
+                focus.anchor.get_or_insert(caret);
+            } else {
+                focus.anchor = None;
+            }
+            focus.caret = index;
+        };
+
+        match &event.logical_key {
+            Key::ArrowLeft => {
+                let index = match focus.selection(&text_input.value) {
+                    Some((start, _)) if !shift => start,
+                    _ if primary_modifier => previous_word(&text_input.value, caret),
+                    _ => caret.saturating_sub(1),
+                };
+                move_caret(&mut focus, index);
+            }
+            Key::ArrowRight => {
+                let index = match focus.selection(&text_input.value) {
+                    Some((_, end)) if !shift => end,
+                    _ if primary_modifier => next_word(&text_input.value, caret),
+                    _ => (caret + 1).min(length),
+                };
+                move_caret(&mut focus, index);
+            }
+            Key::Home => move_caret(&mut focus, 0),
+            Key::End => move_caret(&mut focus, length),
+            Key::Backspace => {
+                let index = if primary_modifier {
+                    previous_word(&text_input.value, caret)
+                } else {
+                    caret.saturating_sub(1)
+                };
+                delete_to(&mut text_input, &mut focus, index);
+            }
+            Key::Delete => {
+                let index = if primary_modifier {
+                    next_word(&text_input.value, caret)
+                } else {
+                    (caret + 1).min(length)
+                };
+                delete_to(&mut text_input, &mut focus, index);
+            }
+            Key::Enter if text_input.is_valid() => {
+                text_input_submitted_writer.write(TextInputSubmitted {
+                    entity,
+                    value: text_input.value.clone(),
+                });
+                focus.blur();
+                break;
+            }
+            Key::Escape => {
+                text_input.value = focus.original.clone();
+                text_input_cancelled_writer.write(TextInputCancelled { entity });
+                focus.blur();
+                break;
+            }
+            Key::Character(character) if primary_modifier && !alt => match character.to_lowercase().as_str() {
+                "a" => {
+                    focus.anchor = Some(0);
+                    focus.caret = length;
+                }
+                "c" | "x" => {
+                    if let Some((start, end)) = focus.selection(&text_input.value) {
+                        let (start_byte, end_byte) =
+                            (byte_index(&text_input.value, start), byte_index(&text_input.value, end));
+                        clipboard.set_text(&text_input.value[start_byte..end_byte]);
+                        if character.eq_ignore_ascii_case("x") {
+                            delete_to(&mut text_input, &mut focus, caret);
+                        }
+                    }
+                }
+                "v" => {
+                    let text = clipboard.get_text();
+                    insert_text(&mut text_input, &mut focus, &text);
+                }
+                _ => {}
+            },
+            _ if !primary_modifier => {
+                if let Some(text) = &event.text {
+                    insert_text(&mut text_input, &mut focus, text);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    for event in ime_reader.read() {
+        if let Ime::Commit { value, .. } = event
+            && focus.entity.is_some()
+        {
+            insert_text(&mut text_input, &mut focus, value);
+        }
+    }
+
+    if text_input.value != value_before {
+        text_input_changed_writer.write(TextInputChanged {
+            entity,
+            value: text_input.value.clone(),
+        });
+    }
+}
+
+/// Enables IME while a text input has focus, with the candidate box below the input.
+fn update_ime(
+    focus: Res<TextInputFocus>,
+    text_inputs: Query<(&UiGlobalTransform, &ComputedNode), With<TextInput>>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    if !focus.is_changed() {
+        return;
+    }
+    for mut window in windows.iter_mut() {
+        let focused = focus.entity.and_then(|entity| text_inputs.get(entity).ok());
+        if window.ime_enabled != focused.is_some() {
+            window.ime_enabled = focused.is_some();
+        }
+        if let Some((transform, computed_node)) = focused {
+            let bottom_left = transform.translation + computed_node.size() * Vec2::new(-0.5, 0.5);
+            window.ime_position = bottom_left * computed_node.inverse_scale_factor();
+        }
+    }
+}
+
+/// Shows the value, placeholder, caret and selection of the text inputs.
+fn update_text_input_text(
+    focus: Res<TextInputFocus>,
+    ui_colors: Res<UiColors>,
+    text_inputs: Query<(Entity, &TextInput, &Children)>,
+    texts: Query<&Children, With<TextInputText>>,
+    mut spans: Query<(&mut TextSpan, &mut EditorTextColor, &mut EditorFontSize, Option<&mut TextBackgroundColor>)>,
+) {
+    let selection_color = ui_colors.get(EditorColor::TextSelection);
+
+    for (entity, text_input, children) in text_inputs.iter() {
+        let Some(span_entities) = children.iter().find_map(|child| texts.get(child).ok()) else {
+            continue;
+        };
+        let is_focused = focus.entity == Some(entity);
+        let value = &text_input.value;
+
+        let (parts, color) = if value.is_empty() && !is_focused {
+            ([text_input.placeholder.clone(), String::new(), String::new()], EditorColor::FadedText)
+        } else {
+            let parts = match (is_focused, focus.selection(value)) {
+                (true, Some((start, end))) => {
+                    let (start, end) = (byte_index(value, start), byte_index(value, end));
+                    [value[..start].to_string(), value[start..end].to_string(), value[end..].to_string()]
+                }
+                // The caret is drawn between the first and the last span by update_text_input_caret
+                (true, None) => {
+                    let caret = byte_index(value, focus.caret);
+                    [value[..caret].to_string(), String::new(), value[caret..].to_string()]
+                }
+                (false, _) => [value.clone(), String::new(), String::new()],
+            };
+            let color = if text_input.is_valid() {
+                EditorColor::InputFieldText
+            } else {
+                EditorColor::Error
+            };
+            (parts, color)
+        };
+
+        for (span_entity, part) in span_entities.iter().zip(parts) {
+            let Ok((mut span, mut text_color, mut font_size, background_color)) = spans.get_mut(span_entity) else {
+                continue;
+            };
+            if span.0 != part {
+                span.0 = part;
+            }
+            if text_color.0 != color {
+                text_color.0 = color;
+            }
+            if font_size.0 != text_input.font_size {
+                font_size.0 = text_input.font_size;
+            }
+            if let Some(mut background_color) = background_color
+                && background_color.0 != selection_color
+            {
+                background_color.0 = selection_color;
+            }
+        }
+    }
+}
+
+/// Places the caret of the focused text input after the text before the caret, and hides the other carets.
+/// This runs after the text layout, as the position of the caret depends on the width of the text.
+fn update_text_input_caret(
+    focus: Res<TextInputFocus>,
+    text_inputs: Query<(Entity, &TextInput, &Children, &ComputedNode, &UiGlobalTransform)>,
+    texts: Query<(&Children, &TextLayoutInfo, &ComputedNode, &UiGlobalTransform), With<TextInputText>>,
+    mut carets: Query<(&mut Node, &mut Visibility), With<TextInputCaret>>,
+) {
+    for (entity, text_input, children, computed_node, transform) in text_inputs.iter() {
+        let Some(caret) = children.iter().find(|child| carets.contains(*child)) else {
+            continue;
+        };
+        let is_shown = focus.entity == Some(entity) && focus.selection(&text_input.value).is_none();
+        let text = children
+            .iter()
+            .find_map(|child| texts.get(child).ok())
+            .filter(|_| is_shown);
+        let Ok((mut node, mut visibility)) = carets.get_mut(caret) else {
+            continue;
+        };
+        let new_visibility = if text.is_some() { Visibility::Inherited } else { Visibility::Hidden };
+        if *visibility != new_visibility {
+            *visibility = new_visibility;
+        }
+        let Some((spans, layout_info, text_node, text_transform)) = text else {
+            continue;
+        };
+
+        // The right edge of the text before the caret, or the start of the text when there is none
+        let offset = spans
+            .first()
+            .and_then(|before| layout_info.section_rects.iter().rev().find(|(span, _)| *span == *before))
+            .map_or(0.0, |(_, rect)| rect.max.x);
+        // Absolute positions are relative to the padding box of the input, and the node positions are at their centers
+        let input_min = transform.translation - computed_node.size() / 2.0;
+        let text_min = text_transform.translation - text_node.size() / 2.0;
+        let border = computed_node.border();
+        let inset = computed_node.content_inset();
+        let scale = computed_node.inverse_scale_factor();
+        let left = Val::Px((text_min.x - input_min.x - border.left + offset) * scale);
+        let top = Val::Px((inset.top - border.top) * scale);
+        let height = Val::Px((computed_node.size().y - inset.top - inset.bottom) * scale);
+        if node.left != left || node.top != top || node.height != height {
+            node.left = left;
+            node.top = top;
+            node.height = height;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns a focused text input with the caret and the start of the selection at the given character indices.
+    fn focused(value: &str, caret: usize, anchor: Option<usize>) -> (TextInput, TextInputFocus) {
+        let focus = TextInputFocus {
+            entity: Some(Entity::PLACEHOLDER),
+            caret,
+            anchor,
+            original: value.to_string(),
+        };
+        (TextInput::new(value), focus)
+    }
+
+    #[test]
+    fn byte_indices_of_multi_byte_characters() {
+        let value = "aé€😀b";
+        assert_eq!(byte_index(value, 0), 0);
+        assert_eq!(byte_index(value, 1), 1);
+        assert_eq!(byte_index(value, 2), 3);
+        assert_eq!(byte_index(value, 3), 6);
+        assert_eq!(byte_index(value, 4), 10);
+        // Indices past the end are the end of the value
+        assert_eq!(byte_index(value, 5), value.len());
+        assert_eq!(byte_index(value, 9), value.len());
+    }
+
+    #[test]
+    fn word_jumps_skip_whitespace_and_stop_at_word_edges() {
+        let value = "one  twö three";
+        assert_eq!(previous_word(value, 14), 9);
+        assert_eq!(previous_word(value, 9), 5);
+        assert_eq!(previous_word(value, 6), 5);
+        assert_eq!(previous_word(value, 5), 0);
+        assert_eq!(previous_word(value, 0), 0);
+
+        assert_eq!(next_word(value, 0), 3);
+        assert_eq!(next_word(value, 3), 8);
+        assert_eq!(next_word(value, 8), 14);
+        assert_eq!(next_word(value, 14), 14);
+        // Indices past the end are clamped
+        assert_eq!(previous_word(value, 99), 9);
+        assert_eq!(next_word(value, 99), 14);
+    }
+
+    #[test]
+    fn inserting_at_the_caret() {
+        let (mut text_input, mut focus) = focused("héllo", 2, None);
+        insert_text(&mut text_input, &mut focus, "€€");
+        assert_eq!(text_input.value, "hé€€llo");
+        assert_eq!(focus.caret, 4);
+
+        // Control characters, e.g. from a pasted line break, are left out
+        insert_text(&mut text_input, &mut focus, "a\nb\t");
+        assert_eq!(text_input.value, "hé€€abllo");
+        assert_eq!(focus.caret, 6);
+    }
+
+    #[test]
+    fn inserting_replaces_the_selection() {
+        // The anchor can be on either side of the caret
+        for (caret, anchor) in [(1, 4), (4, 1)] {
+            let (mut text_input, mut focus) = focused("a😀€éb", caret, Some(anchor));
+            insert_text(&mut text_input, &mut focus, "xy");
+            assert_eq!(text_input.value, "axyb");
+            assert_eq!((focus.caret, focus.anchor), (3, None));
+        }
+    }
+
+    #[test]
+    fn inserting_is_cut_off_at_the_maximum_length() {
+        let (text_input, mut focus) = focused("abcd", 2, None);
+        let mut text_input = text_input.with_max_length(6);
+        insert_text(&mut text_input, &mut focus, "éééé");
+        assert_eq!(text_input.value, "abéécd");
+        assert_eq!(focus.caret, 4);
+        insert_text(&mut text_input, &mut focus, "x");
+        assert_eq!(text_input.value, "abéécd");
+        assert_eq!(focus.caret, 4);
+
+        // The selected characters don't count, as they are replaced
+        focus.anchor = Some(0);
+        insert_text(&mut text_input, &mut focus, "1234567");
+        assert_eq!(text_input.value, "1234cd");
+        assert_eq!(focus.caret, 4);
+
+        // A value that is already too long isn't cut off, but nothing can be added
+        let (text_input, mut focus) = focused("abcd", 4, None);
+        let mut text_input = text_input.with_max_length(2);
+        insert_text(&mut text_input, &mut focus, "e");
+        assert_eq!(text_input.value, "abcd");
+        assert_eq!(focus.caret, 4);
+    }
+
+    #[test]
+    fn deleting_to_an_index() {
+        // Backspace
+        let (mut text_input, mut focus) = focused("a€😀b", 3, None);
+        delete_to(&mut text_input, &mut focus, 2);
+        assert_eq!(text_input.value, "a€b");
+        assert_eq!(focus.caret, 2);
+
+        // Delete a word forwards
+        let (mut text_input, mut focus) = focused("één twee", 0, None);
+        let index = next_word(&text_input.value, focus.caret);
+        delete_to(&mut text_input, &mut focus, index);
+        assert_eq!(text_input.value, " twee");
+        assert_eq!(focus.caret, 0);
+
+        // Nothing to delete at the start
+        let (mut text_input, mut focus) = focused("abc", 0, None);
+        delete_to(&mut text_input, &mut focus, 0);
+        assert_eq!(text_input.value, "abc");
+    }
+
+    #[test]
+    fn deleting_removes_the_selection_instead() {
+        let (mut text_input, mut focus) = focused("ab€😀cd", 4, Some(1));
+        delete_to(&mut text_input, &mut focus, 5);
+        assert_eq!(text_input.value, "acd");
+        assert_eq!((focus.caret, focus.anchor), (1, None));
+    }
+}
@@ -0,0 +1,270 @@
+use std::any::TypeId;
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedFolder, io::Reader},
+    platform::collections::HashMap,
+    prelude::*,
+    reflect::{TypeRegistry, TypeRegistryArc, serde::TypedReflectDeserializer},
+    scene::ron,
+};
+
+use crate::ui::{
+    ClickAction, UiEvent,
+    colors::{EditorColor, MIN_CONTRAST_RATIO, UiColors},
+    elements::MenuBarButton,
+    menu::{MenuCommandsExt, MenuItem},
+    style::{Density, UiStyle},
+};
+
+/// Asset folder that contains the theme files.
+const THEME_FOLDER: &str = "themes";
+
+/// Id of the View > Theme submenu, which lists the themes.
+const THEME_MENU: &str = "view.theme";
+
+pub struct ThemePlugin;
+
+impl Plugin for ThemePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<Theme>()
+            .init_asset_loader::<ThemeLoader>()
+            .register_type::<ThemeFile>()
+            .add_systems(Startup, load_themes)
+            .add_systems(Update, (collect_themes, handle_theme_events))
+            .add_systems(Update, apply_theme.after(collect_themes).after(handle_theme_events))
+            .add_systems(
+                Update,
+                update_theme_menu_items
+                    .after(collect_themes)
+                    .after(handle_theme_events)
+                    .run_if(resource_changed::<Themes>),
+            );
+    }
+}
+
+/// A theme, loaded from a `.theme.ron` file in the `themes` asset folder.
+/// Colors that the theme doesn't set are taken from their fallback, see [`EditorColor::fallback`].
+#[derive(Asset, TypePath, Debug)]
+pub struct Theme {
+    pub name: String,
+    pub colors: HashMap<EditorColor, Color>,
+    pub density: Density,
+}
+
+impl Theme {
+    /// Parses the contents of a theme file. The registry must contain the types that [`ThemePlugin`] registers.
+    pub fn parse(bytes: &[u8], registry: &TypeRegistry) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let registration = registry
+            .get(TypeId::of::<ThemeFile>())
+            .ok_or("ThemeFile is not registered")?;
+        let value = ron::Options::default()
+            .from_bytes_seed(bytes, TypedReflectDeserializer::new(registration, registry))?;
+        let file = ThemeFile::from_reflect(value.as_ref()).ok_or("invalid theme file")?;
+
+        let mut colors = HashMap::new();
+        for (editor_color, hex) in file.colors {
+            let color = Srgba::hex(hex.trim()).map_err(|_| format!("{hex} is not a hex color"))?;
+            colors.insert(editor_color, color.into());
+        }
+        Ok(Theme {
+            name: file.name,
+            colors,
+            density: file.density,
+        })
+    }
+}
+
+/// Contents of a theme file, e.g. `(name: "Dark", density: Compact, colors: { MenuBar: "#3B3B3B", Overlay: "#00000080" })`.
+#[derive(Reflect, Debug)]
+struct ThemeFile {
+    name: String,
+    /// Comfortable if the theme doesn't set it.
+    #[reflect(default)]
+    density: Density,
+    /// Hex colors, with an optional alpha channel.
+    colors: HashMap<EditorColor, String>,
+}
+
+/// Reads theme files through the reflection of [`ThemeFile`].
+struct ThemeLoader {
+    type_registry: TypeRegistryArc,
+}
+
+impl FromWorld for ThemeLoader {
+    fn from_world(world: &mut World) -> Self {
+        Self {
+            type_registry: world.resource::<AppTypeRegistry>().0.clone(),
+        }
+    }
+}
+
+impl AssetLoader for ThemeLoader {
+    type Asset = Theme;
+    type Settings = ();
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Theme, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+
+        Theme::parse(&bytes, &self.type_registry.read())
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["theme.ron"]
+    }
+}
+
+/// The themes in the `themes` asset folder and the name of the theme that is applied.
+#[derive(Resource)]
+pub struct Themes {
+    folder: Handle<LoadedFolder>,
+    themes: Vec<Handle<Theme>>,
+    pub current: String,
+}
+
+impl Themes {
+    /// Returns the loaded theme with the given name.
+    pub fn get<'a>(&self, assets: &'a Assets<Theme>, name: &str) -> Option<(AssetId<Theme>, &'a Theme)> {
+        self.themes
+            .iter()
+            .filter_map(|handle| assets.get(handle).map(|theme| (handle.id(), theme)))
+            .find(|(_, theme)| theme.name == name)
+    }
+}
+
+fn load_themes(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(Themes {
+        folder: asset_server.load_folder(THEME_FOLDER),
+        themes: Vec::new(),
+        current: "Dark".to_string(),
+    });
+}
+
+/// Lists the themes in the View > Theme menu once the theme folder is loaded, and again whenever a theme is
+/// added, removed or renamed.
+fn collect_themes(
+    mut commands: Commands,
+    mut folder_event_reader: MessageReader<AssetEvent<LoadedFolder>>,
+    mut theme_event_reader: MessageReader<AssetEvent<Theme>>,
+    mut themes: ResMut<Themes>,
+    folders: Res<Assets<LoadedFolder>>,
+    theme_assets: Res<Assets<Theme>>,
+    menu_items: Query<(Entity, &ClickAction), With<MenuBarButton>>,
+) {
+    // The folder is loaded again when a file is added to or removed from it
+    let is_folder_loaded = folder_event_reader.read().any(|event| event.is_loaded_with_dependencies(&themes.folder));
+    // A changed theme may have a new name, which also changes its position in the menu
+    let is_theme_modified = theme_event_reader.read().any(|event| match event {
+        AssetEvent::Modified { id } => themes.themes.iter().any(|handle| handle.id() == *id),
+        _ => false,
+    });
+    if !is_folder_loaded && !is_theme_modified {
+        return;
+    }
+    let Some(folder) = folders.get(&themes.folder) else {
+        return;
+    };
+    let mut loaded: Vec<(String, Handle<Theme>)> = folder
+        .handles
+        .iter()
+        .filter_map(|handle| handle.clone().try_typed::<Theme>().ok())
+        .filter_map(|handle| theme_assets.get(&handle).map(|theme| (theme.name.clone(), handle)))
+        .collect();
+    loaded.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    for (entity, click_action) in menu_items.iter() {
+        if let UiEvent::SelectTheme(_) = click_action.0 {
+            commands.entity(entity).despawn();
+        }
+    }
+    let items: Vec<MenuItem> = loaded
+        .iter()
+        .map(|(name, _)| {
+            // A `&` in the name would be read as a mnemonic
+            MenuItem::new(name.replace('&', "&&"), UiEvent::SelectTheme(name.clone()))
+                .radio("theme", *name == themes.current)
+        })
+        .collect();
+    commands.extend_menu(THEME_MENU, move |menu| items.into_iter().fold(menu, |menu, item| menu.add(item)));
+    themes.themes = loaded.into_iter().map(|(_, handle)| handle).collect();
+}
+
+fn handle_theme_events(mut ui_event_reader: MessageReader<UiEvent>, mut themes: ResMut<Themes>) {
+    for event in ui_event_reader.read() {
+        if let UiEvent::SelectTheme(name) = event
+            && themes.current != *name
+        {
+            themes.current = name.clone();
+        }
+    }
+}
+
+/// Copies the colors and density of the current theme into [`UiColors`] and [`UiStyle`] when another theme is
+/// selected, and again whenever its file is changed, so edits to the theme are visible right away.
+fn apply_theme(
+    mut asset_event_reader: MessageReader<AssetEvent<Theme>>,
+    themes: Res<Themes>,
+    theme_assets: Res<Assets<Theme>>,
+    mut ui_colors: ResMut<UiColors>,
+    mut ui_style: ResMut<UiStyle>,
+) {
+    let Some((id, theme)) = themes.get(&theme_assets, &themes.current) else {
+        return;
+    };
+    let is_modified = asset_event_reader.read().any(|event| {
+        matches!(event, AssetEvent::Modified { id: modified } | AssetEvent::LoadedWithDependencies { id: modified } if *modified == id)
+    });
+    if !is_modified && !themes.is_changed() {
+        return;
+    }
+    *ui_colors = validate_theme(theme);
+    if ui_style.density != theme.density {
+        ui_style.density = theme.density;
+    }
+}
+
+/// Fills in the colors that the theme doesn't set, and warns about them and about text that is hard to read.
+fn validate_theme(theme: &Theme) -> UiColors {
+    let (colors, missing) = UiColors::from_theme(&theme.colors);
+    if !missing.is_empty() {
+        let missing: Vec<String> = missing
+            .iter()
+            .map(|editor_color| match editor_color.fallback() {
+                Some(fallback) => format!("{editor_color:?} (using {fallback:?})"),
+                None => format!("{editor_color:?} (using the default)"),
+            })
+            .collect();
+        warn!("Theme {} is missing colors: {}", theme.name, missing.join(", "));
+    }
+    let low_contrast_pairs = colors.low_contrast_pairs();
+    if !low_contrast_pairs.is_empty() {
+        let pairs: Vec<String> = low_contrast_pairs
+            .iter()
+            .map(|(text, background, ratio)| format!("{text:?} on {background:?} ({ratio:.2}:1)"))
+            .collect();
+        warn!(
+            "Theme {} has text with a contrast below {MIN_CONTRAST_RATIO}:1: {}",
+            theme.name,
+            pairs.join(", ")
+        );
+    }
+    colors
+}
+
+/// Checks the menu item of the current theme.
+fn update_theme_menu_items(themes: Res<Themes>, mut menu_bar_buttons: Query<(&mut MenuBarButton, &ClickAction)>) {
+    for (mut menu_bar_button, click_action) in menu_bar_buttons.iter_mut() {
+        if let UiEvent::SelectTheme(name) = &click_action.0 {
+            let is_checked = Some(*name == themes.current);
+            if menu_bar_button.is_checked != is_checked {
+                menu_bar_button.is_checked = is_checked;
+            }
+        }
+    }
+}
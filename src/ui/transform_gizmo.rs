@@ -0,0 +1,637 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::ui::{
+    CurrentTool, EditorUiCamera, EditorUiElement, Tool,
+    elements::CameraPreview,
+    history::{EditorHistoryCommandsExt, TransformChange, TransformCommand},
+    selection::Selected,
+    viewport_cursor_ray,
+};
+
+pub struct TransformGizmoPlugin;
+
+impl Plugin for TransformGizmoPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_gizmo_config(
+            TransformGizmoGroup,
+            GizmoConfig {
+                // Always draw the handles on top of the scene
+                depth_bias: -1.0,
+                line: GizmoLineConfig {
+                    width: 3.0,
+                    ..default()
+                },
+                ..default()
+            },
+        )
+        .init_resource::<TransformGizmo>()
+        .add_systems(Update, (update_transform_gizmo, draw_transform_gizmo).chain());
+    }
+}
+
+/// Gizmo config group for the translate, rotate and scale handles.
+#[derive(Default, Reflect, GizmoConfigGroup)]
+pub struct TransformGizmoGroup;
+
+/// Size of the handles relative to the distance between the camera and the gizmo,
+/// so they keep roughly the same size on screen.
+const GIZMO_SCALE: f32 = 0.2;
+/// Maximum distance between the cursor ray and a handle for it to be hovered, relative to the gizmo size.
+const HANDLE_PICK_DISTANCE: f32 = 0.06;
+/// Start and end of the plane handles along the two axes of the plane, relative to the gizmo size.
+const PLANE_HANDLE_RANGE: (f32, f32) = (0.25, 0.45);
+/// Radius of the center handle of the scale tool, relative to the gizmo size.
+const UNIFORM_HANDLE_RADIUS: f32 = 0.1;
+
+const HOVER_COLOR: Color = Color::srgb(1.0, 0.85, 0.1);
+const UNIFORM_COLOR: Color = Color::srgb(0.85, 0.85, 0.85);
+
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq)]
+pub enum GizmoAxis {
+    X,
+    Y,
+    Z,
+}
+
+impl GizmoAxis {
+    pub const ALL: [GizmoAxis; 3] = [GizmoAxis::X, GizmoAxis::Y, GizmoAxis::Z];
+
+    pub fn direction(self) -> Vec3 {
+        match self {
+            GizmoAxis::X => Vec3::X,
+            GizmoAxis::Y => Vec3::Y,
+            GizmoAxis::Z => Vec3::Z,
+        }
+    }
+
+    pub fn index(self) -> usize {
+        match self {
+            GizmoAxis::X => 0,
+            GizmoAxis::Y => 1,
+            GizmoAxis::Z => 2,
+        }
+    }
+
+    /// The two other axes, which span the plane perpendicular to this axis.
+    pub fn plane_axes(self) -> (GizmoAxis, GizmoAxis) {
+        match self {
+            GizmoAxis::X => (GizmoAxis::Y, GizmoAxis::Z),
+            GizmoAxis::Y => (GizmoAxis::X, GizmoAxis::Z),
+            GizmoAxis::Z => (GizmoAxis::X, GizmoAxis::Y),
+        }
+    }
+
+    pub fn color(self) -> Color {
+        match self {
+            GizmoAxis::X => Color::srgb(0.9, 0.2, 0.25),
+            GizmoAxis::Y => Color::srgb(0.4, 0.8, 0.1),
+            GizmoAxis::Z => Color::srgb(0.2, 0.45, 0.95),
+        }
+    }
+}
+
+/// A part of the gizmo that can be dragged.
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq)]
+pub enum GizmoHandle {
+    /// Constrains the edit to a single axis.
+    Axis(GizmoAxis),
+    /// Constrains the edit to the plane perpendicular to the axis.
+    Plane(GizmoAxis),
+    /// Scales uniformly along all axes.
+    Uniform,
+}
+
+/// An entity that is being edited by a drag, with its transform from before the drag started.
+#[derive(Debug, Clone)]
+pub struct GizmoTarget {
+    pub entity: Entity,
+    pub start: Transform,
+    start_translation: Vec3,
+    parent: Option<GlobalTransform>,
+}
+
+#[derive(Debug, Clone)]
+pub struct GizmoDrag {
+    pub tool: Tool,
+    pub handle: GizmoHandle,
+    pub targets: Vec<GizmoTarget>,
+    frame: GizmoFrame,
+    /// Normal of the plane the cursor ray is intersected with, for handles that are not a single axis.
+    plane_normal: Vec3,
+    view_right: Vec3,
+    start_point: Vec3,
+}
+
+/// State of the transform gizmo, so other systems can tell whether the cursor is busy with the gizmo.
+#[derive(Resource, Default)]
+pub struct TransformGizmo {
+    pub hovered: Option<GizmoHandle>,
+    pub drag: Option<GizmoDrag>,
+}
+
+/// Position, orientation and size of the gizmo.
+#[derive(Debug, Clone, Copy)]
+struct GizmoFrame {
+    origin: Vec3,
+    rotation: Quat,
+    size: f32,
+}
+
+impl GizmoFrame {
+    fn axis(&self, axis: GizmoAxis) -> Vec3 {
+        self.rotation * axis.direction()
+    }
+}
+
+fn gizmo_frame(tool: Tool, pivot: &GlobalTransform, camera_transform: &GlobalTransform) -> GizmoFrame {
+    let (_, rotation, origin) = pivot.to_scale_rotation_translation();
+    GizmoFrame {
+        origin,
+        // Scaling happens along the local axes, moving and rotating along the world axes
+        rotation: if tool == Tool::Scale { rotation } else { Quat::IDENTITY },
+        size: camera_transform.translation().distance(origin) * GIZMO_SCALE,
+    }
+}
+
+fn is_transform_tool(tool: Tool) -> bool {
+    matches!(tool, Tool::Move | Tool::Rotate | Tool::Scale)
+}
+
+/// Returns the distance along the axis and the distance along the ray of the points where they are closest,
+/// or None if they are parallel.
+fn closest_points(axis_origin: Vec3, axis: Vec3, ray: Ray3d) -> Option<(f32, f32)> {
+    let ray_direction = *ray.direction;
+    let offset = axis_origin - ray.origin;
+    let b = axis.dot(ray_direction);
+    let denominator = 1.0 - b * b;
+    if denominator.abs() < 1e-6 {
+        return None;
+    }
+    let d = axis.dot(offset);
+    let e = ray_direction.dot(offset);
+    Some(((b * e - d) / denominator, (e - b * d) / denominator))
+}
+
+fn handles(tool: Tool) -> Vec<GizmoHandle> {
+    let axes = GizmoAxis::ALL.map(GizmoHandle::Axis);
+    match tool {
+        Tool::Move => axes.into_iter().chain(GizmoAxis::ALL.map(GizmoHandle::Plane)).collect(),
+        Tool::Rotate => axes.to_vec(),
+        Tool::Scale => axes.into_iter().chain([GizmoHandle::Uniform]).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Returns the handle under the cursor ray, closest to the camera.
+fn pick_handle(tool: Tool, frame: &GizmoFrame, ray: Ray3d) -> Option<GizmoHandle> {
+    let pick_distance = frame.size * HANDLE_PICK_DISTANCE;
+    handles(tool)
+        .into_iter()
+        .filter_map(|handle| {
+            let ray_distance = match (tool, handle) {
+                (Tool::Rotate, GizmoHandle::Axis(axis)) => {
+                    let normal = frame.axis(axis);
+                    let distance = ray.intersect_plane(frame.origin, InfinitePlane3d::new(normal))?;
+                    let radius = ray.get_point(distance).distance(frame.origin);
+                    ((radius - frame.size).abs() < pick_distance).then_some(distance)
+                }
+                (_, GizmoHandle::Axis(axis)) => {
+                    let (along_axis, distance) = closest_points(frame.origin, frame.axis(axis), ray)?;
+                    let point = frame.origin + frame.axis(axis) * along_axis;
+                    (along_axis > 0.0
+                        && along_axis < frame.size
+                        && ray.get_point(distance).distance(point) < pick_distance)
+                        .then_some(distance)
+                }
+                (_, GizmoHandle::Plane(axis)) => {
+                    let distance = ray.intersect_plane(frame.origin, InfinitePlane3d::new(frame.axis(axis)))?;
+                    let offset = ray.get_point(distance) - frame.origin;
+                    let (u, v) = axis.plane_axes();
+                    let (min, max) = (PLANE_HANDLE_RANGE.0 * frame.size, PLANE_HANDLE_RANGE.1 * frame.size);
+                    let in_range = |value: f32| value > min && value < max;
+                    (in_range(offset.dot(frame.axis(u))) && in_range(offset.dot(frame.axis(v)))).then_some(distance)
+                }
+                (_, GizmoHandle::Uniform) => {
+                    let distance = (frame.origin - ray.origin).dot(*ray.direction);
+                    (ray.get_point(distance).distance(frame.origin) < frame.size * UNIFORM_HANDLE_RADIUS)
+                        .then_some(distance)
+                }
+            }?;
+            Some((handle, ray_distance))
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(handle, _)| handle)
+}
+
+/// Returns the point the cursor ray points at while dragging the handle.
+fn drag_point(drag: &GizmoDrag, ray: Ray3d) -> Option<Vec3> {
+    match (drag.tool, drag.handle) {
+        (Tool::Move | Tool::Scale, GizmoHandle::Axis(axis)) => {
+            let axis = drag.frame.axis(axis);
+            let (along_axis, _) = closest_points(drag.frame.origin, axis, ray)?;
+            Some(drag.frame.origin + axis * along_axis)
+        }
+        _ => {
+            let distance = ray.intersect_plane(drag.frame.origin, InfinitePlane3d::new(drag.plane_normal))?;
+            Some(ray.get_point(distance))
+        }
+    }
+}
+
+/// Returns the new transform of a target, for the cursor pointing at `point`.
+fn drag_transform(drag: &GizmoDrag, target: &GizmoTarget, point: Vec3) -> Transform {
+    let frame = &drag.frame;
+    let parent_rotation = target
+        .parent
+        .map(|parent| parent.to_scale_rotation_translation().1)
+        .unwrap_or_default();
+    let to_local = |world_offset: Vec3| match target.parent {
+        Some(parent) => parent.affine().inverse().transform_vector3(world_offset),
+        None => world_offset,
+    };
+    let mut transform = target.start;
+
+    match (drag.tool, drag.handle) {
+        (Tool::Move, _) => {
+            transform.translation += to_local(point - drag.start_point);
+        }
+        (Tool::Rotate, GizmoHandle::Axis(axis)) => {
+            let axis = frame.axis(axis);
+            let from = drag.start_point - frame.origin;
+            let to = point - frame.origin;
+            let angle = axis.dot(from.cross(to)).atan2(from.dot(to));
+            let rotation = Quat::from_axis_angle(axis, angle);
+
+            // Rotate the targets around the gizmo, not only around their own origin
+            let rotated_translation = frame.origin + rotation * (target.start_translation - frame.origin);
+            transform.translation += to_local(rotated_translation - target.start_translation);
+            transform.rotation = (parent_rotation.inverse() * rotation * parent_rotation) * target.start.rotation;
+        }
+        (Tool::Scale, GizmoHandle::Axis(axis)) => {
+            let direction = frame.axis(axis);
+            let start = (drag.start_point - frame.origin).dot(direction);
+            let current = (point - frame.origin).dot(direction);
+            if start.abs() > f32::EPSILON {
+                transform.scale[axis.index()] *= (current / start).max(0.01);
+            }
+        }
+        (Tool::Scale, GizmoHandle::Uniform) => {
+            let factor = 1.0 + (point - drag.start_point).dot(drag.view_right) / frame.size;
+            transform.scale *= factor.max(0.01);
+        }
+        _ => {}
+    }
+    transform
+}
+
+type SelectedTargets<'w, 's> = Query<
+    'w,
+    's,
+    (Entity, &'static GlobalTransform, Option<&'static ChildOf>),
+    (With<Selected>, With<Transform>, Without<EditorUiElement>),
+>;
+
+/// Returns the selected entities as drag targets. Entities with a selected ancestor are left out,
+/// because they already follow the transform of that ancestor.
+fn drag_targets(
+    selected: &SelectedTargets,
+    ancestors: &Query<&ChildOf>,
+    parents: &Query<&GlobalTransform>,
+    transforms: &Query<&Transform>,
+) -> Vec<GizmoTarget> {
+    selected
+        .iter()
+        .filter(|(entity, ..)| !ancestors.iter_ancestors(*entity).any(|ancestor| selected.contains(ancestor)))
+        .filter_map(|(entity, global_transform, child_of)| {
+            Some(GizmoTarget {
+                entity,
+                start: *transforms.get(entity).ok()?,
+                start_translation: global_transform.translation(),
+                parent: child_of.and_then(|child_of| parents.get(child_of.parent()).ok().copied()),
+            })
+        })
+        .collect()
+}
+
+/// Picks the hovered handle and applies dragging to the transforms of the selected entities.
+/// A drag only starts while the cursor is over the CameraPreview, like selecting with a click.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn update_transform_gizmo(
+    mut commands: Commands,
+    mut transform_gizmo: ResMut<TransformGizmo>,
+    current_tool: Res<CurrentTool>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    camera_preview: Single<&Interaction, With<CameraPreview>>,
+    cameras: Query<(&Camera, &GlobalTransform), (With<Camera3d>, Without<EditorUiCamera>)>,
+    selected: SelectedTargets,
+    ancestors: Query<&ChildOf>,
+    parents: Query<&GlobalTransform>,
+    mut transforms: Query<&mut Transform>,
+) {
+    let tool = current_tool.0;
+    if !is_transform_tool(tool) || mouse_buttons.just_released(MouseButton::Left) {
+        if transform_gizmo.drag.take().is_some() {
+            // The drag is finished, the next drag gets its own undo step
+            commands.seal_editor_history();
+        }
+        if !is_transform_tool(tool) {
+            transform_gizmo.hovered = None;
+            return;
+        }
+    }
+
+    let Some(((_, camera_transform), ray)) = viewport_cursor_ray(&window, cameras.iter()) else {
+        if transform_gizmo.drag.is_none() {
+            transform_gizmo.hovered = None;
+        }
+        return;
+    };
+
+    if let Some(drag) = &transform_gizmo.drag {
+        if let Some(point) = drag_point(drag, ray) {
+            let changes: Vec<TransformChange> = drag
+                .targets
+                .iter()
+                .map(|target| TransformChange {
+                    entity: target.entity,
+                    before: target.start,
+                    after: drag_transform(drag, target, point),
+                })
+                .collect();
+            for change in changes.iter() {
+                if let Ok(mut transform) = transforms.get_mut(change.entity) {
+                    *transform = change.after;
+                }
+            }
+            // Every frame of the drag is merged into a single undo step
+            if changes.iter().any(|change| change.before != change.after) {
+                commands.record_editor_command(TransformCommand {
+                    label: match drag.tool {
+                        Tool::Rotate => "Rotate",
+                        Tool::Scale => "Scale",
+                        _ => "Move",
+                    }
+                    .to_string(),
+                    changes,
+                });
+            }
+        }
+        return;
+    }
+
+    let Some((_, pivot, _)) = selected.iter().next() else {
+        transform_gizmo.hovered = None;
+        return;
+    };
+    let frame = gizmo_frame(tool, pivot, camera_transform);
+    // The cursor is over another UI element, e.g. an open menu, so the handles can't be hovered or dragged
+    let hovered = if **camera_preview == Interaction::None {
+        None
+    } else {
+        pick_handle(tool, &frame, ray)
+    };
+    if transform_gizmo.hovered != hovered {
+        transform_gizmo.hovered = hovered;
+    }
+
+    if let Some(handle) = hovered
+        && mouse_buttons.just_pressed(MouseButton::Left)
+    {
+        let plane_normal = match (tool, handle) {
+            (Tool::Rotate, GizmoHandle::Axis(axis)) | (_, GizmoHandle::Plane(axis)) => frame.axis(axis),
+            // Drag along a plane facing the camera
+            _ => camera_transform.forward().as_vec3(),
+        };
+        let mut drag = GizmoDrag {
+            tool,
+            handle,
+            targets: drag_targets(&selected, &ancestors, &parents, &transforms.as_readonly()),
+            frame,
+            plane_normal,
+            view_right: camera_transform.right().as_vec3(),
+            start_point: Vec3::ZERO,
+        };
+        if let Some(start_point) = drag_point(&drag, ray) {
+            drag.start_point = start_point;
+            transform_gizmo.drag = Some(drag);
+        }
+    }
+}
+
+/// Draws the handles of the current tool for the selected entity.
+#[allow(clippy::type_complexity)]
+fn draw_transform_gizmo(
+    mut gizmos: Gizmos<TransformGizmoGroup>,
+    transform_gizmo: Res<TransformGizmo>,
+    current_tool: Res<CurrentTool>,
+    cameras: Query<(&Camera, &GlobalTransform), (With<Camera3d>, Without<EditorUiCamera>)>,
+    selected: Query<&GlobalTransform, (With<Selected>, With<Transform>, Without<EditorUiElement>)>,
+) {
+    let tool = current_tool.0;
+    let Some(pivot) = selected.iter().next() else {
+        return;
+    };
+    let Some((_, camera_transform)) = cameras.iter().find(|(camera, _)| camera.is_active) else {
+        return;
+    };
+    if !is_transform_tool(tool) {
+        return;
+    }
+
+    let frame = match &transform_gizmo.drag {
+        // Keep the gizmo in place while dragging, so the handles don't move away from the cursor
+        Some(drag) if tool == Tool::Scale => drag.frame,
+        _ => gizmo_frame(tool, pivot, camera_transform),
+    };
+    let active = transform_gizmo
+        .drag
+        .as_ref()
+        .map(|drag| drag.handle)
+        .or(transform_gizmo.hovered);
+    let color = |handle: GizmoHandle, color: Color| if active == Some(handle) { HOVER_COLOR } else { color };
+
+    for handle in handles(tool) {
+        match (tool, handle) {
+            (Tool::Rotate, GizmoHandle::Axis(axis)) => {
+                let rotation = Quat::from_rotation_arc(Vec3::Z, frame.axis(axis));
+                gizmos
+                    .circle(Isometry3d::new(frame.origin, rotation), frame.size, color(handle, axis.color()))
+                    .resolution(64);
+            }
+            (Tool::Move, GizmoHandle::Axis(axis)) => {
+                gizmos.arrow(frame.origin, frame.origin + frame.axis(axis) * frame.size, color(handle, axis.color()));
+            }
+            (_, GizmoHandle::Axis(axis)) => {
+                let end = frame.origin + frame.axis(axis) * frame.size;
+                gizmos.line(frame.origin, end, color(handle, axis.color()));
+                gizmos.cuboid(
+                    Transform::from_translation(end)
+                        .with_rotation(frame.rotation)
+                        .with_scale(Vec3::splat(frame.size * 0.08)),
+                    color(handle, axis.color()),
+                );
+            }
+            (_, GizmoHandle::Plane(axis)) => {
+                let (u, v) = axis.plane_axes();
+                let center = (PLANE_HANDLE_RANGE.0 + PLANE_HANDLE_RANGE.1) / 2.0 * frame.size;
+                let rotation = frame.rotation * Quat::from_rotation_arc(Vec3::Z, axis.direction());
+                gizmos.rect(
+                    Isometry3d::new(frame.origin + (frame.axis(u) + frame.axis(v)) * center, rotation),
+                    Vec2::splat((PLANE_HANDLE_RANGE.1 - PLANE_HANDLE_RANGE.0) * frame.size),
+                    color(handle, axis.color()),
+                );
+            }
+            (_, GizmoHandle::Uniform) => {
+                gizmos.cuboid(
+                    Transform::from_translation(frame.origin)
+                        .with_rotation(frame.rotation)
+                        .with_scale(Vec3::splat(frame.size * UNIFORM_HANDLE_RADIUS * 2.0)),
+                    color(handle, UNIFORM_COLOR),
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    /// A drag of a gizmo at the world origin with a size of 1, which started at `start_point`.
+    fn drag(tool: Tool, handle: GizmoHandle, start_point: Vec3) -> GizmoDrag {
+        GizmoDrag {
+            tool,
+            handle,
+            targets: Vec::new(),
+            frame: GizmoFrame {
+                origin: Vec3::ZERO,
+                rotation: Quat::IDENTITY,
+                size: 1.0,
+            },
+            plane_normal: match handle {
+                GizmoHandle::Axis(axis) | GizmoHandle::Plane(axis) if tool != Tool::Scale => axis.direction(),
+                _ => Vec3::Z,
+            },
+            view_right: Vec3::X,
+            start_point,
+        }
+    }
+
+    fn target(start: Transform, parent: Option<GlobalTransform>) -> GizmoTarget {
+        let start_translation = match parent {
+            Some(parent) => parent.transform_point(start.translation),
+            None => start.translation,
+        };
+        GizmoTarget {
+            entity: Entity::PLACEHOLDER,
+            start,
+            start_translation,
+            parent,
+        }
+    }
+
+    #[test]
+    fn descendants_of_selected_entities_are_not_dragged() {
+        let mut world = World::new();
+        let mut spawn = |transform: Transform, global_transform: Vec3| {
+            world.spawn((transform, GlobalTransform::from_translation(global_transform))).id()
+        };
+        let parent = spawn(Transform::from_xyz(1.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        let child = spawn(Transform::from_xyz(0.0, 2.0, 0.0), Vec3::new(1.0, 2.0, 0.0));
+        let grandchild = spawn(Transform::default(), Vec3::new(1.0, 2.0, 0.0));
+        let unselected = spawn(Transform::from_xyz(0.0, 0.0, 3.0), Vec3::new(0.0, 0.0, 3.0));
+        let other = spawn(Transform::default(), Vec3::new(0.0, 0.0, 3.0));
+        world.entity_mut(child).insert(ChildOf(parent));
+        world.entity_mut(grandchild).insert(ChildOf(child));
+        world.entity_mut(other).insert(ChildOf(unselected));
+        for entity in [parent, child, grandchild, other] {
+            world.entity_mut(entity).insert(Selected);
+        }
+
+        let targets = world
+            .run_system_once(
+                |selected: SelectedTargets, ancestors: Query<&ChildOf>, parents: Query<&GlobalTransform>, transforms: Query<&Transform>| {
+                    drag_targets(&selected, &ancestors, &parents, &transforms)
+                },
+            )
+            .unwrap();
+        let entities: Vec<Entity> = targets.iter().map(|target| target.entity).collect();
+        assert_eq!(entities.len(), 2);
+        assert!(entities.contains(&parent) && entities.contains(&other));
+
+        // Only the parent moves, so its child follows it once
+        let drag = drag(Tool::Move, GizmoHandle::Axis(GizmoAxis::X), Vec3::ZERO);
+        let parent_target = targets.iter().find(|target| target.entity == parent).unwrap();
+        let transform = drag_transform(&drag, parent_target, Vec3::new(2.0, 0.0, 0.0));
+        assert_eq!(transform.translation, Vec3::new(3.0, 0.0, 0.0));
+        let other_target = targets.iter().find(|target| target.entity == other).unwrap();
+        assert_eq!(other_target.start_translation, Vec3::new(0.0, 0.0, 3.0));
+        assert_eq!(other_target.parent.map(|parent| parent.translation()), Some(Vec3::new(0.0, 0.0, 3.0)));
+    }
+
+    #[test]
+    fn closest_points_of_crossing_lines() {
+        let ray = Ray3d::new(Vec3::new(2.0, 0.0, 5.0), Dir3::NEG_Z);
+        assert_eq!(closest_points(Vec3::ZERO, Vec3::X, ray), Some((2.0, 5.0)));
+        assert_eq!(closest_points(Vec3::new(1.0, 0.0, 0.0), Vec3::X, ray), Some((1.0, 5.0)));
+    }
+
+    #[test]
+    fn closest_points_of_skew_lines() {
+        // The ray passes the axis one unit above it
+        let ray = Ray3d::new(Vec3::new(3.0, 1.0, -4.0), Dir3::Z);
+        assert_eq!(closest_points(Vec3::ZERO, Vec3::X, ray), Some((3.0, 4.0)));
+    }
+
+    #[test]
+    fn closest_points_of_parallel_lines() {
+        let ray = Ray3d::new(Vec3::new(0.0, 1.0, 0.0), Dir3::X);
+        assert_eq!(closest_points(Vec3::ZERO, Vec3::X, ray), None);
+        assert_eq!(closest_points(Vec3::ZERO, Vec3::NEG_X, ray), None);
+    }
+
+    #[test]
+    fn move_is_applied_in_the_parent_space() {
+        let drag = drag(Tool::Move, GizmoHandle::Axis(GizmoAxis::X), Vec3::ZERO);
+        let point = Vec3::new(2.0, 0.0, 0.0);
+
+        let root = target(Transform::from_xyz(1.0, 0.0, 0.0), None);
+        assert_eq!(drag_transform(&drag, &root, point).translation, Vec3::new(3.0, 0.0, 0.0));
+
+        let parent = GlobalTransform::from(Transform::from_scale(Vec3::splat(2.0)));
+        let child = target(Transform::from_xyz(1.0, 0.0, 0.0), Some(parent));
+        assert_eq!(drag_transform(&drag, &child, point).translation, Vec3::new(2.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn rotate_turns_targets_around_the_gizmo() {
+        let drag = drag(Tool::Rotate, GizmoHandle::Axis(GizmoAxis::Y), Vec3::X);
+        let target = target(Transform::from_xyz(1.0, 0.0, 0.0), None);
+
+        let transform = drag_transform(&drag, &target, Vec3::NEG_Z);
+
+        assert!(transform.translation.abs_diff_eq(Vec3::NEG_Z, 1e-5));
+        assert!(transform.rotation.abs_diff_eq(Quat::from_rotation_y(FRAC_PI_2), 1e-5));
+    }
+
+    #[test]
+    fn scale_follows_the_distance_to_the_gizmo() {
+        let axis_drag = drag(Tool::Scale, GizmoHandle::Axis(GizmoAxis::X), Vec3::X);
+        let target = target(Transform::from_scale(Vec3::new(2.0, 1.0, 1.0)), None);
+
+        let scale = drag_transform(&axis_drag, &target, Vec3::new(3.0, 0.5, 0.0)).scale;
+        assert_eq!(scale, Vec3::new(6.0, 1.0, 1.0));
+        // Dragging past the gizmo doesn't flip or collapse the scale
+        let scale = drag_transform(&axis_drag, &target, Vec3::NEG_X).scale;
+        assert_eq!(scale, Vec3::new(0.02, 1.0, 1.0));
+
+        let uniform_drag = drag(Tool::Scale, GizmoHandle::Uniform, Vec3::ZERO);
+        let scale = drag_transform(&uniform_drag, &target, Vec3::new(0.5, 0.0, 0.0)).scale;
+        assert_eq!(scale, Vec3::new(3.0, 1.5, 1.5));
+    }
+}
@@ -0,0 +1,38 @@
+use bevy::prelude::*;
+
+pub struct TransitionPlugin {
+    pub settings: TransitionSettings,
+}
+
+impl Plugin for TransitionPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.settings);
+    }
+}
+
+/// Durations and easing of the UI animations. A duration of zero turns the animation off.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct TransitionSettings {
+    /// Seconds it takes to fade between the normal, hover and pressed colors of an element.
+    pub color_duration: f32,
+    /// Seconds it takes to slide or fade the panels in or out when toggling fullscreen.
+    pub layout_duration: f32,
+    pub easing: EaseFunction,
+}
+
+impl Default for TransitionSettings {
+    fn default() -> Self {
+        Self {
+            color_duration: 0.12,
+            layout_duration: 0.2,
+            easing: EaseFunction::CubicInOut,
+        }
+    }
+}
+
+impl TransitionSettings {
+    /// Eases the linear progress of a transition, from 0 at the start to 1 at the end.
+    pub fn ease(&self, progress: f32) -> f32 {
+        self.easing.sample_clamped(progress.clamp(0.0, 1.0))
+    }
+}